@@ -1,5 +1,6 @@
 use anyhow::anyhow;
 use clap::{Arg, ArgMatches};
+use taxel::TaxType;
 
 // args for command
 pub const VERBOSE: &str = "verbose";
@@ -13,6 +14,35 @@ pub const LOG_DIR: &str = "log-dir";
 pub const TAX_TYPE: &str = "tax-type";
 pub const TAX_VERSION: &str = "tax-version";
 pub const PRINT: &str = "print";
+pub const TAXONOMY_DIR: &str = "taxonomy-dir";
+pub const ROUNDING: &str = "rounding";
+pub const PREVIOUS: &str = "previous";
+pub const OLD_FILE: &str = "old-file";
+pub const NEW_FILE: &str = "new-file";
+pub const FORMAT: &str = "format";
+pub const INPUT_FILES: &str = "input-files";
+pub const CONCEPT: &str = "concept";
+pub const CONTEXT: &str = "context";
+pub const UNIT: &str = "unit";
+pub const SUBTREE: &str = "subtree";
+pub const REPORT_FILE: &str = "report-file";
+pub const SOURCE_FILE: &str = "source-file";
+pub const CERTIFICATE_FILE: &str = "certificate-file";
+pub const PIN_ENV: &str = "pin-env";
+pub const PIN_FD: &str = "pin-fd";
+pub const PIN_PROMPT: &str = "pin-prompt";
+pub const ARCHIVE_DIR: &str = "archive-dir";
+pub const FORCE: &str = "force";
+pub const SUBMISSION: &str = "submission";
+pub const XML_DIR: &str = "xml-dir";
+pub const MANIFEST: &str = "manifest";
+pub const RUN_NAME: &str = "run-name";
+pub const KEEP_RUNS: &str = "keep-runs";
+pub const FROM: &str = "from";
+pub const TO: &str = "to";
+pub const FROM_TAXONOMY_DIR: &str = "from-taxonomy-dir";
+pub const TO_TAXONOMY_DIR: &str = "to-taxonomy-dir";
+pub const MAPPING_FILE: &str = "mapping-file";
 
 pub fn get_one<'a>(matches: &'a ArgMatches, id: &str) -> Result<&'a str, anyhow::Error> {
     match matches.get_one::<String>(id) {
@@ -21,18 +51,59 @@ pub fn get_one<'a>(matches: &'a ArgMatches, id: &str) -> Result<&'a str, anyhow:
     }
 }
 
+pub fn get_many<'a>(matches: &'a ArgMatches, id: &str) -> Result<Vec<&'a str>, anyhow::Error> {
+    match matches.get_many::<String>(id) {
+        Some(el) => Ok(el.map(|el| el.as_str()).collect()),
+        None => Err(anyhow!("Missing values for argument '{}'", id)),
+    }
+}
+
 pub fn get_maybe_one<'a>(matches: &'a ArgMatches, id: &str) -> Option<&'a str> {
     matches.get_one::<String>(id).map(|el| el.as_str())
 }
 
+/// Get the tax type and the tax version if given; the tax version must be
+/// supported by the tax type.
+pub fn get_tax_type_version(
+    matches: &ArgMatches,
+) -> Result<(TaxType, Option<&str>), anyhow::Error> {
+    let tax_type = get_one(matches, TAX_TYPE)?.parse::<TaxType>()?;
+    let tax_version = get_maybe_one(matches, TAX_VERSION);
+
+    if let Some(tax_version) = tax_version {
+        tax_type.check_version(tax_version)?;
+    }
+
+    Ok((tax_type, tax_version))
+}
+
 pub fn xml_file() -> Arg<'static> {
     Arg::new(XML_FILE)
         .long(XML_FILE)
-        .required(true)
+        .required(false)
+        .required_unless_present_any([XML_DIR, MANIFEST])
         .takes_value(true)
         .help("The path to the XML file to be validated.")
 }
 
+pub fn xml_dir() -> Arg<'static> {
+    Arg::new(XML_DIR)
+        .long(XML_DIR)
+        .required(false)
+        .takes_value(true)
+        .conflicts_with_all(&[XML_FILE, MANIFEST, REPORT_FILE, SOURCE_FILE])
+        .help("Process all xml files of the given directory in batch mode. The responses of each xml file are logged to a subdirectory of the log directory.")
+}
+
+pub fn manifest() -> Arg<'static> {
+    Arg::new(MANIFEST)
+        .long(MANIFEST)
+        .required(false)
+        .takes_value(true)
+        .conflicts_with_all(&[XML_FILE, REPORT_FILE, SOURCE_FILE])
+        .help("Process the xml files listed in the given csv file in batch mode. The column 'xml_file' is required; the columns 'tax_type' and 'tax_version' are optional. Paths are relative to the csv file.")
+}
+
 pub fn csv_file() -> Arg<'static> {
     Arg::new(CSV_FILE)
         .long(CSV_FILE)
@@ -71,7 +142,7 @@ pub fn tax_type() -> Arg<'static> {
         .required(false)
         .takes_value(true)
         .default_value("Bilanz")
-        .possible_values(["Bilanz"])
+        .possible_values(TaxType::ALL.map(|tax_type| tax_type.as_str()))
         .help("The tax type of the xml file.")
 }
 
@@ -80,11 +151,7 @@ pub fn tax_version() -> Arg<'static> {
         .long(TAX_VERSION)
         .required(false)
         .takes_value(true)
-        .default_value("6.5")
-        .possible_values([
-            "5.0", "5.1", "5.2", "5.3", "5.4", "6.0", "6.1", "6.2", "6.3", "6.4", "6.5",
-        ])
-        .help("The tax version of the xml file.")
+        .help("The tax version of the xml file, e.g. '6.5' for 'Bilanz' or '2024' for 'UStVA'. Defaults to the taxonomy version of the namespaces in the xml file, or the latest version of the tax type.")
 }
 
 pub fn print() -> Arg<'static> {
@@ -95,3 +162,111 @@ pub fn print() -> Arg<'static> {
         .takes_value(true)
         .help("Print the transmission confirmation as pdf file.")
 }
+
+pub fn taxonomy_dir() -> Arg<'static> {
+    Arg::new(TAXONOMY_DIR)
+        .long(TAXONOMY_DIR)
+        .required(false)
+        .takes_value(true)
+        .help("The directory of the local taxonomy files, e.g. 'test_data/schema/taxonomy/v6.6'. Used to determine the item types of concepts and to check calculations.")
+}
+
+pub fn rounding() -> Arg<'static> {
+    Arg::new(ROUNDING)
+        .long(ROUNDING)
+        .required(false)
+        .takes_value(true)
+        .default_value("half-up")
+        .possible_values(["half-up", "half-even"])
+        .help("The rounding mode for numeric values.")
+}
+
+pub fn format() -> Arg<'static> {
+    Arg::new(FORMAT)
+        .long(FORMAT)
+        .required(false)
+        .takes_value(true)
+        .default_value("table")
+        .possible_values(["table", "csv", "json"])
+        .help("The output format.")
+}
+
+pub fn report_file() -> Arg<'static> {
+    Arg::new(REPORT_FILE)
+        .long(REPORT_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("Write the errors and warnings of the validation as json file.")
+}
+
+pub fn source_file() -> Arg<'static> {
+    Arg::new(SOURCE_FILE)
+        .long(SOURCE_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("The csv or ods file the xml file was generated from. Errors and warnings of a concept point to the row of its value.")
+}
+
+pub fn certificate_file() -> Arg<'static> {
+    Arg::new(CERTIFICATE_FILE)
+        .long(CERTIFICATE_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("The path to the soft certificate (.pfx file) to sign the transmission. Defaults to the environment variable 'CERTIFICATE_PATH'.")
+}
+
+pub fn pin_env() -> Arg<'static> {
+    Arg::new(PIN_ENV)
+        .long(PIN_ENV)
+        .value_name("name")
+        .required(false)
+        .takes_value(true)
+        .conflicts_with_all(&[PIN_FD, PIN_PROMPT])
+        .help("Read the PIN of the certificate from the given environment variable. Defaults to 'CERTIFICATE_PASSWORD' if set, otherwise the PIN is prompted.")
+}
+
+pub fn pin_fd() -> Arg<'static> {
+    Arg::new(PIN_FD)
+        .long(PIN_FD)
+        .value_name("fd")
+        .required(false)
+        .takes_value(true)
+        .conflicts_with(PIN_PROMPT)
+        .help("Read the PIN of the certificate from the first line of the given file descriptor, e.g. '3' for '3< pin.txt'.")
+}
+
+pub fn pin_prompt() -> Arg<'static> {
+    Arg::new(PIN_PROMPT)
+        .long(PIN_PROMPT)
+        .required(false)
+        .takes_value(false)
+        .help("Prompt for the PIN of the certificate.")
+}
+
+pub fn archive_dir() -> Arg<'static> {
+    Arg::new(ARCHIVE_DIR)
+        .long(ARCHIVE_DIR)
+        .required(false)
+        .takes_value(true)
+        .default_value("archive")
+        .help("The directory of the archived submissions.")
+}
+
+pub fn run_name() -> Arg<'static> {
+    Arg::new(RUN_NAME)
+        .long(RUN_NAME)
+        .required(false)
+        .takes_value(true)
+        .default_value("timestamp")
+        .possible_values(["timestamp", "ticket"])
+        .help("The name of the directory of each run in the log directory. A run which sent a single xml file can be named by its transfer ticket; otherwise the start time is used.")
+}
+
+pub fn keep_runs() -> Arg<'static> {
+    Arg::new(KEEP_RUNS)
+        .long(KEEP_RUNS)
+        .value_name("n")
+        .required(false)
+        .takes_value(true)
+        .help("Keep only the latest n runs in the log directory and remove older ones. By default all runs are kept.")
+}
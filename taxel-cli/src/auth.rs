@@ -0,0 +1,213 @@
+//! The authentication with a soft certificate (`.pfx` file) and its PIN for
+//! sending xml files.
+//!
+//! The PIN is never passed as plain text on the command line; it is read from
+//! an environment variable, a file descriptor, or an interactive prompt.
+
+use anyhow::anyhow;
+use log::warn;
+use std::{
+    env,
+    fs::File,
+    io::{self, BufRead, BufReader, Read, Write},
+    path::Path,
+};
+
+pub use taxel_eric::{Credentials, CERTIFICATE_PASSWORD, CERTIFICATE_PATH};
+
+/// The source of the PIN of the certificate.
+#[derive(Debug, PartialEq, Clone)]
+pub enum PinSource {
+    /// The name of an environment variable.
+    Env(String),
+    /// A file descriptor, e.g. `3` for `3< pin.txt`; the first line is read.
+    Fd(i32),
+    /// An interactive prompt without echo.
+    Prompt,
+}
+
+/// Check that the certificate file exists and is readable.
+pub fn check_certificate(path: &Path) -> Result<(), anyhow::Error> {
+    if !path.is_file() {
+        return Err(anyhow!(
+            "Certificate file '{}' doesn't exist",
+            path.display()
+        ));
+    }
+
+    File::open(path)
+        .map_err(|err| anyhow!("Can't read certificate file '{}': {err}", path.display()))?;
+
+    let is_pfx = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pfx"));
+
+    if !is_pfx {
+        warn!("Certificate file '{}' is not a '.pfx' file", path.display());
+    }
+
+    Ok(())
+}
+
+/// Read the PIN from the given source; the PIN must not be empty.
+pub fn read_pin(source: &PinSource, certificate_path: &Path) -> Result<String, anyhow::Error> {
+    let pin = match source {
+        PinSource::Env(name) => {
+            env::var(name).map_err(|_| anyhow!("Missing PIN in environment variable '{name}'"))?
+        }
+        PinSource::Fd(fd) => read_pin_fd(*fd)?,
+        PinSource::Prompt => prompt_pin(&format!(
+            "PIN for certificate '{}': ",
+            certificate_path.display()
+        ))?,
+    };
+
+    if pin.is_empty() {
+        return Err(anyhow!("Empty PIN for certificate"));
+    }
+
+    Ok(pin)
+}
+
+/// Read the first line of the given reader without the line break.
+fn read_first_line<R>(reader: R) -> Result<String, anyhow::Error>
+where
+    R: Read,
+{
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line)?;
+
+    Ok(line.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+#[cfg(unix)]
+fn read_pin_fd(fd: i32) -> Result<String, anyhow::Error> {
+    use std::{mem::ManuallyDrop, os::fd::FromRawFd};
+
+    if fd < 0 || unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+        return Err(anyhow!("Invalid file descriptor '{fd}' for PIN"));
+    }
+
+    // The file descriptor is owned by the caller and must not be closed.
+    let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+
+    read_first_line(&*file)
+}
+
+#[cfg(not(unix))]
+fn read_pin_fd(_fd: i32) -> Result<String, anyhow::Error> {
+    Err(anyhow!(
+        "Reading the PIN from a file descriptor is not supported on this platform"
+    ))
+}
+
+/// Prompt for the PIN on the terminal without echo.
+#[cfg(unix)]
+fn prompt_pin(prompt: &str) -> Result<String, anyhow::Error> {
+    let fd = libc::STDIN_FILENO;
+
+    if unsafe { libc::isatty(fd) } != 1 {
+        return Err(anyhow!(
+            "Can't prompt for PIN without a terminal; use an environment variable or a file descriptor"
+        ));
+    }
+
+    eprint!("{prompt}");
+    io::stderr().flush()?;
+
+    let mut termios = unsafe { std::mem::zeroed::<libc::termios>() };
+
+    if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    let original = termios;
+    termios.c_lflag &= !libc::ECHO;
+    termios.c_lflag |= libc::ECHONL;
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) };
+
+    let pin = read_first_line(io::stdin().lock());
+
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+
+    pin
+}
+
+#[cfg(not(unix))]
+fn prompt_pin(_prompt: &str) -> Result<String, anyhow::Error> {
+    Err(anyhow!(
+        "Prompting for the PIN is not supported on this platform"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_certificate() {
+        let dir = env::temp_dir().join("taxel_test_check_certificate");
+        std::fs::create_dir_all(&dir).unwrap();
+        let certificate_path = dir.join("certificate.pfx");
+        std::fs::write(&certificate_path, b"pfx").unwrap();
+
+        assert!(check_certificate(&certificate_path).is_ok());
+        assert_eq!(
+            check_certificate(&dir.join("missing.pfx"))
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Certificate file '{}' doesn't exist",
+                dir.join("missing.pfx").display()
+            )
+        );
+        assert!(check_certificate(&dir).is_err());
+    }
+
+    #[test]
+    fn test_read_pin() {
+        let certificate_path = Path::new("certificate.pfx");
+        env::set_var("TAXEL_TEST_READ_PIN", "123456");
+
+        assert_eq!(
+            read_pin(
+                &PinSource::Env(String::from("TAXEL_TEST_READ_PIN")),
+                certificate_path
+            )
+            .unwrap(),
+            "123456"
+        );
+        assert!(read_pin(
+            &PinSource::Env(String::from("TAXEL_TEST_READ_PIN_MISSING")),
+            certificate_path
+        )
+        .is_err());
+        assert!(read_pin(&PinSource::Fd(-1), certificate_path).is_err());
+        assert_eq!(read_first_line(&b"123456\r\nrest"[..]).unwrap(), "123456");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_read_pin_fd() {
+        use std::os::fd::AsRawFd;
+
+        let pin_path = env::temp_dir().join("taxel_test_read_pin_fd.txt");
+        std::fs::write(&pin_path, "654321\n").unwrap();
+        let file = File::open(&pin_path).unwrap();
+
+        let pin = read_pin(
+            &PinSource::Fd(file.as_raw_fd()),
+            Path::new("certificate.pfx"),
+        );
+
+        assert_eq!(pin.unwrap(), "654321");
+    }
+
+    #[test]
+    fn test_debug_credentials() {
+        let credentials = Credentials::new("certificate.pfx", "123456");
+
+        assert!(!format!("{credentials:?}").contains("123456"));
+    }
+}
@@ -0,0 +1,358 @@
+//! Validate or send many xml files in one run, e.g. the filings of all
+//! clients of a tax office.
+
+use super::{
+    run_log::{FilingSummary, RunLog},
+    utils,
+};
+use crate::arg;
+use anyhow::anyhow;
+use clap::ArgMatches;
+use serde::Deserialize;
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use taxel::{CsvReaderBuilder, TaxType, TaxonomyVersion, Trim};
+use taxel_eric::{exit::ExitError, Outcome};
+
+const HEADER: [&str; 5] = ["xml_file", "status", "errors", "warnings", "log_dir"];
+
+/// A xml file to be processed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Filing {
+    pub xml_file: PathBuf,
+    pub tax_type: String,
+    pub tax_version: String,
+}
+
+impl Filing {
+    /// A xml file of the given tax type. The tax version defaults to the
+    /// taxonomy version of the namespaces in the xml file, or the latest version
+    /// of the tax type.
+    ///
+    /// A warning is printed if the given tax version doesn't match the
+    /// namespaces in the xml file.
+    pub fn new(xml_file: PathBuf, tax_type: TaxType, tax_version: Option<&str>) -> Self {
+        let detected_version = detect_version(&xml_file, tax_type);
+        let tax_version = match (tax_version, detected_version) {
+            (Some(tax_version), Some(detected_version)) => {
+                if tax_version != detected_version {
+                    println!(
+                        "Warning: Tax version '{tax_version}' doesn't match the taxonomy version '{detected_version}' of the namespaces in '{}'",
+                        xml_file.display()
+                    );
+                }
+
+                tax_version
+            }
+            (Some(tax_version), None) => tax_version,
+            (None, Some(detected_version)) => detected_version,
+            (None, None) => tax_type.latest_version(),
+        };
+
+        Self {
+            xml_file,
+            tax_type: tax_type.to_string(),
+            tax_version: tax_version.to_owned(),
+        }
+    }
+}
+
+/// Detect the taxonomy version of an eBilanz; unreadable xml files are
+/// reported when they are processed.
+fn detect_version(xml_file: &Path, tax_type: TaxType) -> Option<&'static str> {
+    if tax_type != TaxType::Bilanz {
+        return None;
+    }
+
+    let xml = fs::read_to_string(xml_file).ok()?;
+
+    match TaxonomyVersion::detect(&xml) {
+        Ok(taxonomy) => taxonomy.map(|taxonomy| taxonomy.version),
+        Err(err) => {
+            println!("Warning: {err} in '{}'", xml_file.display());
+            None
+        }
+    }
+}
+
+/// A row of the manifest file; missing values default to the command line
+/// arguments.
+#[derive(Debug, Deserialize)]
+struct ManifestRow {
+    xml_file: String,
+    #[serde(default)]
+    tax_type: Option<String>,
+    #[serde(default)]
+    tax_version: Option<String>,
+}
+
+/// Check if the xml files are given by directory or manifest.
+pub fn is_batch(matches: &ArgMatches) -> bool {
+    matches.contains_id(arg::XML_DIR) || matches.contains_id(arg::MANIFEST)
+}
+
+/// Get the xml files of the directory or the manifest.
+pub fn filings(matches: &ArgMatches) -> Result<Vec<Filing>, anyhow::Error> {
+    let (tax_type, tax_version) = arg::get_tax_type_version(matches)?;
+
+    if let Some(xml_dir) = arg::get_maybe_one(matches, arg::XML_DIR) {
+        read_dir(Path::new(xml_dir), tax_type, tax_version)
+    } else if let Some(manifest) = arg::get_maybe_one(matches, arg::MANIFEST) {
+        read_manifest(Path::new(manifest), tax_type, tax_version)
+    } else {
+        Err(anyhow!(
+            "Missing argument '--{}' or '--{}'",
+            arg::XML_DIR,
+            arg::MANIFEST
+        ))
+    }
+}
+
+/// Get the xml files of a directory in alphabetical order.
+fn read_dir(
+    xml_dir: &Path,
+    tax_type: TaxType,
+    tax_version: Option<&str>,
+) -> Result<Vec<Filing>, anyhow::Error> {
+    let mut xml_files = vec![];
+
+    for entry in fs::read_dir(xml_dir)
+        .map_err(|err| anyhow!("Can't read directory '{}': {err}", xml_dir.display()))?
+    {
+        let path = entry?.path();
+        let is_xml = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"));
+
+        if path.is_file() && is_xml {
+            xml_files.push(path);
+        }
+    }
+
+    if xml_files.is_empty() {
+        return Err(anyhow!("Missing xml files in '{}'", xml_dir.display()));
+    }
+
+    xml_files.sort();
+
+    Ok(xml_files
+        .into_iter()
+        .map(|xml_file| Filing::new(xml_file, tax_type, tax_version))
+        .collect())
+}
+
+/// Get the xml files listed in a csv file; paths are relative to the csv
+/// file.
+///
+/// The tax version of a row with another tax type than the command line
+/// argument is not taken from the command line.
+fn read_manifest(
+    manifest: &Path,
+    tax_type: TaxType,
+    tax_version: Option<&str>,
+) -> Result<Vec<Filing>, anyhow::Error> {
+    let base_dir = manifest.parent().unwrap_or(Path::new(""));
+    let mut reader = CsvReaderBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .trim(Trim::All)
+        .from_path(manifest)
+        .map_err(|err| anyhow!("Can't read manifest '{}': {err}", manifest.display()))?;
+    let mut filings = vec![];
+
+    for row in reader.deserialize() {
+        let row: ManifestRow = row?;
+        let row_tax_type = match row.tax_type.filter(|tax_type| !tax_type.is_empty()) {
+            Some(row_tax_type) => row_tax_type.parse::<TaxType>()?,
+            None => tax_type,
+        };
+        let row_tax_version = row
+            .tax_version
+            .as_deref()
+            .filter(|tax_version| !tax_version.is_empty());
+        let row_tax_version = match row_tax_version {
+            Some(row_tax_version) => {
+                row_tax_type.check_version(row_tax_version)?;
+                Some(row_tax_version)
+            }
+            None if row_tax_type == tax_type => tax_version,
+            None => None,
+        };
+
+        filings.push(Filing::new(
+            base_dir.join(row.xml_file),
+            row_tax_type,
+            row_tax_version,
+        ));
+    }
+
+    if filings.is_empty() {
+        return Err(anyhow!("Missing xml files in '{}'", manifest.display()));
+    }
+
+    Ok(filings)
+}
+
+/// Get a log directory per filing named by the xml file, e.g.
+/// `<log-dir>/ebilanz` for `ebilanz.xml`.
+fn log_dirs(log_path: &Path, filings: &[Filing]) -> Vec<PathBuf> {
+    let mut names = HashSet::new();
+
+    filings
+        .iter()
+        .map(|filing| {
+            let stem = filing
+                .xml_file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or(String::from("filing"));
+            let mut name = stem.clone();
+            let mut counter = 2;
+
+            // Xml files with the same name in different directories
+            while !names.insert(name.clone()) {
+                name = format!("{stem}-{counter}");
+                counter += 1;
+            }
+
+            log_path.join(name)
+        })
+        .collect()
+}
+
+/// Process the filings one after another in subdirectories of the run
+/// directory; failed filings don't stop the batch.
+///
+/// A summary table is printed at the end. Returns an [`ExitError`] with the
+/// highest exit code of the failed filings.
+pub fn run<F>(run_log: &mut RunLog, filings: &[Filing], mut process: F) -> Result<(), anyhow::Error>
+where
+    F: FnMut(&Filing, &Path) -> Result<Outcome, anyhow::Error>,
+{
+    for (filing, log_dir) in filings.iter().zip(log_dirs(run_log.path(), filings)) {
+        println!("==> {} <==", filing.xml_file.display());
+
+        let outcome = fs::create_dir_all(&log_dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|_| process(filing, &log_dir));
+
+        if let Err(err) = run_log.record(filing, &log_dir, outcome) {
+            println!("Error: {err}");
+        }
+
+        println!();
+    }
+
+    let mut stdout = io::stdout().lock();
+    write_summary(&mut stdout, run_log.filings())?;
+
+    let results = run_log.filings();
+    let failed = results
+        .iter()
+        .filter(|result| result.exit_code != 0)
+        .count();
+
+    match results.iter().map(|result| result.exit_code).max() {
+        Some(code) if code != 0 => Err(ExitError::new(
+            code,
+            format!("{failed} of {} xml files failed", results.len()),
+        )
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+fn write_summary<W>(writer: &mut W, results: &[FilingSummary]) -> Result<(), anyhow::Error>
+where
+    W: io::Write,
+{
+    let count = |count: Option<usize>| count.map(|count| count.to_string()).unwrap_or_default();
+    let rows = results
+        .iter()
+        .map(|result| {
+            vec![
+                result.xml_file.clone(),
+                result.status.clone(),
+                count(result.errors),
+                count(result.warnings),
+                result.log_dir.clone(),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    utils::write_table(writer, &HEADER, &rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_filing_tax_version() {
+        let xml_file =
+            PathBuf::from("../test_data/taxonomy/v6.4/SteuerbilanzAutoverkaeufer_PersG.xml");
+
+        // Detected by the namespaces
+        let filing = Filing::new(xml_file.clone(), TaxType::Bilanz, None);
+        assert_eq!(filing.tax_version, "6.4");
+
+        // The given tax version is used despite a mismatch
+        let filing = Filing::new(xml_file, TaxType::Bilanz, Some("6.5"));
+        assert_eq!(filing.tax_version, "6.5");
+
+        // Defaults to the latest version of the tax type
+        let filing = Filing::new(PathBuf::from("missing.xml"), TaxType::UStVA, None);
+        assert_eq!(filing.tax_version, "2024");
+    }
+
+    #[test]
+    fn test_read_manifest() {
+        let dir = env::temp_dir().join("taxel_test_read_manifest");
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = dir.join("manifest.csv");
+        fs::write(
+            &manifest,
+            "xml_file,tax_type,tax_version\n\
+             client_a/ebilanz.xml,,6.4\n\
+             client_b/ebilanz.xml,,\n\
+             client_c/ustva.xml,UStVA,\n",
+        )
+        .unwrap();
+
+        let filings = read_manifest(&manifest, TaxType::Bilanz, Some("6.5")).unwrap();
+
+        assert_eq!(
+            filings,
+            vec![
+                Filing {
+                    xml_file: dir.join("client_a/ebilanz.xml"),
+                    tax_type: String::from("Bilanz"),
+                    tax_version: String::from("6.4"),
+                },
+                Filing {
+                    xml_file: dir.join("client_b/ebilanz.xml"),
+                    tax_type: String::from("Bilanz"),
+                    tax_version: String::from("6.5"),
+                },
+                Filing {
+                    xml_file: dir.join("client_c/ustva.xml"),
+                    tax_type: String::from("UStVA"),
+                    tax_version: String::from("2024"),
+                },
+            ]
+        );
+        assert_eq!(
+            log_dirs(Path::new("logs"), &filings),
+            vec![
+                PathBuf::from("logs/ebilanz"),
+                PathBuf::from("logs/ebilanz-2"),
+                PathBuf::from("logs/ustva")
+            ]
+        );
+    }
+}
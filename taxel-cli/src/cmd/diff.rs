@@ -0,0 +1,269 @@
+//! Compare the facts of two xml files in the XBRL standard.
+
+use super::utils;
+use crate::arg::{self, NEW_FILE, OLD_FILE};
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches};
+use log::debug;
+use std::{
+    fs::File,
+    io::{self, BufReader, Write},
+};
+use taxel::{CsvWriterBuilder, Fact, FactDiff, Reader, XbrlElement};
+
+const HEADER: [&str; 9] = [
+    "change",
+    "concept",
+    "context",
+    "occurrence",
+    "old_value",
+    "new_value",
+    "delta",
+    "delta_percent",
+    "attributes",
+];
+
+pub fn old_file() -> Arg<'static> {
+    Arg::new(OLD_FILE)
+        .index(1)
+        .required(true)
+        .takes_value(true)
+        .help("The path to the original xml file.")
+}
+
+pub fn new_file() -> Arg<'static> {
+    Arg::new(NEW_FILE)
+        .index(2)
+        .required(true)
+        .takes_value(true)
+        .help("The path to the changed xml file.")
+}
+
+pub fn diff_args() -> [Arg<'static>; 3] {
+    [old_file(), new_file(), arg::format()]
+}
+
+pub fn diff(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let old_file = arg::get_one(matches, arg::OLD_FILE)?;
+    let new_file = arg::get_one(matches, arg::NEW_FILE)?;
+    let format = arg::get_one(matches, arg::FORMAT)?;
+
+    debug!(
+        "Run `taxel diff` with configuration:\n{}={}\n{}={}\n{}={}",
+        arg::OLD_FILE,
+        old_file,
+        arg::NEW_FILE,
+        new_file,
+        arg::FORMAT,
+        format,
+    );
+
+    let old_facts = read_facts(old_file)?;
+    let new_facts = read_facts(new_file)?;
+    let diffs = taxel::diff_facts(&old_facts, &new_facts);
+
+    let mut stdout = io::stdout().lock();
+    write_diffs(&mut stdout, &diffs, format)?;
+
+    Ok(())
+}
+
+fn read_facts(xml_file: &str) -> Result<Vec<Fact>, anyhow::Error> {
+    let file =
+        File::open(xml_file).map_err(|err| anyhow!("Can't open file '{xml_file}': {err}"))?;
+    let mut xml_reader = Reader::from_reader(BufReader::new(file));
+    xml_reader.trim_text(true);
+
+    let element = XbrlElement::parse(&mut xml_reader)?;
+
+    Ok(element.facts())
+}
+
+/// Write the differences as table, csv, or json.
+pub fn write_diffs<W>(writer: &mut W, diffs: &[FactDiff], format: &str) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    match format {
+        "table" => {
+            if diffs.is_empty() {
+                writeln!(writer, "No differences found")?;
+            } else {
+                let rows = diffs.iter().map(to_row).collect::<Vec<_>>();
+                utils::write_table(writer, &HEADER, &rows)?;
+            }
+        }
+        "csv" => {
+            let mut csv_writer = CsvWriterBuilder::new()
+                .delimiter(b',')
+                .has_headers(false)
+                .from_writer(writer);
+            csv_writer.write_record(HEADER)?;
+
+            for diff in diffs {
+                csv_writer.write_record(to_row(diff))?;
+            }
+
+            csv_writer.flush()?;
+        }
+        "json" => {
+            serde_json::to_writer_pretty(&mut *writer, diffs)?;
+            writeln!(writer)?;
+        }
+        other => return Err(anyhow!("Invalid format: {other}")),
+    }
+
+    Ok(())
+}
+
+fn to_row(diff: &FactDiff) -> Vec<String> {
+    vec![
+        diff.change.to_string(),
+        diff.concept.clone(),
+        diff.context.clone(),
+        diff.occurrence.to_string(),
+        display_value(diff.old.as_ref()),
+        display_value(diff.new.as_ref()),
+        diff.delta
+            .map(|delta| delta.to_string())
+            .unwrap_or_default(),
+        diff.percentage
+            .map(|percentage| format!("{percentage}%"))
+            .unwrap_or_default(),
+        diff.attributes.join(" "),
+    ]
+}
+
+fn display_value(fact: Option<&Fact>) -> String {
+    match fact {
+        Some(fact) if fact.nil => String::from("nil"),
+        Some(fact) => fact.value.clone().unwrap_or_default(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+    use taxel::Change;
+
+    fn diffs() -> Vec<FactDiff> {
+        let fact = |value: Option<&str>| {
+            Fact::new(
+                "de-gaap-ci:bs.ass",
+                "I-AKTJAHR",
+                Some(String::from("EUR")),
+                Some(String::from("2")),
+                value.map(String::from),
+                value.is_none(),
+            )
+        };
+
+        taxel::diff_facts(&[fact(Some("200.00")), fact(None)], &[fact(Some("250.00"))])
+    }
+
+    #[test]
+    fn test_write_diffs_table() {
+        let mut output = vec![];
+
+        write_diffs(&mut output, &diffs(), "table").unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "change   concept            context    occurrence  old_value  new_value  delta  delta_percent  attributes\n\
+             -------  -----------------  ---------  ----------  ---------  ---------  -----  -------------  ----------\n\
+             changed  de-gaap-ci:bs.ass  I-AKTJAHR  1           200.00     250.00     50.00  25.00%\n\
+             removed  de-gaap-ci:bs.ass  I-AKTJAHR  2           nil\n"
+        );
+    }
+
+    #[test]
+    fn test_write_diffs_csv() {
+        let mut output = vec![];
+
+        write_diffs(&mut output, &diffs(), "csv").unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "change,concept,context,occurrence,old_value,new_value,delta,delta_percent,attributes\n\
+             changed,de-gaap-ci:bs.ass,I-AKTJAHR,1,200.00,250.00,50.00,25.00%,\n\
+             removed,de-gaap-ci:bs.ass,I-AKTJAHR,2,nil,,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let old_facts =
+            read_facts("../test_data/taxonomy/v6.4/HandelsbilanzLandwirt_GmbH.xml").unwrap();
+        let new_facts =
+            read_facts("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml").unwrap();
+
+        let diffs = taxel::diff_facts(&old_facts, &new_facts);
+        let identical = taxel::diff_facts(&old_facts, &old_facts);
+
+        assert!(!old_facts.is_empty());
+        assert!(identical.is_empty());
+
+        let count = |change: Change| diffs.iter().filter(|diff| diff.change == change).count();
+        assert_eq!(count(Change::Changed), 10);
+        assert_eq!(count(Change::Removed), 9);
+        assert_eq!(count(Change::Added), 83);
+
+        let mut output = vec![];
+        write_diffs(&mut output, &diffs, "json").unwrap();
+        let json = serde_json::from_slice::<serde_json::Value>(&output).unwrap();
+        let entries = json.as_array().unwrap();
+        assert_eq!(entries.len(), diffs.len());
+
+        let entry = |concept: &str| {
+            entries
+                .iter()
+                .find(|entry| entry["concept"] == concept)
+                .unwrap()
+        };
+
+        let changed =
+            entry("de-gaap-ci:is.netIncome.regular.operatingTC.otherOpRevenue.insuranceRefunds");
+        assert_eq!(changed["change"], "changed");
+        assert_eq!(changed["context"], "D-AKTJAHR");
+        assert_eq!(changed["occurrence"], 1);
+        assert_eq!(changed["old"]["value"], "8599.34");
+        assert_eq!(changed["new"]["value"], "4983.34");
+        assert_eq!(changed["delta"], "-3616.00");
+        assert_eq!(changed["percentage"], "-42.05");
+
+        let removed = entry("de-gaap-ci:bs.eqLiab.liab.shareholders.gmbhSilent");
+        assert_eq!(removed["change"], "removed");
+        assert_eq!(removed["old"]["nil"], true);
+        assert!(removed["new"].is_null());
+
+        let added =
+            entry("de-bra:is.netIncome.regular.operatingTC.otherOpRevenue.fromOtherPeriods");
+        assert_eq!(added["change"], "added");
+        assert!(added["old"].is_null());
+        assert_eq!(added["new"]["value"], "3616.00");
+    }
+
+    #[test]
+    fn test_diff_args() {
+        let args = vec![cmd::BIN, cmd::DIFF, "a.xml", "b.xml", "--format", "json"];
+
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::DIFF).unwrap();
+
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::OLD_FILE).unwrap(),
+            "a.xml"
+        );
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::NEW_FILE).unwrap(),
+            "b.xml"
+        );
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::FORMAT).unwrap(),
+            "json"
+        );
+    }
+}
@@ -61,3 +61,44 @@ pub fn extract(matches: &ArgMatches) -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_extract_escaped_values() {
+        let dir = env::temp_dir().join(format!("taxel_test_extract_escaped_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let xml_file = dir.join("ebilanz.xml");
+        let output_file = dir.join("ebilanz.csv");
+        fs::write(
+            &xml_file,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance">
+                <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Müller &amp; Söhne GmbH</de-gcd:genInfo.company.id.name>
+            </xbrli:xbrl>"#,
+        )
+        .unwrap();
+        let args = vec![
+            cmd::BIN,
+            cmd::EXTRACT,
+            "--xml-file",
+            xml_file.to_str().unwrap(),
+            "--output-file",
+            output_file.to_str().unwrap(),
+        ];
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::EXTRACT).unwrap();
+
+        extract(subcommand_matches).unwrap();
+
+        let csv = fs::read_to_string(&output_file).unwrap();
+        assert!(csv.contains("de-gcd:genInfo.company.id.name,Müller & Söhne GmbH"));
+        assert!(!csv.contains("&amp;"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
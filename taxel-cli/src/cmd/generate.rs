@@ -9,30 +9,50 @@ use std::{
     io::{BufRead, BufReader},
     path::{Path, PathBuf},
 };
-use taxel::{CsvReaderBuilder, Reader, Tags, Trim, Writer, XbrlElement};
+use taxel::{
+    CalculationMismatch, CsvReaderBuilder, Reader, Rounding, SourceMap, Tags, TaxType,
+    TaxonomySchema, Trim, Writer, XbrlElement,
+};
 
-pub fn generate_args() -> [Arg<'static>; 3] {
-    [arg::csv_file(), arg::template_file(), arg::output_file()]
+pub fn generate_args() -> [Arg<'static>; 6] {
+    [
+        arg::tax_type(),
+        arg::csv_file(),
+        arg::template_file(),
+        arg::output_file(),
+        arg::taxonomy_dir(),
+        arg::rounding(),
+    ]
 }
 
 pub fn generate(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let tax_type = arg::get_one(matches, arg::TAX_TYPE)?.parse::<TaxType>()?;
     let csv_file = arg::get_maybe_one(matches, arg::CSV_FILE);
     let template_file = arg::get_one(matches, arg::TEMPLATE_FILE)?;
     let output_file = arg::get_maybe_one(matches, arg::OUTPUT_FILE);
+    let taxonomy_dir = arg::get_maybe_one(matches, arg::TAXONOMY_DIR);
+    let rounding = arg::get_one(matches, arg::ROUNDING)?.parse::<Rounding>()?;
     let csv_path = csv_file.map(Path::new);
+    let taxonomy_path = taxonomy_dir.map(Path::new);
     let output_path = match output_file {
         Some(output_file) => PathBuf::from(output_file),
         None => current_dir()?,
     };
 
     debug!(
-        "Run `taxel generate` with configuration:\n{}={:?}\n{}={}\n{}={:?}",
+        "Run `taxel generate` with configuration:\n{}={}\n{}={:?}\n{}={}\n{}={:?}\n{}={:?}\n{}={}",
+        arg::TAX_TYPE,
+        tax_type,
         arg::CSV_FILE,
         csv_file,
         arg::TEMPLATE_FILE,
         template_file,
         arg::OUTPUT_FILE,
         output_file,
+        arg::TAXONOMY_DIR,
+        taxonomy_dir,
+        arg::ROUNDING,
+        rounding,
     );
 
     // Read the csv file
@@ -62,9 +82,27 @@ pub fn generate(matches: &ArgMatches) -> Result<(), anyhow::Error> {
     // Format XML file
     let mut xml_writer = Writer::new_with_indent(output_file, b' ', 4);
 
-    let target_tags = taxel::read_tags(csv_reader.as_mut())?;
+    // Record the provenance of the values to locate the mismatches
+    let target_tags = match (csv_reader.as_mut(), csv_file) {
+        (Some(csv_reader), Some(csv_file)) => taxel::read_tags_from(csv_reader, csv_file)?,
+        _ => Tags::new(),
+    };
+
+    let (mismatches, sources) = update_values(
+        target_tags,
+        tax_type,
+        taxonomy_path,
+        rounding,
+        &mut xml_reader,
+        &mut xml_writer,
+    )?;
 
-    update_values(target_tags, &mut xml_reader, &mut xml_writer)?;
+    for mismatch in mismatches {
+        match sources.get(&mismatch.concept) {
+            Some(provenance) => println!("Warning: {mismatch} ({provenance})"),
+            None => println!("Warning: {mismatch}"),
+        }
+    }
 
     // Flush the output XML writer and finalize the file
     xml_writer.into_inner().sync_all()?;
@@ -73,31 +111,44 @@ pub fn generate(matches: &ArgMatches) -> Result<(), anyhow::Error> {
 }
 
 /// Update values for xbrl tags.
+///
+/// The procedure and data type of the transfer header are set for the given
+/// tax type.
+///
+/// If a taxonomy directory is given, the taxonomy schema referenced by the
+/// template determines `decimals` and `unitRef` of numeric facts, and the
+/// calculations are checked after rounding. Returns the totals which don't
+/// add up, and the provenance of the applied values by concept.
 pub fn update_values<R, W>(
-    mut target_tags: Tags,
+    target_tags: Tags,
+    tax_type: TaxType,
+    taxonomy_path: Option<&Path>,
+    rounding: Rounding,
     xml_reader: &mut Reader<R>,
     xml_writer: &mut Writer<W>,
-) -> Result<(), anyhow::Error>
+) -> Result<(Vec<CalculationMismatch>, SourceMap), anyhow::Error>
 where
     R: std::io::Read + BufRead,
     W: std::io::Write,
 {
-    target_tags.add_required_tags();
-    target_tags.remove_unsupported_tags();
     let mut element = XbrlElement::parse(xml_reader)?;
+    let schema = match taxonomy_path {
+        Some(taxonomy_path) => TaxonomySchema::discover(taxonomy_path, &element.schema_refs())?,
+        None => TaxonomySchema::default(),
+    };
     element.remove_values();
-    element.add_values(&target_tags);
+    let generated = element.generate_values(target_tags, tax_type, &schema, rounding);
     taxel::write_declaration(xml_writer)?;
     element.serialize(xml_writer)?;
 
-    Ok(())
+    Ok(generated)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io::Cursor;
-    use taxel::{remove_formatting, Tags};
+    use taxel::{remove_formatting, Provenance, Tags};
 
     // Helper function to test updated tags.
     fn test_update_target_tags(xml: &str, expected_xml: &str, target_tags: Tags) {
@@ -105,7 +156,15 @@ mod tests {
         reader.trim_text(true);
         let mut writer = Writer::new(Cursor::new(Vec::new()));
 
-        update_values(target_tags, &mut reader, &mut writer).unwrap();
+        update_values(
+            target_tags,
+            TaxType::Bilanz,
+            None,
+            Rounding::default(),
+            &mut reader,
+            &mut writer,
+        )
+        .unwrap();
 
         let actual = writer.into_inner().into_inner();
         let expected = remove_formatting(expected_xml).unwrap();
@@ -244,4 +303,129 @@ mod tests {
 
         test_update_target_tags(actual_xbrl, expected_xbrl, target_tags);
     }
+
+    #[test]
+    fn test_update_escaped_values() {
+        let xbrl = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <xbrli:xbrl xmlns:xbrli="http://www.xbrl.org/2003/instance">
+                <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Müller &amp; Söhne GmbH</de-gcd:genInfo.company.id.name>
+                <de-gcd:genInfo.company.id.location contextRef="D-AKTJAHR" xsi:nil="true"/>
+            </xbrli:xbrl>"#;
+        let mut reader = Reader::from_str(xbrl);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let mut target_tags = Tags::new();
+        target_tags.insert(
+            "de-gcd:genInfo.company.id.name",
+            Some("Müller & Söhne GmbH"),
+        );
+        target_tags.insert("de-gcd:genInfo.company.id.location", Some("<Berlin>"));
+
+        update_values(
+            target_tags,
+            TaxType::Bilanz,
+            None,
+            Rounding::default(),
+            &mut reader,
+            &mut writer,
+        )
+        .unwrap();
+
+        // Values are escaped exactly once
+        let actual = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+        assert!(actual.contains("Müller &amp; Söhne GmbH"));
+        assert!(actual.contains("&lt;Berlin&gt;"));
+        assert!(!actual.contains("&amp;amp;"));
+    }
+
+    #[test]
+    fn test_update_values_ustva() {
+        let template =
+            std::fs::read_to_string("../templates/elster_v11/ustva_v2024/ustva.xml").unwrap();
+        let mut reader = Reader::from_str(&template);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let mut target_tags = Tags::new();
+        target_tags.insert("Verfahren", Some("ElsterBilanz"));
+        target_tags.insert("Steuernummer", Some("9198011310010"));
+        target_tags.insert("Zeitraum", Some("03"));
+        target_tags.insert("Kz81", Some("1000"));
+        target_tags.insert("Kz66", Some("150.00"));
+
+        update_values(
+            target_tags,
+            TaxType::UStVA,
+            None,
+            Rounding::default(),
+            &mut reader,
+            &mut writer,
+        )
+        .unwrap();
+
+        let actual = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+        assert!(actual.contains("<Verfahren>ElsterAnmeldung</Verfahren>"));
+        assert!(actual.contains("<DatenArt>UStVA</DatenArt>"));
+        assert!(actual.contains(
+            r#"<Anmeldungssteuern xmlns="http://finkonsens.de/elster/elsteranmeldung/ustva/v2024" art="UStVA" version="2024">"#
+        ));
+        assert!(actual.contains("<Steuernummer>9198011310010</Steuernummer>"));
+        assert!(actual.contains("<Zeitraum>03</Zeitraum>"));
+        assert!(actual.contains("<Kz81>1000</Kz81>"));
+        assert!(actual.contains("<Kz66>150.00</Kz66>"));
+    }
+
+    #[test]
+    fn test_update_values_rounding() {
+        let xbrl = r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <xbrli:xbrl xmlns:de-gaap-ci="http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02" xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xbrli="http://www.xbrl.org/2003/instance" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+                <link:schemaRef xlink:href="http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02/de-gaap-ci-2022-05-02-shell-fiscal-microbilg.xsd" xlink:type="simple"/>
+                <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" xsi:nil="true"/>
+                <de-gaap-ci:bs.ass.fixAss contextRef="I-AKTJAHR" xsi:nil="true"/>
+                <de-gaap-ci:bs.ass.currAss contextRef="I-AKTJAHR" xsi:nil="true"/>
+            </xbrli:xbrl>"#;
+        let mut reader = Reader::from_str(xbrl);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        let mut target_tags = Tags::new();
+        target_tags.insert_with_provenance(
+            "de-gaap-ci:bs.ass",
+            Some("300.01"),
+            Provenance::new("balance.csv", 2, 2),
+        );
+        target_tags.insert("de-gaap-ci:bs.ass.fixAss", Some("100.004"));
+        target_tags.insert("de-gaap-ci:bs.ass.currAss", Some("200.004"));
+        target_tags.insert_with_provenance(
+            "de-gaap-ci:bs.eqLiab",
+            Some("300.00"),
+            Provenance::new("balance.csv", 3, 2),
+        );
+
+        let (mismatches, sources) = update_values(
+            target_tags,
+            TaxType::Bilanz,
+            Some(Path::new("../test_data/schema/taxonomy/v6.6")),
+            Rounding::HalfUp,
+            &mut reader,
+            &mut writer,
+        )
+        .unwrap();
+
+        let actual = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+        assert!(actual.contains(r#"<de-gaap-ci:bs.ass.fixAss contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">100.00</de-gaap-ci:bs.ass.fixAss>"#));
+        assert!(actual.contains(
+            r#"<xbrli:unit id="EUR"><xbrli:measure>iso4217:EUR</xbrli:measure></xbrli:unit>"#
+        ));
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].concept, "de-gaap-ci:bs.ass");
+        assert_eq!(mismatches[0].expected.to_string(), "300.00");
+        assert_eq!(mismatches[0].actual.to_string(), "300.01");
+        // Only the applied values are located
+        assert_eq!(sources.len(), 1);
+        assert_eq!(
+            sources.get("de-gaap-ci:bs.ass"),
+            Some(&Provenance::new("balance.csv", 2, 2))
+        );
+    }
 }
@@ -0,0 +1,249 @@
+//! List and inspect the submissions archived by `taxel send`.
+
+use super::utils;
+use crate::arg::{self, SUBMISSION};
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches};
+use log::debug;
+use std::io::{self, Write};
+use taxel::CsvWriterBuilder;
+use taxel_eric::archive::{
+    Archive, Submission, CHECKSUM_FILE, METADATA_FILE, PDF_FILE, SERVER_RESPONSE_FILE,
+    VALIDATION_RESPONSE_FILE, XML_FILE,
+};
+
+const HEADER: [&str; 6] = [
+    "id",
+    "timestamp",
+    "status",
+    "transfer_ticket",
+    "tax_version",
+    "xml_file",
+];
+
+pub fn submission() -> Arg<'static> {
+    Arg::new(SUBMISSION)
+        .index(1)
+        .required(false)
+        .takes_value(true)
+        .help("The id of the submission to be inspected. If no id is specified all submissions are listed.")
+}
+
+pub fn history_args() -> [Arg<'static>; 3] {
+    [submission(), arg::archive_dir(), arg::format()]
+}
+
+pub fn history(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let submission = arg::get_maybe_one(matches, arg::SUBMISSION);
+    let archive_dir = arg::get_one(matches, arg::ARCHIVE_DIR)?;
+    let format = arg::get_one(matches, arg::FORMAT)?;
+
+    debug!(
+        "Run `taxel history` with configuration:\n{}={:?}\n{}={}\n{}={}",
+        arg::SUBMISSION,
+        submission,
+        arg::ARCHIVE_DIR,
+        archive_dir,
+        arg::FORMAT,
+        format,
+    );
+
+    let archive = Archive::new(archive_dir);
+    let mut stdout = io::stdout().lock();
+
+    match submission {
+        Some(id) => {
+            let submission = archive.get(id)?;
+            write_submission(&mut stdout, &archive, &submission, format)
+        }
+        None => write_submissions(&mut stdout, &archive.submissions()?, format),
+    }
+}
+
+/// Write the submissions as table, csv, or json.
+pub fn write_submissions<W>(
+    writer: &mut W,
+    submissions: &[Submission],
+    format: &str,
+) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    match format {
+        "table" => {
+            let rows = submissions.iter().map(to_row).collect::<Vec<_>>();
+            utils::write_table(writer, &HEADER, &rows)?;
+        }
+        "csv" => {
+            let mut csv_writer = CsvWriterBuilder::new()
+                .delimiter(b',')
+                .has_headers(false)
+                .from_writer(writer);
+            csv_writer.write_record(HEADER)?;
+
+            for submission in submissions {
+                csv_writer.write_record(to_row(submission))?;
+            }
+
+            csv_writer.flush()?;
+        }
+        "json" => {
+            serde_json::to_writer_pretty(&mut *writer, submissions)?;
+            writeln!(writer)?;
+        }
+        other => return Err(anyhow!("Invalid format: {other}")),
+    }
+
+    Ok(())
+}
+
+/// Write the details and the archived files of a submission.
+///
+/// Returns an error if the archived xml file doesn't match its hash.
+pub fn write_submission<W>(
+    writer: &mut W,
+    archive: &Archive,
+    submission: &Submission,
+    format: &str,
+) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    let is_verified = archive.verify(submission)?;
+
+    if format == "json" {
+        serde_json::to_writer_pretty(&mut *writer, submission)?;
+        writeln!(writer)?;
+    } else {
+        let path = archive.path(&submission.id);
+        let checksum = if is_verified { "verified" } else { "MISMATCH" };
+
+        writeln!(writer, "id:              {}", submission.id)?;
+        writeln!(writer, "timestamp:       {}", submission.timestamp)?;
+        writeln!(writer, "status:          {}", status(submission))?;
+        writeln!(writer, "error code:      {}", submission.error_code)?;
+        writeln!(
+            writer,
+            "transfer ticket: {}",
+            submission.transfer_ticket.as_deref().unwrap_or("-")
+        )?;
+        writeln!(
+            writer,
+            "tax type:        {} {}",
+            submission.tax_type, submission.tax_version
+        )?;
+        writeln!(writer, "xml file:        {}", submission.xml_file)?;
+        writeln!(
+            writer,
+            "sha256:          {} ({checksum})",
+            submission.sha256
+        )?;
+        writeln!(writer, "taxel version:   {}", submission.taxel_version)?;
+        writeln!(writer, "files:")?;
+
+        for file in [
+            XML_FILE,
+            CHECKSUM_FILE,
+            VALIDATION_RESPONSE_FILE,
+            SERVER_RESPONSE_FILE,
+            PDF_FILE,
+            METADATA_FILE,
+        ] {
+            let file_path = path.join(file);
+
+            if file_path.is_file() {
+                writeln!(writer, "  {}", file_path.display())?;
+            }
+        }
+    }
+
+    if !is_verified {
+        return Err(anyhow!(
+            "Archived xml file of submission '{}' doesn't match its sha256",
+            submission.id
+        ));
+    }
+
+    Ok(())
+}
+
+fn status(submission: &Submission) -> &'static str {
+    if submission.accepted {
+        "accepted"
+    } else {
+        "rejected"
+    }
+}
+
+fn to_row(submission: &Submission) -> Vec<String> {
+    vec![
+        submission.id.clone(),
+        submission.timestamp.clone(),
+        String::from(status(submission)),
+        submission.transfer_ticket.clone().unwrap_or_default(),
+        submission.tax_version.clone(),
+        submission.xml_file.clone(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+    use clap::crate_version;
+    use std::{env, fs, str};
+    use taxel_eric::{archive::Transmission, MockEric};
+
+    #[test]
+    fn test_history_args() {
+        let args = [cmd::BIN, cmd::HISTORY, "--archive-dir", "archive"];
+
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::HISTORY).unwrap();
+
+        assert_eq!(
+            arg::get_maybe_one(subcommand_matches, arg::SUBMISSION),
+            None
+        );
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::FORMAT).unwrap(),
+            "table"
+        );
+    }
+
+    #[test]
+    fn test_write_submissions() {
+        let root = env::temp_dir().join("taxel_test_write_submissions");
+        let _ = fs::remove_dir_all(&root);
+        let archive = Archive::new(&root);
+        let response = MockEric::success(true);
+        let transmission = Transmission {
+            xml: "<Elster/>",
+            xml_file: "ebilanz.xml",
+            tax_type: "Bilanz",
+            tax_version: "6.5",
+            response: &response,
+            taxel_version: crate_version!(),
+            pdf_path: None,
+        };
+        let submission = archive.store(&transmission, true).unwrap();
+
+        let mut table = vec![];
+        write_submissions(&mut table, &archive.submissions().unwrap(), "table").unwrap();
+        let mut details = vec![];
+        write_submission(&mut details, &archive, &submission, "table").unwrap();
+
+        let table = str::from_utf8(&table).unwrap();
+        assert!(table.starts_with("id "));
+        assert!(table.contains(&submission.id));
+        assert!(table.contains("accepted"));
+        assert!(table.contains("mock-transfer-ticket"));
+        let details = str::from_utf8(&details).unwrap();
+        assert!(details.contains(&format!(
+            "sha256:          {} (verified)",
+            submission.sha256
+        )));
+        assert!(details.contains(SERVER_RESPONSE_FILE));
+        assert!(!details.contains(PDF_FILE));
+    }
+}
@@ -0,0 +1,186 @@
+//! Merge several partial csv or xml files into one file.
+
+use crate::arg::{self, INPUT_FILES, OUTPUT_FILE};
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches};
+use log::debug;
+use std::{fs::File, io::BufReader, path::Path};
+use taxel::{
+    CsvReaderBuilder, CsvWriterBuilder, MergeConflict, Reader, Tags, Trim, Writer, XbrlElement,
+};
+
+pub fn input_files() -> Arg<'static> {
+    Arg::new(INPUT_FILES)
+        .index(1)
+        .required(true)
+        .takes_value(true)
+        .multiple_values(true)
+        .min_values(2)
+        .help("The paths to the csv or xml files to be merged, in order of priority. For conflicting values the value of the first file is used.")
+}
+
+pub fn output_file() -> Arg<'static> {
+    Arg::new(OUTPUT_FILE)
+        .long(OUTPUT_FILE)
+        .required(true)
+        .takes_value(true)
+        .help("The path to the merged csv or xml file.")
+}
+
+pub fn merge_args() -> [Arg<'static>; 2] {
+    [input_files(), output_file()]
+}
+
+pub fn merge(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let input_files = arg::get_many(matches, arg::INPUT_FILES)?;
+    let output_file = arg::get_one(matches, arg::OUTPUT_FILE)?;
+
+    debug!(
+        "Run `taxel merge` with configuration:\n{}={:?}\n{}={}",
+        arg::INPUT_FILES,
+        input_files,
+        arg::OUTPUT_FILE,
+        output_file,
+    );
+
+    let conflicts = if input_files.iter().all(|file| has_extension(file, "csv")) {
+        merge_csv_files(&input_files, output_file)?
+    } else if input_files.iter().all(|file| has_extension(file, "xml")) {
+        merge_xml_files(&input_files, output_file)?
+    } else {
+        return Err(anyhow!(
+            "Can't merge csv and xml files: all input files must be either csv or xml files"
+        ));
+    };
+
+    for conflict in conflicts {
+        println!("Warning: {conflict}");
+    }
+
+    Ok(())
+}
+
+fn merge_csv_files(
+    input_files: &[&str],
+    output_file: &str,
+) -> Result<Vec<MergeConflict>, anyhow::Error> {
+    let mut sources = vec![];
+
+    for input_file in input_files {
+        let mut csv_reader = CsvReaderBuilder::new()
+            .delimiter(b',')
+            .has_headers(true)
+            .trim(Trim::All)
+            .from_path(input_file)?;
+        let tags = taxel::read_tags(Some(&mut csv_reader))?;
+        sources.push((*input_file, tags));
+    }
+
+    let (merged, conflicts) = taxel::merge_tags(&sources);
+
+    let mut csv_writer = CsvWriterBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .from_path(output_file)?;
+
+    taxel::write_tags(&mut csv_writer, Tags::into_vec(merged))?;
+
+    Ok(conflicts)
+}
+
+fn merge_xml_files(
+    input_files: &[&str],
+    output_file: &str,
+) -> Result<Vec<MergeConflict>, anyhow::Error> {
+    let mut sources = vec![];
+
+    for input_file in input_files {
+        let file = File::open(input_file)
+            .map_err(|err| anyhow!("Can't open file '{input_file}': {err}"))?;
+        let mut xml_reader = Reader::from_reader(BufReader::new(file));
+        xml_reader.trim_text(true);
+        let element = XbrlElement::parse(&mut xml_reader)?;
+        sources.push((*input_file, element));
+    }
+
+    let (merged, conflicts) = taxel::merge_instances(&sources)?;
+
+    let output_file = File::create(output_file)?;
+    let mut xml_writer = Writer::new_with_indent(output_file, b' ', 4);
+    taxel::write_declaration(&mut xml_writer)?;
+    merged.serialize(&mut xml_writer)?;
+    xml_writer.into_inner().sync_all()?;
+
+    Ok(conflicts)
+}
+
+fn has_extension(file: &str, extension: &str) -> bool {
+    Path::new(file)
+        .extension()
+        .is_some_and(|el| el.eq_ignore_ascii_case(extension))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+    use std::{env, fs, process};
+
+    #[test]
+    fn test_merge_csv_files() {
+        let dir = env::temp_dir().join(format!("taxel_test_merge_csv_files_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let company = dir.join("company.csv");
+        let balance_sheet = dir.join("balance_sheet.csv");
+        let output = dir.join("merged.csv");
+        fs::write(
+            &company,
+            "ebilanz_key,ebilanz_value\nde-gcd:genInfo.company.id.name,Landwirt GmbH\nde-gaap-ci:bs.ass,\n",
+        )
+        .unwrap();
+        fs::write(
+            &balance_sheet,
+            "ebilanz_key,ebilanz_value\nde-gcd:genInfo.company.id.name,Landwirt\nde-gaap-ci:bs.ass,300.00\n",
+        )
+        .unwrap();
+
+        let conflicts = merge_csv_files(
+            &[company.to_str().unwrap(), balance_sheet.to_str().unwrap()],
+            output.to_str().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "ebilanz_key,ebilanz_value\nde-gaap-ci:bs.ass,300.00\nde-gcd:genInfo.company.id.name,Landwirt GmbH\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_args() {
+        let args = vec![
+            cmd::BIN,
+            cmd::MERGE,
+            "company.xml",
+            "balance_sheet.xml",
+            "--output-file",
+            "merged.xml",
+        ];
+
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::MERGE).unwrap();
+
+        assert_eq!(
+            arg::get_many(subcommand_matches, arg::INPUT_FILES).unwrap(),
+            vec!["company.xml", "balance_sheet.xml"]
+        );
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::OUTPUT_FILE).unwrap(),
+            "merged.xml"
+        );
+    }
+}
@@ -0,0 +1,737 @@
+//! Migrate xml and csv files from one taxonomy version to another.
+
+use super::utils;
+use crate::arg::{
+    self, CSV_FILE, FROM, FROM_TAXONOMY_DIR, MAPPING_FILE, OUTPUT_FILE, TO, TO_TAXONOMY_DIR,
+    XML_FILE,
+};
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches};
+use log::debug;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::Path,
+};
+use taxel::{
+    CsvReaderBuilder, CsvWriterBuilder, Migration, Reader, TaxType, TaxonomySchema,
+    TaxonomyVersion, Trim, Writer, XbrlElement,
+};
+
+const HEADER: [&str; 3] = ["change", CONCEPT_HEADER, NEW_CONCEPT_HEADER];
+const KEY_HEADER: &str = "ebilanz_key";
+const CONCEPT_HEADER: &str = "concept";
+const NEW_CONCEPT_HEADER: &str = "new_concept";
+
+/// A change of a concept between two taxonomy versions.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct ConceptChange {
+    /// Either `renamed`, `suggested`, `removed`, `mandatory`, or `added`.
+    pub change: &'static str,
+    pub concept: String,
+    pub new_concept: Option<String>,
+}
+
+pub fn from() -> Arg<'static> {
+    Arg::new(FROM)
+        .long(FROM)
+        .required(true)
+        .takes_value(true)
+        .possible_values(TaxType::Bilanz.versions())
+        .help("The taxonomy version of the given files, e.g. '6.5'.")
+}
+
+pub fn to() -> Arg<'static> {
+    Arg::new(TO)
+        .long(TO)
+        .required(true)
+        .takes_value(true)
+        .possible_values(TaxType::Bilanz.versions())
+        .help("The taxonomy version to migrate to, e.g. '6.6'.")
+}
+
+pub fn from_taxonomy_dir() -> Arg<'static> {
+    Arg::new(FROM_TAXONOMY_DIR)
+        .long(FROM_TAXONOMY_DIR)
+        .required(true)
+        .takes_value(true)
+        .help("The directory of the local taxonomy files of the old version, e.g. 'taxonomy/v6.5'.")
+}
+
+pub fn to_taxonomy_dir() -> Arg<'static> {
+    Arg::new(TO_TAXONOMY_DIR)
+        .long(TO_TAXONOMY_DIR)
+        .required(true)
+        .takes_value(true)
+        .help("The directory of the local taxonomy files of the new version, e.g. 'taxonomy/v6.6'.")
+}
+
+pub fn xml_file() -> Arg<'static> {
+    Arg::new(XML_FILE)
+        .long(XML_FILE)
+        .required(false)
+        .takes_value(true)
+        .conflicts_with(CSV_FILE)
+        .requires(OUTPUT_FILE)
+        .help("The path to the xml file or template to be migrated.")
+}
+
+pub fn csv_file() -> Arg<'static> {
+    Arg::new(CSV_FILE)
+        .long(CSV_FILE)
+        .required(false)
+        .takes_value(true)
+        .requires(OUTPUT_FILE)
+        .help("The path to the csv file with the columns 'ebilanz_key' and 'ebilanz_value' to be migrated.")
+}
+
+pub fn mapping_file() -> Arg<'static> {
+    Arg::new(MAPPING_FILE)
+        .long(MAPPING_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("The path to a csv file with the columns 'concept' and 'new_concept' of concepts to be renamed, e.g. the confirmed suggestions of the output in csv format.")
+}
+
+pub fn output_file() -> Arg<'static> {
+    Arg::new(OUTPUT_FILE)
+        .long(OUTPUT_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("The path to the migrated xml or csv file.")
+}
+
+pub fn migrate_args() -> [Arg<'static>; 9] {
+    [
+        from(),
+        to(),
+        from_taxonomy_dir(),
+        to_taxonomy_dir(),
+        xml_file(),
+        csv_file(),
+        mapping_file(),
+        output_file(),
+        arg::format(),
+    ]
+}
+
+pub fn migrate(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let from = arg::get_one(matches, arg::FROM)?;
+    let to = arg::get_one(matches, arg::TO)?;
+    let from_taxonomy_dir = arg::get_one(matches, arg::FROM_TAXONOMY_DIR)?;
+    let to_taxonomy_dir = arg::get_one(matches, arg::TO_TAXONOMY_DIR)?;
+    let xml_file = arg::get_maybe_one(matches, arg::XML_FILE);
+    let csv_file = arg::get_maybe_one(matches, arg::CSV_FILE);
+    let mapping_file = arg::get_maybe_one(matches, arg::MAPPING_FILE);
+    let output_file = arg::get_maybe_one(matches, arg::OUTPUT_FILE);
+    let format = arg::get_one(matches, arg::FORMAT)?;
+
+    debug!(
+        "Run `taxel migrate` with configuration:\n{}={}\n{}={}\n{}={}\n{}={}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={}",
+        arg::FROM,
+        from,
+        arg::TO,
+        to,
+        arg::FROM_TAXONOMY_DIR,
+        from_taxonomy_dir,
+        arg::TO_TAXONOMY_DIR,
+        to_taxonomy_dir,
+        arg::XML_FILE,
+        xml_file,
+        arg::CSV_FILE,
+        csv_file,
+        arg::MAPPING_FILE,
+        mapping_file,
+        arg::OUTPUT_FILE,
+        output_file,
+        arg::FORMAT,
+        format,
+    );
+
+    let from = taxonomy_version(from)?;
+    let to = taxonomy_version(to)?;
+
+    if from == to {
+        return Err(anyhow!(
+            "Can't migrate taxonomy version {} to itself",
+            from.version
+        ));
+    }
+
+    let from_schema = discover(from, Path::new(from_taxonomy_dir))?;
+    let to_schema = discover(to, Path::new(to_taxonomy_dir))?;
+    let migration = match mapping_file {
+        Some(mapping_file) => {
+            let input = File::open(mapping_file)
+                .map_err(|err| anyhow!("Can't open file '{mapping_file}': {err}"))?;
+            let renames = read_mapping(input)?;
+            Migration::with_renames(from, to, &from_schema, &to_schema, &renames)?
+        }
+        None => Migration::new(from, to, &from_schema, &to_schema),
+    };
+
+    let concepts = match (xml_file, csv_file, output_file) {
+        (Some(xml_file), _, Some(output_file)) => {
+            let xml = fs::read_to_string(xml_file)
+                .map_err(|err| anyhow!("Can't read file '{xml_file}': {err}"))?;
+            let output = File::create(output_file)?;
+            Some(migrate_xml(&xml, output, &migration)?)
+        }
+        (_, Some(csv_file), Some(output_file)) => {
+            let input = File::open(csv_file)
+                .map_err(|err| anyhow!("Can't open file '{csv_file}': {err}"))?;
+            let output = File::create(output_file)?;
+            Some(migrate_csv(input, output, &migration)?)
+        }
+        _ => None,
+    };
+
+    let changes = changes(&migration, concepts.as_ref());
+    let mut stdout = io::stdout().lock();
+    write_changes(&mut stdout, &changes, format)?;
+
+    Ok(())
+}
+
+fn taxonomy_version(version: &str) -> Result<&'static TaxonomyVersion, anyhow::Error> {
+    TaxonomyVersion::get(version).ok_or(anyhow!("Unsupported taxonomy version '{version}'"))
+}
+
+/// Discover the entry points of the eBilanz in the local taxonomy files.
+fn discover(
+    taxonomy: &TaxonomyVersion,
+    taxonomy_dir: &Path,
+) -> Result<TaxonomySchema, anyhow::Error> {
+    let schema = TaxonomySchema::discover(taxonomy_dir, &taxonomy.shells())?;
+
+    if schema.concept_types.is_empty() {
+        return Err(anyhow!(
+            "Missing taxonomy {} ({}) in directory '{}'",
+            taxonomy.version,
+            taxonomy.date,
+            taxonomy_dir.display()
+        ));
+    }
+
+    Ok(schema)
+}
+
+/// Read the concepts to be renamed from a csv file with the columns `concept`
+/// and `new_concept`; rows without a new concept are skipped.
+///
+/// Other columns are ignored, so the output of `taxel migrate --format csv`
+/// can be used after removing the rows which shouldn't be applied.
+pub fn read_mapping<R>(reader: R) -> Result<BTreeMap<String, String>, anyhow::Error>
+where
+    R: Read,
+{
+    let mut csv_reader = CsvReaderBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .trim(Trim::All)
+        .from_reader(reader);
+
+    let headers = csv_reader.headers()?.clone();
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .ok_or(anyhow!("Missing column `{name}`"))
+    };
+    let concept_column = column(CONCEPT_HEADER)?;
+    let new_concept_column = column(NEW_CONCEPT_HEADER)?;
+
+    let mut renames = BTreeMap::new();
+
+    for record in csv_reader.records() {
+        let record = record?;
+        let concept = &record[concept_column];
+        let new_concept = &record[new_concept_column];
+
+        if !new_concept.is_empty() {
+            renames.insert(concept.to_owned(), new_concept.to_owned());
+        }
+    }
+
+    Ok(renames)
+}
+
+/// Migrate a xml file and write it to the given writer.
+///
+/// Returns the concepts of the xml file before the migration.
+pub fn migrate_xml<W>(
+    xml: &str,
+    writer: W,
+    migration: &Migration,
+) -> Result<BTreeSet<String>, anyhow::Error>
+where
+    W: Write,
+{
+    if let Some(taxonomy) = TaxonomyVersion::detect(xml)? {
+        if taxonomy != migration.from {
+            return Err(anyhow!(
+                "Expected taxonomy version {} but xml file uses taxonomy version {}",
+                migration.from.version,
+                taxonomy.version
+            ));
+        }
+    }
+
+    let mut xml_reader = Reader::from_str(xml);
+    xml_reader.trim_text(true);
+    let mut element = XbrlElement::parse(&mut xml_reader)?;
+    let concepts = element
+        .facts()
+        .into_iter()
+        .map(|fact| fact.concept)
+        .collect();
+
+    element.migrate(migration);
+
+    let mut xml_writer = Writer::new_with_indent(writer, b' ', 4);
+    taxel::write_declaration(&mut xml_writer)?;
+    element.serialize(&mut xml_writer)?;
+
+    Ok(concepts)
+}
+
+/// Migrate the keys of a csv file and write it to the given writer; rows of
+/// removed concepts are discarded.
+///
+/// Returns the keys of the csv file before the migration.
+pub fn migrate_csv<R, W>(
+    reader: R,
+    writer: W,
+    migration: &Migration,
+) -> Result<BTreeSet<String>, anyhow::Error>
+where
+    R: Read,
+    W: Write,
+{
+    let mut csv_reader = CsvReaderBuilder::new()
+        .delimiter(b',')
+        .has_headers(true)
+        .trim(Trim::All)
+        .from_reader(reader);
+    let mut csv_writer = CsvWriterBuilder::new()
+        .delimiter(b',')
+        .has_headers(false)
+        .from_writer(writer);
+
+    let headers = csv_reader.headers()?.clone();
+    let key_column = headers
+        .iter()
+        .position(|header| header == KEY_HEADER)
+        .ok_or(anyhow!("Missing column `{KEY_HEADER}`"))?;
+    csv_writer.write_record(&headers)?;
+
+    let mut concepts = BTreeSet::new();
+
+    for record in csv_reader.records() {
+        let record = record?;
+        let key = &record[key_column];
+        concepts.insert(key.to_owned());
+
+        if let Some(new_key) = migration.concept(key) {
+            csv_writer.write_record(record.iter().enumerate().map(|(column, cell)| {
+                if column == key_column {
+                    new_key
+                } else {
+                    cell
+                }
+            }))?;
+        }
+    }
+
+    csv_writer.flush()?;
+
+    Ok(concepts)
+}
+
+/// The changes of the migration.
+///
+/// If the concepts of a migrated file are given, only the renamed, suggested,
+/// and removed concepts of the file and the newly mandatory concepts missing
+/// in the file are returned. Otherwise all changes including the added concepts are
+/// returned.
+pub fn changes(migration: &Migration, concepts: Option<&BTreeSet<String>>) -> Vec<ConceptChange> {
+    let is_used = |concept: &String| concepts.is_none_or(|concepts| concepts.contains(concept));
+    let is_missing = |concept: &String| {
+        concepts.is_none_or(|concepts| {
+            !concepts
+                .iter()
+                .any(|old_concept| migration.concept(old_concept) == Some(concept.as_str()))
+        })
+    };
+    let mut changes = vec![];
+
+    for (concept, new_concept) in &migration.renamed {
+        if is_used(concept) {
+            changes.push(ConceptChange {
+                change: "renamed",
+                concept: concept.clone(),
+                new_concept: Some(new_concept.clone()),
+            });
+        }
+    }
+
+    for (concept, new_concept) in &migration.suggested {
+        if is_used(concept) {
+            changes.push(ConceptChange {
+                change: "suggested",
+                concept: concept.clone(),
+                new_concept: Some(new_concept.clone()),
+            });
+        }
+    }
+
+    for concept in &migration.removed {
+        if is_used(concept) {
+            changes.push(ConceptChange {
+                change: "removed",
+                concept: concept.clone(),
+                new_concept: None,
+            });
+        }
+    }
+
+    for concept in &migration.newly_mandatory {
+        if is_missing(concept) {
+            changes.push(ConceptChange {
+                change: "mandatory",
+                concept: concept.clone(),
+                new_concept: None,
+            });
+        }
+    }
+
+    if concepts.is_none() {
+        for concept in &migration.added {
+            changes.push(ConceptChange {
+                change: "added",
+                concept: concept.clone(),
+                new_concept: None,
+            });
+        }
+    }
+
+    changes
+}
+
+/// Write the changes as table, csv, or json.
+pub fn write_changes<W>(
+    writer: &mut W,
+    changes: &[ConceptChange],
+    format: &str,
+) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    match format {
+        "table" => {
+            if changes.is_empty() {
+                writeln!(writer, "No changes found")?;
+            } else {
+                let rows = changes.iter().map(to_row).collect::<Vec<_>>();
+                utils::write_table(writer, &HEADER, &rows)?;
+            }
+        }
+        "csv" => {
+            let mut csv_writer = CsvWriterBuilder::new()
+                .delimiter(b',')
+                .has_headers(false)
+                .from_writer(writer);
+            csv_writer.write_record(HEADER)?;
+
+            for change in changes {
+                csv_writer.write_record(to_row(change))?;
+            }
+
+            csv_writer.flush()?;
+        }
+        "json" => {
+            serde_json::to_writer_pretty(&mut *writer, changes)?;
+            writeln!(writer)?;
+        }
+        other => return Err(anyhow!("Invalid format: {other}")),
+    }
+
+    Ok(())
+}
+
+fn to_row(change: &ConceptChange) -> Vec<String> {
+    vec![
+        change.change.to_owned(),
+        change.concept.clone(),
+        change.new_concept.clone().unwrap_or_default(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+    use std::{env, io::Cursor};
+
+    /// Write a minimal taxonomy with a single `de-gaap-ci` shell schema.
+    fn write_taxonomy(
+        taxonomy_dir: &Path,
+        taxonomy: &TaxonomyVersion,
+        concepts: &[&str],
+        mandatory: &[&str],
+    ) {
+        let date = taxonomy.date;
+        let module_dir = taxonomy_dir.join(format!("de-gaap-ci-{date}"));
+        fs::create_dir_all(&module_dir).unwrap();
+
+        let elements = concepts
+            .iter()
+            .map(|concept| {
+                format!(r#"<xs:element name="{concept}" id="de-gaap-ci_{concept}" substitutionGroup="xbrli:item" type="xbrli:monetaryItemType"/>"#)
+            })
+            .collect::<String>();
+        let schema = format!(
+            r#"<xs:schema xmlns:de-gaap-ci="{namespace}" xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink" xmlns:xs="http://www.w3.org/2001/XMLSchema" targetNamespace="{namespace}">
+                <xs:annotation><xs:appinfo><link:linkbaseRef xlink:type="simple" xlink:href="de-gaap-ci-{date}-reference-fiscal.xml"/></xs:appinfo></xs:annotation>
+                {elements}
+            </xs:schema>"#,
+            namespace = taxonomy.namespace("de-gaap-ci"),
+        );
+        fs::write(
+            module_dir.join(format!("de-gaap-ci-{date}-shell-fiscal.xsd")),
+            schema,
+        )
+        .unwrap();
+
+        let references = mandatory
+            .iter()
+            .map(|concept| {
+                format!(
+                    r#"<loc xlink:type="locator" xlink:href="de-gaap-ci-{date}.xsd#de-gaap-ci_{concept}" xlink:label="{concept}"/>
+                    <referenceArc xlink:type="arc" xlink:from="{concept}" xlink:to="reference_{concept}"/>
+                    <reference xlink:type="resource" xlink:label="reference_{concept}"><hgbref:fiscalRequirement>Mussfeld</hgbref:fiscalRequirement></reference>"#
+                )
+            })
+            .collect::<String>();
+        let linkbase = format!(
+            r#"<link:linkbase xmlns:hgbref="http://www.xbrl.de/taxonomies/de-ref-2010-02-19" xmlns:link="http://www.xbrl.org/2003/linkbase" xmlns:xlink="http://www.w3.org/1999/xlink">
+                <link:referenceLink xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">{references}</link:referenceLink>
+            </link:linkbase>"#
+        );
+        fs::write(
+            module_dir.join(format!("de-gaap-ci-{date}-reference-fiscal.xml")),
+            linkbase,
+        )
+        .unwrap();
+    }
+
+    fn migration(name: &str, renames: &BTreeMap<String, String>) -> Migration {
+        let taxonomy_dir = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&taxonomy_dir);
+        let from = TaxonomyVersion::get("6.5").unwrap();
+        let to = TaxonomyVersion::get("6.6").unwrap();
+        let from_dir = taxonomy_dir.join("v6.5");
+        let to_dir = taxonomy_dir.join("v6.6");
+
+        write_taxonomy(
+            &from_dir,
+            from,
+            &[
+                "bs.ass",
+                "bs.ass.misc.other",
+                "bs.ass.obsolete",
+                "bs.ass.fixAss.tan",
+            ],
+            &["bs.ass"],
+        );
+        write_taxonomy(
+            &to_dir,
+            to,
+            &[
+                "bs.ass",
+                "bs.ass.other.other",
+                "bs.eqLiab.deferred",
+                "bs.ass.fixAss.tangible",
+            ],
+            &["bs.ass", "bs.eqLiab.deferred"],
+        );
+
+        let migration = Migration::with_renames(
+            from,
+            to,
+            &discover(from, &from_dir).unwrap(),
+            &discover(to, &to_dir).unwrap(),
+            renames,
+        )
+        .unwrap();
+        fs::remove_dir_all(&taxonomy_dir).unwrap();
+
+        migration
+    }
+
+    #[test]
+    fn test_migrate_csv() {
+        let migration = migration("taxel_test_migrate_csv", &BTreeMap::new());
+        let csv = "ebilanz_key,ebilanz_value\n\
+            ebilanz:stichtag,20221231\n\
+            de-gaap-ci:bs.ass,300.00\n\
+            de-gaap-ci:bs.ass.misc.other,100.00\n\
+            de-gaap-ci:bs.ass.obsolete,200.00\n\
+            de-gaap-ci:bs.ass.fixAss.tan,50.00\n";
+        let mut output = Cursor::new(Vec::new());
+
+        let concepts = migrate_csv(csv.as_bytes(), &mut output, &migration).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "ebilanz_key,ebilanz_value\n\
+            ebilanz:stichtag,20221231\n\
+            de-gaap-ci:bs.ass,300.00\n"
+        );
+        assert_eq!(
+            changes(&migration, Some(&concepts)),
+            vec![
+                ConceptChange {
+                    change: "suggested",
+                    concept: String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+                    new_concept: Some(String::from("de-gaap-ci:bs.ass.fixAss.tangible")),
+                },
+                ConceptChange {
+                    change: "suggested",
+                    concept: String::from("de-gaap-ci:bs.ass.misc.other"),
+                    new_concept: Some(String::from("de-gaap-ci:bs.ass.other.other")),
+                },
+                ConceptChange {
+                    change: "removed",
+                    concept: String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+                    new_concept: None,
+                },
+                ConceptChange {
+                    change: "removed",
+                    concept: String::from("de-gaap-ci:bs.ass.misc.other"),
+                    new_concept: None,
+                },
+                ConceptChange {
+                    change: "removed",
+                    concept: String::from("de-gaap-ci:bs.ass.obsolete"),
+                    new_concept: None,
+                },
+                ConceptChange {
+                    change: "mandatory",
+                    concept: String::from("de-gaap-ci:bs.eqLiab.deferred"),
+                    new_concept: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_migrate_csv_with_mapping() {
+        let mapping = "change,concept,new_concept\n\
+            suggested,de-gaap-ci:bs.ass.fixAss.tan,de-gaap-ci:bs.ass.fixAss.tangible\n\
+            removed,de-gaap-ci:bs.ass.obsolete,\n";
+        let renames = read_mapping(mapping.as_bytes()).unwrap();
+        assert_eq!(
+            renames,
+            BTreeMap::from([(
+                String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+                String::from("de-gaap-ci:bs.ass.fixAss.tangible")
+            )])
+        );
+        let migration = migration("taxel_test_migrate_csv_with_mapping", &renames);
+        let csv = "ebilanz_key,ebilanz_value\n\
+            de-gaap-ci:bs.ass.fixAss.tan,50.00\n";
+        let mut output = Cursor::new(Vec::new());
+
+        let concepts = migrate_csv(csv.as_bytes(), &mut output, &migration).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output.into_inner()).unwrap(),
+            "ebilanz_key,ebilanz_value\n\
+            de-gaap-ci:bs.ass.fixAss.tangible,50.00\n"
+        );
+        assert_eq!(
+            changes(&migration, Some(&concepts))[0],
+            ConceptChange {
+                change: "renamed",
+                concept: String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+                new_concept: Some(String::from("de-gaap-ci:bs.ass.fixAss.tangible")),
+            }
+        );
+    }
+
+    #[test]
+    fn test_migrate_xml() {
+        let migration = migration("taxel_test_migrate_xml", &BTreeMap::new());
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut output = Cursor::new(Vec::new());
+
+        let concepts = migrate_xml(&xml, &mut output, &migration).unwrap();
+
+        let actual = String::from_utf8(output.into_inner()).unwrap();
+        assert!(concepts.contains("de-gaap-ci:bs.ass"));
+        assert!(!actual.contains("2021-04-14"));
+        assert!(
+            actual.contains(r#"xmlns:de-gcd="http://www.xbrl.de/taxonomies/de-gcd-2022-05-02""#)
+        );
+        assert!(actual.contains(r#"xlink:href="http://www.xbrl.de/taxonomies/de-gcd-2022-05-02/de-gcd-2022-05-02-shell.xsd""#));
+
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.4/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        assert_eq!(
+            migrate_xml(&xml, Cursor::new(Vec::new()), &migration)
+                .unwrap_err()
+                .to_string(),
+            "Expected taxonomy version 6.5 but xml file uses taxonomy version 6.4"
+        );
+    }
+
+    #[test]
+    fn test_migrate_args() {
+        let args = vec![
+            cmd::BIN,
+            cmd::MIGRATE,
+            "--from",
+            "6.5",
+            "--to",
+            "6.6",
+            "--from-taxonomy-dir",
+            "taxonomy/v6.5",
+            "--to-taxonomy-dir",
+            "taxonomy/v6.6",
+            "--csv-file",
+            "my_ebilanz_6.5.csv",
+            "--output-file",
+            "my_ebilanz_6.6.csv",
+        ];
+
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::MIGRATE).unwrap();
+
+        assert_eq!(arg::get_one(subcommand_matches, arg::FROM).unwrap(), "6.5");
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::CSV_FILE).unwrap(),
+            "my_ebilanz_6.5.csv"
+        );
+
+        // The migrated file requires an output file.
+        let args = vec![
+            cmd::BIN,
+            cmd::MIGRATE,
+            "--from",
+            "6.5",
+            "--to",
+            "6.6",
+            "--from-taxonomy-dir",
+            "taxonomy/v6.5",
+            "--to-taxonomy-dir",
+            "taxonomy/v6.6",
+            "--xml-file",
+            "my_ebilanz_6.5.xml",
+        ];
+        assert!(app().try_get_matches_from(args).is_err());
+    }
+}
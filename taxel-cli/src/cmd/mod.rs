@@ -1,13 +1,27 @@
+mod batch;
+mod diff;
 mod extract;
 mod generate;
+mod history;
+mod merge;
+mod migrate;
+mod query;
+mod rollover;
+mod run_log;
 mod send;
 mod utils;
 mod validate;
 
+pub use diff::{diff, diff_args};
 pub use extract::{extract, extract_args};
 pub use generate::{generate, generate_args};
-pub use send::{send, send_args};
-pub use validate::{validate, validate_args};
+pub use history::{history, history_args};
+pub use merge::{merge, merge_args};
+pub use migrate::{migrate, migrate_args};
+pub use query::{query, query_args};
+pub use rollover::{rollover, rollover_args};
+pub use send::{send, send_args, send_with};
+pub use validate::{validate, validate_args, validate_with};
 
 /// The binary name for taxel-cli.
 pub const BIN: &str = "taxel";
@@ -17,3 +31,9 @@ pub const EXTRACT: &str = "extract";
 pub const GENERATE: &str = "generate";
 pub const VALIDATE: &str = "validate";
 pub const SEND: &str = "send";
+pub const ROLLOVER: &str = "rollover";
+pub const DIFF: &str = "diff";
+pub const MERGE: &str = "merge";
+pub const QUERY: &str = "query";
+pub const HISTORY: &str = "history";
+pub const MIGRATE: &str = "migrate";
@@ -0,0 +1,249 @@
+//! Query facts of a xml file in the XBRL standard.
+
+use super::utils;
+use crate::arg::{self, CONCEPT, CONTEXT, SUBTREE, TAXONOMY_DIR, UNIT, XML_FILE};
+use anyhow::anyhow;
+use clap::{Arg, ArgMatches};
+use log::debug;
+use std::{
+    fs::File,
+    io::{self, BufReader, Write},
+    path::Path,
+};
+use taxel::{CsvWriterBuilder, Fact, Query, Reader, TaxonomySchema, XbrlElement};
+
+const HEADER: [&str; 5] = ["concept", "context", "unit", "decimals", "value"];
+
+pub fn xml_file() -> Arg<'static> {
+    Arg::new(XML_FILE)
+        .index(1)
+        .required(true)
+        .takes_value(true)
+        .help("The path to the XML file to be queried.")
+}
+
+pub fn concept() -> Arg<'static> {
+    Arg::new(CONCEPT)
+        .index(2)
+        .required(false)
+        .takes_value(true)
+        .help("The concept of the facts, e.g. 'de-gaap-ci:bs.ass.*'. Use '*' as wildcard for any characters and '?' for a single character.")
+}
+
+pub fn context() -> Arg<'static> {
+    Arg::new(CONTEXT)
+        .long(CONTEXT)
+        .required(false)
+        .takes_value(true)
+        .help("The context of the facts, e.g. 'D-AKTJAHR'. Wildcards are supported.")
+}
+
+pub fn unit() -> Arg<'static> {
+    Arg::new(UNIT)
+        .long(UNIT)
+        .required(false)
+        .takes_value(true)
+        .help("The unit of the facts, e.g. 'EUR'. Wildcards are supported.")
+}
+
+pub fn subtree() -> Arg<'static> {
+    Arg::new(SUBTREE)
+        .long(SUBTREE)
+        .required(false)
+        .takes_value(true)
+        .requires(TAXONOMY_DIR)
+        .help("Select the given concept and everything under it in the presentation hierarchy, e.g. 'de-gaap-ci:bs.ass.fixAss'.")
+}
+
+pub fn query_args() -> [Arg<'static>; 7] {
+    [
+        xml_file(),
+        concept(),
+        context(),
+        unit(),
+        subtree(),
+        arg::taxonomy_dir(),
+        arg::format(),
+    ]
+}
+
+pub fn query(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let xml_file = arg::get_one(matches, arg::XML_FILE)?;
+    let concept = arg::get_maybe_one(matches, arg::CONCEPT);
+    let context = arg::get_maybe_one(matches, arg::CONTEXT);
+    let unit = arg::get_maybe_one(matches, arg::UNIT);
+    let subtree = arg::get_maybe_one(matches, arg::SUBTREE);
+    let taxonomy_dir = arg::get_maybe_one(matches, arg::TAXONOMY_DIR);
+    let format = arg::get_one(matches, arg::FORMAT)?;
+
+    debug!(
+        "Run `taxel query` with configuration:\n{}={}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={:?}\n{}={}",
+        arg::XML_FILE,
+        xml_file,
+        arg::CONCEPT,
+        concept,
+        arg::CONTEXT,
+        context,
+        arg::UNIT,
+        unit,
+        arg::SUBTREE,
+        subtree,
+        arg::TAXONOMY_DIR,
+        taxonomy_dir,
+        arg::FORMAT,
+        format,
+    );
+
+    let xml_file = File::open(xml_file)?;
+    let mut xml_reader = Reader::from_reader(BufReader::new(xml_file));
+    xml_reader.trim_text(true);
+    let element = XbrlElement::parse(&mut xml_reader)?;
+
+    let mut query = Query::new();
+
+    if let Some(concept) = concept {
+        query = query.concept(concept);
+    }
+
+    if let Some(context) = context {
+        query = query.context(context);
+    }
+
+    if let Some(unit) = unit {
+        query = query.unit(unit);
+    }
+
+    if let (Some(subtree), Some(taxonomy_dir)) = (subtree, taxonomy_dir) {
+        let schema = TaxonomySchema::discover(Path::new(taxonomy_dir), &element.schema_refs())?;
+
+        if schema.presentation.is_empty() {
+            return Err(anyhow!(
+                "Missing presentation hierarchy in taxonomy directory '{taxonomy_dir}'"
+            ));
+        }
+
+        query = query.subtree(&schema.presentation, subtree);
+    }
+
+    let facts = element.query(&query);
+
+    let mut stdout = io::stdout().lock();
+    write_facts(&mut stdout, &facts, format)?;
+
+    Ok(())
+}
+
+/// Write the facts as table, csv, or json.
+pub fn write_facts<W>(writer: &mut W, facts: &[Fact], format: &str) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    match format {
+        "table" => {
+            let rows = facts.iter().map(to_row).collect::<Vec<_>>();
+            utils::write_table(writer, &HEADER, &rows)?;
+        }
+        "csv" => {
+            let mut csv_writer = CsvWriterBuilder::new()
+                .delimiter(b',')
+                .has_headers(false)
+                .from_writer(writer);
+            csv_writer.write_record(HEADER)?;
+
+            for fact in facts {
+                csv_writer.write_record(to_row(fact))?;
+            }
+
+            csv_writer.flush()?;
+        }
+        "json" => {
+            serde_json::to_writer_pretty(&mut *writer, facts)?;
+            writeln!(writer)?;
+        }
+        other => return Err(anyhow!("Invalid format: {other}")),
+    }
+
+    Ok(())
+}
+
+fn to_row(fact: &Fact) -> Vec<String> {
+    let value = if fact.nil {
+        String::from("nil")
+    } else {
+        fact.value.clone().unwrap_or_default()
+    };
+
+    vec![
+        fact.concept.clone(),
+        fact.context.clone(),
+        fact.unit.clone().unwrap_or_default(),
+        fact.decimals.clone().unwrap_or_default(),
+        value,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{app, cmd};
+
+    #[test]
+    fn test_write_facts() {
+        let facts = vec![
+            Fact::new(
+                "de-gaap-ci:bs.ass",
+                "I-AKTJAHR",
+                Some(String::from("EUR")),
+                Some(String::from("2")),
+                Some(String::from("300.00")),
+                false,
+            ),
+            Fact::new(
+                "de-gaap-ci:bs.ass.currAss",
+                "I-AKTJAHR",
+                Some(String::from("EUR")),
+                None,
+                None,
+                true,
+            ),
+        ];
+        let mut output = vec![];
+
+        write_facts(&mut output, &facts, "table").unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "concept                    context    unit  decimals  value\n\
+             -------------------------  ---------  ----  --------  ------\n\
+             de-gaap-ci:bs.ass          I-AKTJAHR  EUR   2         300.00\n\
+             de-gaap-ci:bs.ass.currAss  I-AKTJAHR  EUR             nil\n"
+        );
+    }
+
+    #[test]
+    fn test_query_args() {
+        let args = vec![
+            cmd::BIN,
+            cmd::QUERY,
+            "my_bilanz.xml",
+            "de-gaap-ci:bs.ass.*",
+            "--context",
+            "D-AKTJAHR",
+            "--format",
+            "json",
+        ];
+
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::QUERY).unwrap();
+
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::CONCEPT).unwrap(),
+            "de-gaap-ci:bs.ass.*"
+        );
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::CONTEXT).unwrap(),
+            "D-AKTJAHR"
+        );
+    }
+}
@@ -0,0 +1,179 @@
+//! Roll over last year's xml file to a new xml file for the current year.
+
+use crate::arg::{self, OUTPUT_FILE, PREVIOUS};
+use clap::{Arg, ArgMatches};
+use log::debug;
+use std::{
+    env::current_dir,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+};
+use taxel::{Reader, Writer, XbrlElement};
+
+pub fn previous() -> Arg<'static> {
+    Arg::new(PREVIOUS)
+        .long(PREVIOUS)
+        .required(true)
+        .takes_value(true)
+        .help("The path to the xml file of the previous year.")
+}
+
+pub fn output_file() -> Arg<'static> {
+    Arg::new(OUTPUT_FILE)
+        .long(OUTPUT_FILE)
+        .required(false)
+        .takes_value(true)
+        .help("The path to the generated xml file for the current year. If no path is specified the current directory will be used as output path.")
+}
+
+pub fn rollover_args() -> [Arg<'static>; 2] {
+    [previous(), output_file()]
+}
+
+pub fn rollover(matches: &ArgMatches) -> Result<(), anyhow::Error> {
+    let previous_file = arg::get_one(matches, arg::PREVIOUS)?;
+    let output_file = arg::get_maybe_one(matches, arg::OUTPUT_FILE);
+    let output_path = match output_file {
+        Some(output_file) => PathBuf::from(output_file),
+        None => current_dir()?,
+    };
+
+    debug!(
+        "Run `taxel rollover` with configuration:\n{}={}\n{}={:?}",
+        arg::PREVIOUS,
+        previous_file,
+        arg::OUTPUT_FILE,
+        output_file,
+    );
+
+    // Read the xml file of the previous year
+    let previous_file = File::open(previous_file)?;
+    let reader = BufReader::new(previous_file);
+
+    // Create a reader for parsing the XML file
+    let mut xml_reader = Reader::from_reader(reader);
+    xml_reader.trim_text(true);
+
+    // Create a new XML file as output
+    let output_file = File::create(output_path)?;
+    // Format XML file
+    let mut xml_writer = Writer::new_with_indent(output_file, b' ', 4);
+
+    rollover_values(&mut xml_reader, &mut xml_writer)?;
+
+    // Flush the output XML writer and finalize the file
+    xml_writer.into_inner().sync_all()?;
+
+    Ok(())
+}
+
+/// Move the values of the previous year to the prior-year contexts.
+pub fn rollover_values<R, W>(
+    xml_reader: &mut Reader<R>,
+    xml_writer: &mut Writer<W>,
+) -> Result<(), anyhow::Error>
+where
+    R: std::io::Read + BufRead,
+    W: std::io::Write,
+{
+    let mut element = XbrlElement::parse(xml_reader)?;
+    element.rollover()?;
+    taxel::write_declaration(xml_writer)?;
+    element.serialize(xml_writer)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app,
+        cmd::{self, generate},
+    };
+    use std::{fs, io::Cursor};
+    use taxel::{Rounding, Tags, TaxType};
+
+    #[test]
+    fn test_rollover_values() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut reader = Reader::from_str(&xml);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+
+        rollover_values(&mut reader, &mut writer).unwrap();
+
+        let actual = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+        assert!(actual.contains("<ebilanz:stichtag>20230630</ebilanz:stichtag>"));
+        assert!(actual.contains(r#"<xbrli:context id="D-VORJAHR">"#));
+        assert!(actual.contains(
+            r#"<de-gcd:genInfo.report.period.fiscalYearEnd contextRef="D-AKTJAHR">2023-06-30</de-gcd:genInfo.report.period.fiscalYearEnd>"#
+        ));
+        assert!(actual.contains(r#"<xbrli:identifier scheme="http://www.rzf-nrw.de/Steuernummer">5192050001265</xbrli:identifier>"#));
+    }
+
+    #[test]
+    fn test_rollover_and_generate() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut reader = Reader::from_str(&xml);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        rollover_values(&mut reader, &mut writer).unwrap();
+        let rolled_over = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+
+        let mut target_tags = Tags::new();
+        target_tags.insert("de-gaap-ci:bs.ass", Some("999.00"));
+        let mut reader = Reader::from_str(&rolled_over);
+        reader.trim_text(true);
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        generate::update_values(
+            target_tags,
+            TaxType::Bilanz,
+            None,
+            Rounding::default(),
+            &mut reader,
+            &mut writer,
+        )
+        .unwrap();
+        let generated = String::from_utf8(writer.into_inner().into_inner()).unwrap();
+
+        // The values of the csv file are applied to the current year, and the
+        // prior-year values of the rollover are kept.
+        let value = |context: &str| {
+            let mut reader = Reader::from_str(&generated);
+            reader.trim_text(true);
+            let element = XbrlElement::parse(&mut reader).unwrap();
+            element
+                .facts()
+                .into_iter()
+                .find(|fact| fact.concept == "de-gaap-ci:bs.ass" && fact.context == context)
+                .and_then(|fact| fact.value)
+        };
+        assert_eq!(value("I-AKTJAHR").as_deref(), Some("999.00"));
+        assert_eq!(value("I-VORJAHR").as_deref(), Some("178616.11"));
+    }
+
+    #[test]
+    fn test_rollover_args() {
+        let args = vec![
+            cmd::BIN,
+            cmd::ROLLOVER,
+            "--previous",
+            "my_ebilanz_2023.xml",
+            "--output-file",
+            "my_ebilanz_2024.xml",
+        ];
+
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::ROLLOVER).unwrap();
+
+        assert_eq!(
+            arg::get_one(subcommand_matches, arg::PREVIOUS).unwrap(),
+            "my_ebilanz_2023.xml"
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! The log directories of the runs of `taxel validate` and `taxel send`.
+//!
+//! Each run writes to its own directory in the log directory, so a run never
+//! overwrites the responses of a previous one:
+//!
+//! ```text
+//! <log-dir>/
+//!     20231231T120000.000Z/
+//!         validation_response.xml
+//!         server_response.xml
+//!         summary.json
+//! ```
+//!
+//! In batch mode, the responses of each xml file are written to a
+//! subdirectory of the run directory. The file `summary.json` contains the
+//! status, error code, transfer ticket, and number of errors and warnings of
+//! each xml file.
+
+use super::batch::Filing;
+use crate::arg;
+use anyhow::anyhow;
+use chrono::{SecondsFormat, Utc};
+use clap::{crate_version, ArgMatches};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use taxel_eric::{
+    exit::{self, ExitError},
+    Outcome,
+};
+
+pub const SUMMARY_FILE: &str = "summary.json";
+
+/// The summary of a processed xml file.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct FilingSummary {
+    pub xml_file: String,
+    pub tax_type: String,
+    pub tax_version: String,
+    /// Either `ok`, `invalid`, `rejected`, or `failed`.
+    pub status: String,
+    pub exit_code: i32,
+    /// The error code of ERiC; missing if the xml file wasn't processed.
+    pub error_code: Option<i32>,
+    pub transfer_ticket: Option<String>,
+    pub errors: Option<usize>,
+    pub warnings: Option<usize>,
+    pub log_dir: String,
+    /// The error message of a failed xml file.
+    pub message: Option<String>,
+}
+
+/// The summary of a run.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// The subcommand, e.g. `validate`.
+    pub command: String,
+    /// The start of the run in RFC 3339 format (UTC).
+    pub timestamp: String,
+    /// The highest exit code of the processed xml files.
+    pub exit_code: i32,
+    pub taxel_version: String,
+    pub filings: Vec<FilingSummary>,
+}
+
+/// The name of the run directory.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum RunName {
+    /// The start time of the run, e.g. `20231231T120000.000Z`.
+    Timestamp,
+    /// The transfer ticket of a single sent xml file.
+    Ticket,
+}
+
+/// The log directory of a run.
+#[derive(Debug)]
+pub struct RunLog {
+    log_path: PathBuf,
+    path: PathBuf,
+    run_name: RunName,
+    keep_runs: Option<usize>,
+    summary: RunSummary,
+}
+
+impl RunLog {
+    /// Create a new run directory in the given log directory.
+    pub fn create(
+        log_path: &Path,
+        command: &str,
+        run_name: RunName,
+        keep_runs: Option<usize>,
+    ) -> Result<Self, anyhow::Error> {
+        let now = Utc::now();
+        let path = unique_path(log_path, &now.format("%Y%m%dT%H%M%S%.3fZ").to_string());
+
+        fs::create_dir_all(&path)
+            .map_err(|err| anyhow!("Can't create log directory '{}': {err}", path.display()))?;
+
+        Ok(Self {
+            log_path: log_path.to_owned(),
+            path,
+            run_name,
+            keep_runs,
+            summary: RunSummary {
+                command: command.to_owned(),
+                timestamp: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+                exit_code: 0,
+                taxel_version: String::from(crate_version!()),
+                filings: vec![],
+            },
+        })
+    }
+
+    /// Create a new run directory configured by the command line arguments.
+    pub fn from_matches(
+        log_path: &Path,
+        command: &str,
+        matches: &ArgMatches,
+    ) -> Result<Self, anyhow::Error> {
+        let run_name = match arg::get_one(matches, arg::RUN_NAME)? {
+            "ticket" => RunName::Ticket,
+            _ => RunName::Timestamp,
+        };
+        let keep_runs = match arg::get_maybe_one(matches, arg::KEEP_RUNS) {
+            Some(keep_runs) => match keep_runs.parse() {
+                Ok(keep_runs) if keep_runs > 0 => Some(keep_runs),
+                _ => return Err(anyhow!("Invalid number of runs to keep '{keep_runs}'")),
+            },
+            None => None,
+        };
+
+        Self::create(log_path, command, run_name, keep_runs)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn filings(&self) -> &[FilingSummary] {
+        &self.summary.filings
+    }
+
+    /// Record the outcome of a xml file whose responses were written to the
+    /// given directory; returns the result of the outcome.
+    pub fn record(
+        &mut self,
+        filing: &Filing,
+        log_dir: &Path,
+        outcome: Result<Outcome, anyhow::Error>,
+    ) -> Result<(), anyhow::Error> {
+        let (report, error_code, transfer_ticket, result) = match outcome {
+            Ok(Outcome {
+                report,
+                error_code,
+                transfer_ticket,
+                result,
+            }) => (Some(report), Some(error_code), transfer_ticket, result),
+            Err(err) => (None, None, None, Err(err)),
+        };
+        let exit_code = match &result {
+            Ok(()) => 0,
+            Err(err) => err.downcast_ref::<ExitError>().map_or(1, |err| err.code),
+        };
+        let status = match exit_code {
+            0 => "ok",
+            exit::VALIDATION_ERROR => "invalid",
+            exit::TRANSMISSION_ERROR => "rejected",
+            _ => "failed",
+        };
+
+        self.summary.exit_code = self.summary.exit_code.max(exit_code);
+        self.summary.filings.push(FilingSummary {
+            xml_file: filing.xml_file.display().to_string(),
+            tax_type: filing.tax_type.clone(),
+            tax_version: filing.tax_version.clone(),
+            status: String::from(status),
+            exit_code,
+            error_code,
+            transfer_ticket,
+            errors: report.as_ref().map(|report| report.errors().count()),
+            warnings: report.as_ref().map(|report| report.warnings().count()),
+            log_dir: log_dir.display().to_string(),
+            message: result.as_ref().err().map(|err| err.to_string()),
+        });
+
+        result
+    }
+
+    /// Name the run directory, write the summary, and remove old runs.
+    ///
+    /// Returns the path of the run directory.
+    pub fn finish(mut self) -> Result<PathBuf, anyhow::Error> {
+        let ticket = match self.summary.filings.as_slice() {
+            [filing] if self.run_name == RunName::Ticket => {
+                filing.transfer_ticket.as_deref().and_then(ticket_dir_name)
+            }
+            _ => None,
+        };
+
+        if let Some(ticket) = ticket {
+            let path = unique_path(&self.log_path, &ticket);
+            fs::rename(&self.path, &path)?;
+
+            for filing in &mut self.summary.filings {
+                match Path::new(&filing.log_dir).strip_prefix(&self.path) {
+                    Ok(relative_path) if relative_path.as_os_str().is_empty() => {
+                        filing.log_dir = path.display().to_string();
+                    }
+                    Ok(relative_path) => {
+                        filing.log_dir = path.join(relative_path).display().to_string();
+                    }
+                    Err(_) => {}
+                }
+            }
+
+            self.path = path;
+        }
+
+        let summary_path = self.path.join(SUMMARY_FILE);
+        println!("Writing summary to '{}'", summary_path.display());
+        fs::write(
+            &summary_path,
+            serde_json::to_string_pretty(&self.summary)? + "\n",
+        )?;
+
+        if let Some(keep_runs) = self.keep_runs {
+            for path in prune(&self.log_path, keep_runs)? {
+                println!("Removing old run '{}'", path.display());
+            }
+        }
+
+        Ok(self.path)
+    }
+}
+
+/// All runs in the log directory ordered by time.
+///
+/// Only directories with a summary file are considered runs.
+pub fn runs(log_path: &Path) -> Result<Vec<(PathBuf, RunSummary)>, anyhow::Error> {
+    let mut runs = vec![];
+
+    if !log_path.is_dir() {
+        return Ok(runs);
+    }
+
+    for entry in fs::read_dir(log_path)? {
+        let path = entry?.path();
+        let summary_path = path.join(SUMMARY_FILE);
+
+        if summary_path.is_file() {
+            let summary = fs::read_to_string(&summary_path)?;
+            let summary: RunSummary = serde_json::from_str(&summary)
+                .map_err(|err| anyhow!("Can't read '{}': {err}", summary_path.display()))?;
+            runs.push((path, summary));
+        }
+    }
+
+    runs.sort_by(|(lhs_path, lhs), (rhs_path, rhs)| {
+        (&lhs.timestamp, lhs_path).cmp(&(&rhs.timestamp, rhs_path))
+    });
+
+    Ok(runs)
+}
+
+/// Remove all but the latest runs; returns the removed run directories.
+pub fn prune(log_path: &Path, keep_runs: usize) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let runs = runs(log_path)?;
+    let count = runs.len().saturating_sub(keep_runs);
+    let mut removed = vec![];
+
+    for (path, _) in runs.into_iter().take(count) {
+        fs::remove_dir_all(&path)
+            .map_err(|err| anyhow!("Can't remove old run '{}': {err}", path.display()))?;
+        removed.push(path);
+    }
+
+    Ok(removed)
+}
+
+/// The name of the run directory of a transfer ticket.
+///
+/// The transfer ticket is read from the server response; characters other
+/// than ASCII letters, digits, `-`, and `_` are replaced by `_`, so the ticket
+/// can't escape the log directory, e.g. by path separators or `..`. Returns
+/// `None` for an empty ticket.
+fn ticket_dir_name(ticket: &str) -> Option<String> {
+    let name = ticket
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    (!name.is_empty()).then_some(name)
+}
+
+/// A path in the directory with the given name which doesn't exist yet; a
+/// suffix is added on collision.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let mut path = dir.join(name);
+    let mut counter = 2;
+
+    while path.exists() {
+        path = dir.join(format!("{name}-{counter}"));
+        counter += 1;
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_run_log() {
+        let log_path = env::temp_dir().join("taxel_test_run_log");
+        let _ = fs::remove_dir_all(&log_path);
+        let filing = Filing {
+            xml_file: PathBuf::from("ebilanz.xml"),
+            tax_type: String::from("Bilanz"),
+            tax_version: String::from("6.5"),
+        };
+
+        let mut paths = vec![];
+        for _ in 0..3 {
+            let mut run =
+                RunLog::create(&log_path, "validate", RunName::Timestamp, Some(2)).unwrap();
+            let log_dir = run.path().to_owned();
+            let res = run.record(
+                &filing,
+                &log_dir,
+                Err(ExitError::new(exit::VALIDATION_ERROR, "Validation failed").into()),
+            );
+            assert!(res.is_err());
+            paths.push(run.finish().unwrap());
+        }
+
+        let runs = runs(&log_path).unwrap();
+        assert_eq!(
+            runs.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            vec![&paths[1], &paths[2]]
+        );
+        let summary = &runs[1].1;
+        assert_eq!(summary.command, "validate");
+        assert_eq!(summary.exit_code, exit::VALIDATION_ERROR);
+        assert_eq!(summary.filings[0].status, "invalid");
+        assert_eq!(
+            summary.filings[0].message.as_deref(),
+            Some("Validation failed")
+        );
+        assert_eq!(summary.filings[0].error_code, None);
+    }
+
+    #[test]
+    fn test_ticket_dir_name() {
+        assert_eq!(
+            ticket_dir_name("et1a2b3c4d5e6f").as_deref(),
+            Some("et1a2b3c4d5e6f")
+        );
+        assert_eq!(ticket_dir_name("../../etc").as_deref(), Some("______etc"));
+        assert_eq!(ticket_dir_name("a/b\\c").as_deref(), Some("a_b_c"));
+        assert_eq!(ticket_dir_name("..").as_deref(), Some("__"));
+        assert_eq!(ticket_dir_name(""), None);
+    }
+}
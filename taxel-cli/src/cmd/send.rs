@@ -1,57 +1,538 @@
 //! Validate xml file according to the given taxonomy and send xml file to the
 //! tax authorities.
 
-use super::utils;
-use crate::arg;
-use clap::{Arg, ArgMatches};
-use eric_sdk::Eric;
+use super::{
+    batch::{self, Filing},
+    run_log::RunLog,
+    utils, SEND,
+};
+use crate::{
+    arg::{self, FORCE},
+    auth::{self, Credentials, PinSource, CERTIFICATE_PASSWORD, CERTIFICATE_PATH},
+};
+use anyhow::anyhow;
+use clap::{crate_version, Arg, ArgMatches};
 use std::{
-    env::current_dir,
-    fs,
+    env, fs,
     path::{Path, PathBuf},
 };
+use taxel::SourceMap;
+use taxel_eric::{
+    archive::{self, Archive, Transmission},
+    report_response, Eric, EricBackend, Outcome,
+};
 
-pub fn send_args() -> [Arg<'static>; 5] {
+/// The report of each xml file in batch mode.
+const REPORT_FILE: &str = "report.json";
+
+pub fn force() -> Arg<'static> {
+    Arg::new(FORCE)
+        .long(FORCE)
+        .required(false)
+        .takes_value(false)
+        .help("Send the xml file even if an identical xml file was accepted before.")
+}
+
+pub fn send_args() -> [Arg<'static>; 17] {
     [
         arg::tax_type(),
         arg::tax_version(),
         arg::xml_file(),
+        arg::xml_dir(),
+        arg::manifest(),
         arg::print(),
         arg::log_dir(),
+        arg::run_name(),
+        arg::keep_runs(),
+        arg::report_file(),
+        arg::source_file(),
+        arg::certificate_file(),
+        arg::pin_env(),
+        arg::pin_fd(),
+        arg::pin_prompt(),
+        arg::archive_dir(),
+        force(),
     ]
 }
 
 pub fn send(matches: &ArgMatches) -> Result<(), anyhow::Error> {
-    let xml_file = arg::get_one(matches, arg::XML_FILE)?;
-    let tax_type = arg::get_one(matches, arg::TAX_TYPE)?;
-    let tax_version = arg::get_one(matches, arg::TAX_VERSION)?;
-    let log_dir = arg::get_maybe_one(matches, arg::LOG_DIR);
-    let log_path = match log_dir {
-        Some(log_dir) => PathBuf::from(log_dir),
-        None => current_dir()?,
+    // Check the credentials before initializing ERiC, and export them while
+    // taxel is single-threaded
+    let credentials = credentials(matches)?;
+    credentials.export();
+    let log_path = utils::log_path(matches)?;
+    let eric = Eric::new(&log_path)?;
+
+    send_with(&eric, matches, &credentials)
+}
+
+/// Get the certificate and its PIN.
+///
+/// The certificate file defaults to the environment variable
+/// `CERTIFICATE_PATH`. If no source of the PIN is given, the PIN is read from
+/// the environment variable `CERTIFICATE_PASSWORD` if set, otherwise it is
+/// prompted.
+pub fn credentials(matches: &ArgMatches) -> Result<Credentials, anyhow::Error> {
+    let certificate_path = match arg::get_maybe_one(matches, arg::CERTIFICATE_FILE) {
+        Some(certificate_file) => PathBuf::from(certificate_file),
+        None => env::var_os(CERTIFICATE_PATH)
+            .map(PathBuf::from)
+            .ok_or(anyhow!(
+            "Missing certificate file; use '--{}' or the environment variable '{CERTIFICATE_PATH}'",
+            arg::CERTIFICATE_FILE
+        ))?,
+    };
+
+    auth::check_certificate(&certificate_path)?;
+
+    let pin_source = if let Some(name) = arg::get_maybe_one(matches, arg::PIN_ENV) {
+        PinSource::Env(name.to_owned())
+    } else if let Some(fd) = arg::get_maybe_one(matches, arg::PIN_FD) {
+        let fd = fd
+            .parse()
+            .map_err(|_| anyhow!("Invalid file descriptor '{fd}' for PIN"))?;
+        PinSource::Fd(fd)
+    } else if matches.contains_id(arg::PIN_PROMPT) {
+        PinSource::Prompt
+    } else if env::var_os(CERTIFICATE_PASSWORD).is_some() {
+        PinSource::Env(String::from(CERTIFICATE_PASSWORD))
+    } else {
+        PinSource::Prompt
     };
+
+    let pin = auth::read_pin(&pin_source, &certificate_path)?;
+
+    Ok(Credentials::new(certificate_path, pin))
+}
+
+/// Send the xml file, or the xml files in batch mode, with the given backend.
+///
+/// Each submission is archived, and an xml file which was accepted before is
+/// only sent again with `--force`. The responses are written to a new run
+/// directory in the log directory. In batch mode, the responses, the report,
+/// and the printed pdf file of each xml file are written to a subdirectory of
+/// the run directory.
+pub fn send_with<E>(
+    eric: &E,
+    matches: &ArgMatches,
+    credentials: &Credentials,
+) -> Result<(), anyhow::Error>
+where
+    E: EricBackend,
+{
+    let log_path = utils::log_path(matches)?;
     let print_config = if matches.contains_id(arg::PRINT) {
         let pdf_name = arg::get_one(matches, arg::PRINT)?;
         Some(pdf_name)
     } else {
         None
     };
-    let xml_path = Path::new(xml_file);
-    let xml = fs::read_to_string(xml_path)?;
+    let archive = Archive::new(arg::get_one(matches, arg::ARCHIVE_DIR)?);
+    let is_forced = matches.contains_id(FORCE);
 
-    let eric = Eric::new(&log_path)?;
+    if batch::is_batch(matches) {
+        let filings = batch::filings(matches)?;
+        let mut run_log = RunLog::from_matches(&log_path, SEND, matches)?;
+
+        let res = batch::run(&mut run_log, &filings, |filing, log_dir| {
+            let pdf_path = print_config.map(|pdf_name| log_dir.join(pdf_name));
+            let report_file = log_dir.join(REPORT_FILE);
+            let options = SendOptions {
+                credentials,
+                archive: &archive,
+                is_forced,
+                log_path: log_dir,
+                pdf_path: pdf_path.as_deref(),
+                report_file: Some(&report_file),
+                sources: &SourceMap::new(),
+            };
+
+            send_filing(eric, filing, &options)
+        });
+        run_log.finish()?;
+
+        return res;
+    }
+
+    let (tax_type, tax_version) = arg::get_tax_type_version(matches)?;
+    let filing = Filing::new(
+        PathBuf::from(arg::get_one(matches, arg::XML_FILE)?),
+        tax_type,
+        tax_version,
+    );
+    let sources = match arg::get_maybe_one(matches, arg::SOURCE_FILE) {
+        Some(source_file) => utils::read_sources(Path::new(source_file), &filing.xml_file)?,
+        None => SourceMap::new(),
+    };
+    let mut run_log = RunLog::from_matches(&log_path, SEND, matches)?;
+    let log_dir = run_log.path().to_owned();
+    let options = SendOptions {
+        credentials,
+        archive: &archive,
+        is_forced,
+        log_path: &log_dir,
+        pdf_path: print_config.map(Path::new),
+        report_file: arg::get_maybe_one(matches, arg::REPORT_FILE).map(Path::new),
+        sources: &sources,
+    };
+
+    let outcome = send_filing(eric, &filing, &options);
+    let res = run_log.record(&filing, &log_dir, outcome);
+    run_log.finish()?;
+
+    res
+}
+
+/// The options to send a single xml file.
+struct SendOptions<'a> {
+    credentials: &'a Credentials,
+    archive: &'a Archive,
+    /// Send the xml file even if it was accepted before.
+    is_forced: bool,
+    log_path: &'a Path,
+    pdf_path: Option<&'a Path>,
+    report_file: Option<&'a Path>,
+    sources: &'a SourceMap,
+}
+
+fn send_filing<E>(
+    eric: &E,
+    filing: &Filing,
+    options: &SendOptions,
+) -> Result<Outcome, anyhow::Error>
+where
+    E: EricBackend,
+{
+    let xml = fs::read_to_string(&filing.xml_file)
+        .map_err(|err| anyhow!("Can't read xml file '{}': {err}", filing.xml_file.display()))?;
+    let archive = options.archive;
+
+    if !options.is_forced {
+        if let Some(submission) = archive.find_accepted(&archive::sha256(xml.as_bytes()))? {
+            return Err(anyhow!(
+                "Identical xml file was already sent at {} with transfer ticket '{}' (submission '{}'); use '--{FORCE}' to send it again",
+                submission.timestamp,
+                submission.transfer_ticket.as_deref().unwrap_or_default(),
+                submission.id,
+            ));
+        }
+    }
+
+    let pdf_path = options.pdf_path.map(utils::path_to_str).transpose()?;
+    let response = eric.send(
+        xml.clone(),
+        &filing.tax_type,
+        &filing.tax_version,
+        options.credentials,
+        pdf_path,
+    )?;
 
-    let response = eric.send(xml, tax_type, tax_version, print_config)?;
+    utils::log_response(options.log_path, &response)?;
+    let outcome = report_response(eric, &response, options.report_file, options.sources);
+    let is_accepted = outcome.as_ref().is_ok_and(|outcome| outcome.result.is_ok());
 
-    utils::log_response(&log_path, &response)?;
+    let xml_file = filing.xml_file.display().to_string();
+    let transmission = Transmission {
+        xml: &xml,
+        xml_file: &xml_file,
+        tax_type: &filing.tax_type,
+        tax_version: &filing.tax_version,
+        response: &response,
+        taxel_version: crate_version!(),
+        pdf_path: options.pdf_path,
+    };
+    let submission = archive.store(&transmission, is_accepted)?;
+    println!(
+        "Archiving submission to '{}'",
+        archive.path(&submission.id).display()
+    );
 
-    Ok(())
+    outcome
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{app, cmd};
+    use crate::{
+        app,
+        cmd::{self, run_log},
+    };
+    use std::{env, path::PathBuf};
+    use taxel_eric::{
+        exit::{self, ExitError},
+        MockEric, PDF_STUB,
+    };
+
+    fn log_dir(test_name: &str) -> PathBuf {
+        let log_dir = env::temp_dir().join(test_name);
+        let _ = fs::remove_dir_all(&log_dir);
+        fs::create_dir_all(&log_dir).unwrap();
+        log_dir
+    }
+
+    /// The directory of the latest run in the log directory.
+    fn run_dir(log_dir: &Path) -> PathBuf {
+        let (path, _) = run_log::runs(log_dir).unwrap().pop().unwrap();
+        path
+    }
+
+    fn test_credentials() -> Credentials {
+        Credentials::new("../test_data/test-certificate.pfx", "123456")
+    }
+
+    fn send_args(log_dir: &Path, pdf_path: &Path) -> Vec<String> {
+        [
+            cmd::BIN,
+            cmd::SEND,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--log-dir",
+            log_dir.to_str().unwrap(),
+            "--print",
+            pdf_path.to_str().unwrap(),
+            "--archive-dir",
+            log_dir.join("archive").to_str().unwrap(),
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect()
+    }
+
+    #[test]
+    fn test_send_with_mock() {
+        let log_dir = log_dir("taxel_test_send_with_mock");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let app = app();
+        let matches = app.get_matches_from(send_args(&log_dir, &pdf_path));
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        let eric = MockEric::new([MockEric::success(true)]);
+
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        assert!(res.is_ok());
+        assert_eq!(eric.requests()[0].action, "send");
+        assert_eq!(
+            eric.requests()[0].certificate_path.as_deref(),
+            Some("../test_data/test-certificate.pfx")
+        );
+        assert_eq!(fs::read(&pdf_path).unwrap(), PDF_STUB);
+        assert!(
+            fs::read_to_string(run_dir(&log_dir).join("server_response.xml"))
+                .unwrap()
+                .contains("<TransferTicket>mock-transfer-ticket</TransferTicket>")
+        );
+    }
+
+    #[test]
+    fn test_send_with_mock_server_error() {
+        let log_dir = log_dir("taxel_test_send_with_mock_server_error");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let _ = fs::remove_file(&pdf_path);
+        let app = app();
+        let matches = app.get_matches_from(send_args(&log_dir, &pdf_path));
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        let eric = MockEric::new([MockEric::server_error()]);
+
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        let err = res.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExitError>().map(|err| err.code),
+            Some(exit::TRANSMISSION_ERROR)
+        );
+        assert!(!pdf_path.exists());
+        assert!(
+            fs::read_to_string(run_dir(&log_dir).join("server_response.xml"))
+                .unwrap()
+                .contains("<Code>610101292</Code>")
+        );
+    }
+
+    #[test]
+    fn test_send_with_mock_run_name_ticket() {
+        let log_dir = log_dir("taxel_test_send_with_mock_run_name_ticket");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let mut args = send_args(&log_dir, &pdf_path);
+        args.extend(["--run-name", "ticket", "--keep-runs", "1", "--force"].map(String::from));
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        let eric = MockEric::new([MockEric::success(true), MockEric::server_error()]);
+
+        send_with(&eric, subcommand_matches, &test_credentials()).unwrap();
+
+        let runs = run_log::runs(&log_dir).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].0, log_dir.join("mock-transfer-ticket"));
+        let filing = &runs[0].1.filings[0];
+        assert_eq!(filing.status, "ok");
+        assert_eq!(
+            filing.transfer_ticket.as_deref(),
+            Some("mock-transfer-ticket")
+        );
+        assert_eq!(
+            filing.log_dir,
+            log_dir.join("mock-transfer-ticket").display().to_string()
+        );
+
+        // A run without transfer ticket is named by its start time, and older
+        // runs are removed
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        assert!(res.is_err());
+        let runs = run_log::runs(&log_dir).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].1.exit_code, exit::TRANSMISSION_ERROR);
+        assert_eq!(runs[0].1.filings[0].status, "rejected");
+        assert!(!log_dir.join("mock-transfer-ticket").exists());
+    }
+
+    #[test]
+    fn test_send_with_mock_archive() {
+        let log_dir = log_dir("taxel_test_send_with_mock_archive");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let args = send_args(&log_dir, &pdf_path);
+        let archive = Archive::new(log_dir.join("archive"));
+        let eric = MockEric::new([MockEric::success(true), MockEric::success(true)]);
+
+        let matches = app().get_matches_from(&args);
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        send_with(&eric, subcommand_matches, &test_credentials()).unwrap();
+
+        let submissions = archive.submissions().unwrap();
+        assert_eq!(submissions.len(), 1);
+        assert!(submissions[0].accepted);
+        assert!(submissions[0].has_pdf);
+        assert_eq!(
+            submissions[0].transfer_ticket.as_deref(),
+            Some("mock-transfer-ticket")
+        );
+        let submission_path = archive.path(&submissions[0].id);
+        assert_eq!(
+            fs::read(submission_path.join(archive::PDF_FILE)).unwrap(),
+            PDF_STUB
+        );
+        assert_eq!(
+            fs::read_to_string(submission_path.join(archive::XML_FILE)).unwrap(),
+            eric.requests()[0].xml
+        );
+
+        // An identical xml file is refused
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+        assert!(res
+            .unwrap_err()
+            .to_string()
+            .starts_with("Identical xml file was already sent"));
+        assert_eq!(eric.requests().len(), 1);
+
+        // ... unless forced
+        let mut forced_args = args.clone();
+        forced_args.push(String::from("--force"));
+        let matches = app().get_matches_from(forced_args);
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        send_with(&eric, subcommand_matches, &test_credentials()).unwrap();
+        assert_eq!(archive.submissions().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_send_with_mock_archive_rejected() {
+        let log_dir = log_dir("taxel_test_send_with_mock_archive_rejected");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let archive = Archive::new(log_dir.join("archive"));
+        let eric = MockEric::new([MockEric::server_error(), MockEric::success(true)]);
+        let matches = app().get_matches_from(send_args(&log_dir, &pdf_path));
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        assert!(res.is_err());
+        let submissions = archive.submissions().unwrap();
+        assert_eq!(submissions.len(), 1);
+        assert!(!submissions[0].accepted);
+        assert!(!submissions[0].has_pdf);
+
+        // A rejected xml file can be sent again
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        assert!(res.is_ok());
+        assert_eq!(archive.submissions().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_send_with_mock_missing_response() {
+        let log_dir = log_dir("taxel_test_send_with_mock_missing_response");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let app = app();
+        let matches = app.get_matches_from(send_args(&log_dir, &pdf_path));
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+        let eric = MockEric::new([]);
+
+        let res = send_with(&eric, subcommand_matches, &test_credentials());
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_credentials_from_args() {
+        let log_dir = log_dir("taxel_test_credentials_from_args");
+        let certificate_path = log_dir.join("certificate.pfx");
+        fs::write(&certificate_path, b"pfx").unwrap();
+        env::set_var("TAXEL_TEST_CREDENTIALS_PIN", "123456");
+        let args = [
+            cmd::BIN,
+            cmd::SEND,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--certificate-file",
+            certificate_path.to_str().unwrap(),
+            "--pin-env",
+            "TAXEL_TEST_CREDENTIALS_PIN",
+        ];
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+
+        let credentials = credentials(subcommand_matches).unwrap();
+
+        assert_eq!(credentials.certificate_path, certificate_path);
+        assert_eq!(credentials.pin(), "123456");
+    }
+
+    #[test]
+    fn test_credentials_missing_certificate() {
+        let args = [
+            cmd::BIN,
+            cmd::SEND,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--certificate-file",
+            "../test_data/missing-certificate.pfx",
+            "--pin-env",
+            "TAXEL_TEST_CREDENTIALS_PIN",
+        ];
+        let matches = app().get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
+
+        let res = credentials(subcommand_matches);
+
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "Certificate file '../test_data/missing-certificate.pfx' doesn't exist"
+        );
+    }
+
+    #[test]
+    fn test_send_args_pin_conflict() {
+        let args = [
+            cmd::BIN,
+            cmd::SEND,
+            "--xml-file",
+            "ebilanz.xml",
+            "--pin-env",
+            "PIN",
+            "--pin-prompt",
+        ];
+
+        assert!(app().try_get_matches_from(args).is_err());
+    }
 
     #[test]
     #[cfg_attr(not(feature = "integration-test"), ignore)]
@@ -63,10 +544,12 @@ mod tests {
             "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
             "--certificate-file",
             "../test_data/test-certificate.pfx",
-            "--password",
-            "123456",
+            "--pin-env",
+            "TAXEL_TEST_PIN",
         ];
 
+        env::set_var("TAXEL_TEST_PIN", "123456");
+
         let app = app();
         let matches = app.get_matches_from(args);
         let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
@@ -88,12 +571,14 @@ mod tests {
             "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
             "--certificate-file",
             "../test_data/test-certificate.pfx",
-            "--password",
-            "123456",
+            "--pin-env",
+            "TAXEL_TEST_PIN",
             "--print",
             "ebilanz.pdf",
         ];
 
+        env::set_var("TAXEL_TEST_PIN", "123456");
+
         let app = app();
         let matches = app.get_matches_from(args);
         let subcommand_matches = matches.subcommand_matches(cmd::SEND).unwrap();
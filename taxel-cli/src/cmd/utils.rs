@@ -1,31 +1,146 @@
-use eric_sdk::EricResponse;
-use std::{fs::File, io::Write, path::Path};
+use crate::arg;
+use anyhow::anyhow;
+use clap::ArgMatches;
+use std::{
+    env::current_dir,
+    fs::{self, File},
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+};
+use taxel::{CsvReaderBuilder, Reader, SourceMap, Trim, XbrlElement};
+use taxel_eric::{
+    archive::{SERVER_RESPONSE_FILE, VALIDATION_RESPONSE_FILE},
+    EricResponse,
+};
 
+/// The log directory; defaults to the current directory.
+pub fn log_path(matches: &ArgMatches) -> Result<PathBuf, anyhow::Error> {
+    let log_path = match arg::get_maybe_one(matches, arg::LOG_DIR) {
+        Some(log_dir) => PathBuf::from(log_dir),
+        None => current_dir()?,
+    };
+
+    Ok(log_path)
+}
+
+/// Write the responses which are present to the log directory.
 pub fn log_response(log_path: &Path, response: &EricResponse) -> Result<(), anyhow::Error> {
     println!("Response code: {}", response.error_code);
 
-    let validation_response_path = log_path.join("validation_response.xml");
-    let server_response_path = log_path.join("server_response.xml");
-
     if !response.validation_response.is_empty() {
+        let validation_response_path = log_path.join(VALIDATION_RESPONSE_FILE);
         println!(
             "Logging validation result to '{}'",
             validation_response_path.display()
         );
+        fs::write(validation_response_path, &response.validation_response)?;
     }
 
     if !response.server_response.is_empty() {
+        let server_response_path = log_path.join(SERVER_RESPONSE_FILE);
         println!(
             "Logging server reponse to '{}'",
             server_response_path.display()
         );
+        fs::write(server_response_path, &response.server_response)?;
+    }
+
+    Ok(())
+}
+
+/// Read the locations of the values of a csv or ods file by concept.
+///
+/// Like `taxel generate`, the values are applied to the given xml file; only
+/// the values of concepts in the xml file are located.
+pub fn read_sources(path: &Path, xml_path: &Path) -> Result<SourceMap, anyhow::Error> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let tags = match extension.as_deref() {
+        Some("csv") => {
+            let mut reader = CsvReaderBuilder::new()
+                .delimiter(b',')
+                .has_headers(true)
+                .trim(Trim::All)
+                .from_path(path)?;
+            taxel::read_tags_from(&mut reader, &path.display().to_string())?
+        }
+        Some("ods") => taxel::read_tags_ods(path)?,
+        _ => {
+            return Err(anyhow!(
+                "Unsupported source file '{}'; expected a csv or ods file",
+                path.display()
+            ))
+        }
+    };
+
+    let xml_file = File::open(xml_path)
+        .map_err(|err| anyhow!("Can't open file '{}': {err}", xml_path.display()))?;
+    let mut xml_reader = Reader::from_reader(BufReader::new(xml_file));
+    xml_reader.trim_text(true);
+    let mut element = XbrlElement::parse(&mut xml_reader)?;
+
+    Ok(element.add_values(&tags))
+}
+
+/// Convert a path to a string, e.g. for the pdf path of ERiC.
+pub fn path_to_str(path: &Path) -> Result<&str, anyhow::Error> {
+    path.to_str()
+        .ok_or(anyhow!("Invalid path '{}'", path.display()))
+}
+
+/// Write the rows as table with left-aligned columns.
+pub fn write_table<W>(
+    writer: &mut W,
+    header: &[&str],
+    rows: &[Vec<String>],
+) -> Result<(), anyhow::Error>
+where
+    W: Write,
+{
+    let mut widths = header
+        .iter()
+        .map(|column| column.chars().count())
+        .collect::<Vec<_>>();
+
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
     }
 
-    let mut validation_response_file = File::create(validation_response_path)?;
-    let mut server_response_file = File::create(server_response_path)?;
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_owned()
+    };
+
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>();
 
-    validation_response_file.write_all(response.validation_response.as_bytes())?;
-    server_response_file.write_all(response.server_response.as_bytes())?;
+    writeln!(writer, "{}", format_row(header.to_vec()))?;
+    writeln!(
+        writer,
+        "{}",
+        format_row(separator.iter().map(String::as_str).collect())
+    )?;
+
+    for row in rows {
+        writeln!(
+            writer,
+            "{}",
+            format_row(row.iter().map(String::as_str).collect())
+        )?;
+    }
 
     Ok(())
 }
@@ -1,56 +1,348 @@
 //! Validate xml file according to the given taxonomy.
 
-use super::utils;
+use super::{
+    batch::{self, Filing},
+    run_log::RunLog,
+    utils, VALIDATE,
+};
 use crate::arg;
+use anyhow::anyhow;
 use clap::{Arg, ArgMatches};
-use eric_sdk::Eric;
 use std::{
-    env::current_dir,
     fs,
     path::{Path, PathBuf},
 };
+use taxel::SourceMap;
+use taxel_eric::{report_response, Eric, EricBackend, Outcome};
+
+/// The report of each xml file in batch mode.
+const REPORT_FILE: &str = "report.json";
 
-pub fn validate_args() -> [Arg<'static>; 5] {
+pub fn validate_args() -> [Arg<'static>; 11] {
     [
         arg::tax_type(),
         arg::tax_version(),
         arg::xml_file(),
+        arg::xml_dir(),
+        arg::manifest(),
         arg::print(),
         arg::log_dir(),
+        arg::run_name(),
+        arg::keep_runs(),
+        arg::report_file(),
+        arg::source_file(),
     ]
 }
 
 pub fn validate(matches: &ArgMatches) -> Result<(), anyhow::Error> {
-    let xml_file = arg::get_one(matches, arg::XML_FILE)?;
-    let tax_type = arg::get_one(matches, arg::TAX_TYPE)?;
-    let tax_version = arg::get_one(matches, arg::TAX_VERSION)?;
-    let log_dir = arg::get_maybe_one(matches, arg::LOG_DIR);
-    let log_path = match log_dir {
-        Some(log_dir) => PathBuf::from(log_dir),
-        None => current_dir()?,
-    };
+    let log_path = utils::log_path(matches)?;
+    let eric = Eric::new(&log_path)?;
+
+    validate_with(&eric, matches)
+}
+
+/// Validate the xml file, or the xml files in batch mode, with the given
+/// backend.
+///
+/// The responses are written to a new run directory in the log directory. In
+/// batch mode, the responses, the report, and the printed pdf file of each xml
+/// file are written to a subdirectory of the run directory.
+pub fn validate_with<E>(eric: &E, matches: &ArgMatches) -> Result<(), anyhow::Error>
+where
+    E: EricBackend,
+{
+    let log_path = utils::log_path(matches)?;
     let print_config = if matches.contains_id(arg::PRINT) {
         let pdf_name = arg::get_one(matches, arg::PRINT)?;
         Some(pdf_name)
     } else {
         None
     };
-    let xml_path = Path::new(xml_file);
-    let xml = fs::read_to_string(xml_path)?;
 
-    let eric = Eric::new(&log_path)?;
+    if batch::is_batch(matches) {
+        let filings = batch::filings(matches)?;
+        let mut run_log = RunLog::from_matches(&log_path, VALIDATE, matches)?;
+
+        let res = batch::run(&mut run_log, &filings, |filing, log_dir| {
+            let pdf_path = print_config.map(|pdf_name| log_dir.join(pdf_name));
+            let report_file = log_dir.join(REPORT_FILE);
+
+            validate_filing(
+                eric,
+                filing,
+                log_dir,
+                pdf_path.as_deref(),
+                Some(&report_file),
+                &SourceMap::new(),
+            )
+        });
+        run_log.finish()?;
+
+        return res;
+    }
+
+    let (tax_type, tax_version) = arg::get_tax_type_version(matches)?;
+    let filing = Filing::new(
+        PathBuf::from(arg::get_one(matches, arg::XML_FILE)?),
+        tax_type,
+        tax_version,
+    );
+    let report_file = arg::get_maybe_one(matches, arg::REPORT_FILE).map(Path::new);
+    let sources = match arg::get_maybe_one(matches, arg::SOURCE_FILE) {
+        Some(source_file) => utils::read_sources(Path::new(source_file), &filing.xml_file)?,
+        None => SourceMap::new(),
+    };
 
-    let response = eric.validate(xml, tax_type, tax_version, print_config)?;
+    let mut run_log = RunLog::from_matches(&log_path, VALIDATE, matches)?;
+    let log_dir = run_log.path().to_owned();
 
-    utils::log_response(&log_path, &response)?;
+    let outcome = validate_filing(
+        eric,
+        &filing,
+        &log_dir,
+        print_config.map(Path::new),
+        report_file,
+        &sources,
+    );
+    let res = run_log.record(&filing, &log_dir, outcome);
+    run_log.finish()?;
 
-    Ok(())
+    res
+}
+
+fn validate_filing<E>(
+    eric: &E,
+    filing: &Filing,
+    log_path: &Path,
+    pdf_path: Option<&Path>,
+    report_file: Option<&Path>,
+    sources: &SourceMap,
+) -> Result<Outcome, anyhow::Error>
+where
+    E: EricBackend,
+{
+    let xml = fs::read_to_string(&filing.xml_file)
+        .map_err(|err| anyhow!("Can't read xml file '{}': {err}", filing.xml_file.display()))?;
+    let pdf_path = pdf_path.map(utils::path_to_str).transpose()?;
+
+    let response = eric.validate(xml, &filing.tax_type, &filing.tax_version, pdf_path)?;
+
+    utils::log_response(log_path, &response)?;
+    report_response(eric, &response, report_file, sources)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{app, cmd};
+    use crate::{
+        app,
+        cmd::{self, run_log},
+    };
+    use std::env;
+    use taxel_eric::{
+        exit::{self, ExitError},
+        MockEric, PDF_STUB,
+    };
+
+    fn log_dir(test_name: &str) -> PathBuf {
+        let log_dir = env::temp_dir().join(test_name);
+        let _ = fs::remove_dir_all(&log_dir);
+        fs::create_dir_all(&log_dir).unwrap();
+        log_dir
+    }
+
+    /// The directory of the latest run in the log directory.
+    fn run_dir(log_dir: &Path) -> PathBuf {
+        let (path, _) = run_log::runs(log_dir).unwrap().pop().unwrap();
+        path
+    }
+
+    #[test]
+    fn test_validate_with_mock() {
+        let log_dir = log_dir("taxel_test_validate_with_mock");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let args = vec![
+            cmd::BIN,
+            cmd::VALIDATE,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--log-dir",
+            log_dir.to_str().unwrap(),
+            "--print",
+            pdf_path.to_str().unwrap(),
+        ];
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::VALIDATE).unwrap();
+        let eric = MockEric::new([MockEric::success(false)]);
+
+        let res = validate_with(&eric, subcommand_matches);
+
+        assert!(res.is_ok());
+        let requests = eric.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].action, "validate");
+        assert_eq!(requests[0].tax_type, "Bilanz");
+        assert_eq!(requests[0].tax_version, "6.5");
+        assert!(requests[0].xml.contains("ebilanz:EBilanz"));
+        assert_eq!(fs::read(&pdf_path).unwrap(), PDF_STUB);
+        let run_dir = run_dir(&log_dir);
+        assert!(fs::read_to_string(run_dir.join("validation_response.xml"))
+            .unwrap()
+            .contains("<Erfolg>"));
+        assert!(!run_dir.join("server_response.xml").exists());
+        assert!(run_dir.join("summary.json").is_file());
+    }
+
+    #[test]
+    fn test_validate_with_mock_validation_error() {
+        let log_dir = log_dir("taxel_test_validate_with_mock_validation_error");
+        let pdf_path = log_dir.join("ebilanz.pdf");
+        let report_file = log_dir.join("report.json");
+        let _ = fs::remove_file(&pdf_path);
+        let args = vec![
+            cmd::BIN,
+            cmd::VALIDATE,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--log-dir",
+            log_dir.to_str().unwrap(),
+            "--print",
+            pdf_path.to_str().unwrap(),
+            "--report-file",
+            report_file.to_str().unwrap(),
+        ];
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::VALIDATE).unwrap();
+        let eric = MockEric::new([MockEric::validation_error()]);
+
+        let res = validate_with(&eric, subcommand_matches);
+
+        let err = res.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExitError>().map(|err| err.code),
+            Some(exit::VALIDATION_ERROR)
+        );
+        assert!(!pdf_path.exists());
+        let report = fs::read_to_string(log_dir.join("report.json")).unwrap();
+        assert!(report.contains(r#""rule_id": "E_BS_001""#));
+        assert!(report.contains(r#""concept": "de-gaap-ci:bs.ass""#));
+        assert!(
+            fs::read_to_string(run_dir(&log_dir).join("validation_response.xml"))
+                .unwrap()
+                .contains("<Fehlerregelpruefung>")
+        );
+    }
+
+    #[test]
+    fn test_validate_with_mock_source_file() {
+        let log_dir = log_dir("taxel_test_validate_with_mock_source_file");
+        let source_file = log_dir.join("balance.csv");
+        let report_file = log_dir.join("report.json");
+        fs::write(
+            &source_file,
+            "ebilanz_key,ebilanz_value\n\
+             de-gcd:genInfo.company.id.name,Landwirt GmbH\n\
+             de-gaap-ci:bs.ass,300.00\n",
+        )
+        .unwrap();
+        let args = vec![
+            cmd::BIN,
+            cmd::VALIDATE,
+            "--xml-file",
+            "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+            "--log-dir",
+            log_dir.to_str().unwrap(),
+            "--report-file",
+            report_file.to_str().unwrap(),
+            "--source-file",
+            source_file.to_str().unwrap(),
+        ];
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::VALIDATE).unwrap();
+        let eric = MockEric::new([MockEric::validation_error()]);
+
+        let res = validate_with(&eric, subcommand_matches);
+
+        assert!(res.is_err());
+        let report = fs::read_to_string(&report_file).unwrap();
+        assert!(report.contains(&format!(r#""file": "{}""#, source_file.display())));
+        assert!(report.contains(r#""row": 3"#));
+        assert!(report.contains(r#""column": 2"#));
+    }
+
+    #[test]
+    fn test_read_sources() {
+        let log_dir = log_dir("taxel_test_read_sources");
+        let source_file = log_dir.join("balance.csv");
+        fs::write(
+            &source_file,
+            "ebilanz_key,ebilanz_value\n\
+             de-gaap-ci:bs.ass,300.00\n\
+             de-gaap-ci:unknown,100.00\n",
+        )
+        .unwrap();
+
+        let sources = utils::read_sources(
+            &source_file,
+            Path::new("../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml"),
+        )
+        .unwrap();
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources.get("de-gaap-ci:bs.ass").unwrap().row, 2);
+        assert!(sources.get("de-gaap-ci:unknown").is_none());
+    }
+
+    #[test]
+    fn test_validate_with_mock_xml_dir() {
+        let log_dir = log_dir("taxel_test_validate_with_mock_xml_dir");
+        let xml_dir = log_dir.join("xml");
+        let _ = fs::remove_dir_all(&xml_dir);
+        fs::create_dir_all(&xml_dir).unwrap();
+        for name in ["client_a.xml", "client_b.xml"] {
+            fs::copy(
+                "../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml",
+                xml_dir.join(name),
+            )
+            .unwrap();
+        }
+        let args = vec![
+            cmd::BIN,
+            cmd::VALIDATE,
+            "--xml-dir",
+            xml_dir.to_str().unwrap(),
+            "--log-dir",
+            log_dir.to_str().unwrap(),
+            "--print",
+            "ebilanz.pdf",
+        ];
+        let app = app();
+        let matches = app.get_matches_from(args);
+        let subcommand_matches = matches.subcommand_matches(cmd::VALIDATE).unwrap();
+        let eric = MockEric::new([MockEric::success(false), MockEric::validation_error()]);
+
+        let res = validate_with(&eric, subcommand_matches);
+
+        let err = res.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ExitError>().map(|err| err.code),
+            Some(exit::VALIDATION_ERROR)
+        );
+        assert_eq!(err.to_string(), "1 of 2 xml files failed");
+        assert_eq!(eric.requests().len(), 2);
+        assert_eq!(
+            fs::read(run_dir(&log_dir).join("client_a/ebilanz.pdf")).unwrap(),
+            PDF_STUB
+        );
+        assert!(run_dir(&log_dir)
+            .join("client_a/validation_response.xml")
+            .is_file());
+        let report = fs::read_to_string(run_dir(&log_dir).join("client_b/report.json")).unwrap();
+        assert!(report.contains(r#""rule_id": "E_BS_001""#));
+    }
 
     #[test]
     #[cfg_attr(not(feature = "integration-test"), ignore)]
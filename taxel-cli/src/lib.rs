@@ -1,4 +1,5 @@
 pub mod arg;
+pub mod auth;
 pub mod cmd;
 
 use clap::{crate_version, App, SubCommand};
@@ -32,4 +33,42 @@ pub fn app() -> App<'static> {
                 .args(cmd::send_args())
                 .about("Validate and send xml file"),
         )
+        .subcommand(
+            SubCommand::with_name(cmd::ROLLOVER)
+                .args(cmd::rollover_args())
+                .about("Roll over last year's xml file to the current year"),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::DIFF)
+                .args(cmd::diff_args())
+                .about("Compare the facts of two xml files"),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::MERGE)
+                .args(cmd::merge_args())
+                .about("Merge several csv or xml files into one file")
+                .after_help(
+                    "The files are merged in order of priority, i.e. the first file has the highest priority:\n\
+                     \n\
+                     - Empty or nil values are filled with the value of the first file which has a value.\n\
+                     - For conflicting values the value of the first file is used, and a warning is printed.\n\
+                     - For xml files, the header, contexts, and units of the first file are kept.\n\
+                     - Tuples are only taken from the first xml file; tuples with values in other files are an error.",
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::QUERY)
+                .args(cmd::query_args())
+                .about("Query facts of a xml file by concept, context, and unit"),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::HISTORY)
+                .args(cmd::history_args())
+                .about("List and inspect archived submissions"),
+        )
+        .subcommand(
+            SubCommand::with_name(cmd::MIGRATE)
+                .args(cmd::migrate_args())
+                .about("Migrate xml or csv file to another taxonomy version"),
+        )
 }
@@ -1,5 +1,7 @@
 use anyhow::anyhow;
+use std::process;
 use taxel_cli::{app, arg, cmd};
+use taxel_eric::exit::ExitError;
 
 #[macro_use]
 extern crate log;
@@ -13,11 +15,26 @@ fn main() -> Result<(), anyhow::Error> {
         info!("matches: {:#?}", matches);
     }
 
-    match matches.subcommand() {
+    let res = match matches.subcommand() {
         Some((cmd::EXTRACT, matches)) => cmd::extract(matches),
         Some((cmd::GENERATE, matches)) => cmd::generate(matches),
         Some((cmd::VALIDATE, matches)) => cmd::validate(matches),
         Some((cmd::SEND, matches)) => cmd::send(matches),
+        Some((cmd::ROLLOVER, matches)) => cmd::rollover(matches),
+        Some((cmd::DIFF, matches)) => cmd::diff(matches),
+        Some((cmd::MERGE, matches)) => cmd::merge(matches),
+        Some((cmd::QUERY, matches)) => cmd::query(matches),
+        Some((cmd::HISTORY, matches)) => cmd::history(matches),
+        Some((cmd::MIGRATE, matches)) => cmd::migrate(matches),
         _ => Err(anyhow!("Subcommand not found")),
+    };
+
+    if let Err(err) = &res {
+        if let Some(exit_error) = err.downcast_ref::<ExitError>() {
+            eprintln!("Error: {exit_error}");
+            process::exit(exit_error.code);
+        }
     }
+
+    res
 }
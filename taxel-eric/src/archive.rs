@@ -0,0 +1,336 @@
+//! The archive of submissions to the tax authorities.
+//!
+//! For GoBD compliance, exactly what was sent and what came back is kept. Each
+//! submission is stored in its own directory which is never overwritten:
+//!
+//! ```text
+//! <archive-dir>/
+//!     20231231T120000.000Z-1a2b3c4d5e6f/
+//!         submission.xml
+//!         submission.xml.sha256
+//!         validation_response.xml
+//!         server_response.xml
+//!         confirmation.pdf
+//!         submission.json
+//! ```
+//!
+//! The file `submission.json` contains the timestamp, the SHA-256 of the xml
+//! file, the transfer ticket, and the taxel version.
+
+use crate::EricResponse;
+use anyhow::anyhow;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use taxel::Reader;
+
+pub const XML_FILE: &str = "submission.xml";
+pub const CHECKSUM_FILE: &str = "submission.xml.sha256";
+pub const VALIDATION_RESPONSE_FILE: &str = "validation_response.xml";
+pub const SERVER_RESPONSE_FILE: &str = "server_response.xml";
+pub const PDF_FILE: &str = "confirmation.pdf";
+pub const METADATA_FILE: &str = "submission.json";
+
+/// The metadata of an archived submission.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    /// The name of the directory of the submission.
+    pub id: String,
+    /// The time of the submission in RFC 3339 format (UTC).
+    pub timestamp: String,
+    /// The SHA-256 of the sent xml file in hex format.
+    pub sha256: String,
+    pub transfer_ticket: Option<String>,
+    /// The submission was accepted by the tax authorities.
+    pub accepted: bool,
+    pub error_code: i32,
+    pub tax_type: String,
+    pub tax_version: String,
+    /// The path of the sent xml file.
+    pub xml_file: String,
+    pub has_pdf: bool,
+    pub taxel_version: String,
+}
+
+/// A xml file sent to the tax authorities and the response.
+#[derive(Debug)]
+pub struct Transmission<'a> {
+    pub xml: &'a str,
+    pub xml_file: &'a str,
+    pub tax_type: &'a str,
+    pub tax_version: &'a str,
+    pub response: &'a EricResponse,
+    /// The version of taxel which sent the xml file.
+    pub taxel_version: &'a str,
+    /// The printed confirmation.
+    pub pdf_path: Option<&'a Path>,
+}
+
+/// The archive of submissions in the given directory.
+#[derive(Debug)]
+pub struct Archive {
+    root: PathBuf,
+}
+
+impl Archive {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The directory of the submission with the given id.
+    pub fn path(&self, id: &str) -> PathBuf {
+        self.root.join(id)
+    }
+
+    /// Store a transmission; existing submissions are never overwritten, and
+    /// the archived files are read-only.
+    pub fn store(
+        &self,
+        transmission: &Transmission,
+        accepted: bool,
+    ) -> Result<Submission, anyhow::Error> {
+        let now = Utc::now();
+        let sha256 = sha256(transmission.xml.as_bytes());
+        let mut id = format!("{}-{}", now.format("%Y%m%dT%H%M%S%.3fZ"), &sha256[..12]);
+
+        // Identical xml files sent within the same millisecond get a suffix
+        if self.path(&id).exists() {
+            let mut counter = 2;
+
+            while self.path(&format!("{id}-{counter}")).exists() {
+                counter += 1;
+            }
+
+            id = format!("{id}-{counter}");
+        }
+
+        let path = self.path(&id);
+
+        fs::create_dir_all(&self.root)?;
+        fs::create_dir(&path)
+            .map_err(|err| anyhow!("Can't create archive '{}': {err}", path.display()))?;
+
+        let response = transmission.response;
+        let has_pdf = match transmission.pdf_path {
+            Some(pdf_path) if pdf_path.is_file() => {
+                write_new(&path.join(PDF_FILE), &fs::read(pdf_path)?)?;
+                true
+            }
+            _ => false,
+        };
+
+        write_new(&path.join(XML_FILE), transmission.xml.as_bytes())?;
+        write_new(
+            &path.join(CHECKSUM_FILE),
+            format!("{sha256}  {XML_FILE}\n").as_bytes(),
+        )?;
+
+        if !response.validation_response.is_empty() {
+            write_new(
+                &path.join(VALIDATION_RESPONSE_FILE),
+                response.validation_response.as_bytes(),
+            )?;
+        }
+
+        if !response.server_response.is_empty() {
+            write_new(
+                &path.join(SERVER_RESPONSE_FILE),
+                response.server_response.as_bytes(),
+            )?;
+        }
+
+        let submission = Submission {
+            id,
+            timestamp: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            sha256,
+            transfer_ticket: transfer_ticket(&response.server_response),
+            accepted,
+            error_code: response.error_code,
+            tax_type: transmission.tax_type.to_owned(),
+            tax_version: transmission.tax_version.to_owned(),
+            xml_file: transmission.xml_file.to_owned(),
+            has_pdf,
+            taxel_version: transmission.taxel_version.to_owned(),
+        };
+
+        write_new(
+            &path.join(METADATA_FILE),
+            serde_json::to_string_pretty(&submission)?.as_bytes(),
+        )?;
+
+        Ok(submission)
+    }
+
+    /// All submissions ordered by time.
+    pub fn submissions(&self) -> Result<Vec<Submission>, anyhow::Error> {
+        let mut submissions = vec![];
+
+        if !self.root.is_dir() {
+            return Ok(submissions);
+        }
+
+        for entry in fs::read_dir(&self.root)? {
+            let metadata_path = entry?.path().join(METADATA_FILE);
+
+            if metadata_path.is_file() {
+                let metadata = fs::read_to_string(&metadata_path)?;
+                let submission = serde_json::from_str(&metadata)
+                    .map_err(|err| anyhow!("Can't read '{}': {err}", metadata_path.display()))?;
+                submissions.push(submission);
+            }
+        }
+
+        submissions.sort_by(|lhs: &Submission, rhs| {
+            (&lhs.timestamp, &lhs.id).cmp(&(&rhs.timestamp, &rhs.id))
+        });
+
+        Ok(submissions)
+    }
+
+    /// Get the submission with the given id.
+    pub fn get(&self, id: &str) -> Result<Submission, anyhow::Error> {
+        self.submissions()?
+            .into_iter()
+            .find(|submission| submission.id == id)
+            .ok_or(anyhow!(
+                "Missing submission '{id}' in archive '{}'",
+                self.root.display()
+            ))
+    }
+
+    /// Find the last accepted submission of a xml file with the given hash.
+    pub fn find_accepted(&self, sha256: &str) -> Result<Option<Submission>, anyhow::Error> {
+        let submission = self
+            .submissions()?
+            .into_iter()
+            .rfind(|submission| submission.accepted && submission.sha256 == sha256);
+
+        Ok(submission)
+    }
+
+    /// Check that the archived xml file matches the recorded hash.
+    pub fn verify(&self, submission: &Submission) -> Result<bool, anyhow::Error> {
+        let xml = fs::read(self.path(&submission.id).join(XML_FILE))?;
+
+        Ok(sha256(&xml) == submission.sha256)
+    }
+}
+
+/// The SHA-256 of the given data in hex format.
+pub fn sha256(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Get the transfer ticket of the server response.
+pub fn transfer_ticket(server_response: &str) -> Option<String> {
+    let mut reader = Reader::from_str(server_response);
+    reader.trim_text(true);
+
+    taxel::extract_tag_values(&mut reader)
+        .ok()?
+        .into_iter()
+        .find(|tag| tag.name == "TransferTicket")
+        .and_then(|tag| tag.value)
+}
+
+/// Write a new read-only file.
+fn write_new(path: &Path, content: &[u8]) -> Result<(), anyhow::Error> {
+    let mut file = File::options()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .map_err(|err| anyhow!("Can't create '{}': {err}", path.display()))?;
+    file.write_all(content)?;
+    file.sync_all()?;
+
+    let mut permissions = file.metadata()?.permissions();
+    permissions.set_readonly(true);
+    fs::set_permissions(path, permissions)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockEric;
+    use std::{env, process};
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_transfer_ticket() {
+        let response = MockEric::success(true);
+
+        assert_eq!(
+            transfer_ticket(&response.server_response).as_deref(),
+            Some("mock-transfer-ticket")
+        );
+        assert_eq!(transfer_ticket(""), None);
+    }
+
+    #[test]
+    fn test_archive() {
+        let root = env::temp_dir().join(format!("taxel_test_archive_{}", process::id()));
+        let archive = Archive::new(&root);
+        let response = MockEric::success(true);
+        let xml = "<Elster/>";
+        let transmission = Transmission {
+            xml,
+            xml_file: "ebilanz.xml",
+            tax_type: "Bilanz",
+            tax_version: "6.5",
+            response: &response,
+            taxel_version: "0.2.0",
+            pdf_path: None,
+        };
+
+        let submission = archive.store(&transmission, true).unwrap();
+
+        let path = archive.path(&submission.id);
+        assert_eq!(fs::read_to_string(path.join(XML_FILE)).unwrap(), xml);
+        assert_eq!(
+            fs::read_to_string(path.join(CHECKSUM_FILE)).unwrap(),
+            format!("{}  submission.xml\n", sha256(xml.as_bytes()))
+        );
+        assert!(path.join(SERVER_RESPONSE_FILE).is_file());
+        assert!(!path.join(PDF_FILE).exists());
+        assert!(fs::metadata(path.join(XML_FILE))
+            .unwrap()
+            .permissions()
+            .readonly());
+        assert_eq!(
+            submission.transfer_ticket.as_deref(),
+            Some("mock-transfer-ticket")
+        );
+        assert_eq!(submission.taxel_version, "0.2.0");
+        assert_eq!(archive.submissions().unwrap(), vec![submission.clone()]);
+        assert_eq!(archive.get(&submission.id).unwrap(), submission);
+        assert_eq!(
+            archive.find_accepted(&sha256(xml.as_bytes())).unwrap(),
+            Some(submission.clone())
+        );
+        assert_eq!(archive.find_accepted(&sha256(b"other")).unwrap(), None);
+        assert!(archive.verify(&submission).unwrap());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
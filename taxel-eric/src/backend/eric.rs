@@ -0,0 +1,111 @@
+use super::EricBackend;
+use crate::{Credentials, EricResponse};
+use anyhow::anyhow;
+use std::path::Path;
+
+/// The ELSTER Rich Client.
+///
+/// ERiC is only available with the feature `eric`; otherwise, [`Eric::new`]
+/// returns an error. To send a xml file, the credentials must be exported with
+/// [`Credentials::export`] before ERiC is started.
+pub struct Eric {
+    #[cfg(feature = "eric")]
+    inner: eric_sdk::Eric,
+}
+
+impl Eric {
+    /// Initialize ERiC, which writes its log file to `log_path`.
+    #[cfg(feature = "eric")]
+    pub fn new(log_path: &Path) -> Result<Self, anyhow::Error> {
+        let inner = eric_sdk::Eric::new(log_path)?;
+
+        Ok(Self { inner })
+    }
+
+    /// Initialize ERiC, which writes its log file to `log_path`.
+    #[cfg(not(feature = "eric"))]
+    pub fn new(_log_path: &Path) -> Result<Self, anyhow::Error> {
+        Err(anyhow!(
+            "ERiC is not available: taxel was built without the feature 'eric'"
+        ))
+    }
+}
+
+#[cfg(feature = "eric")]
+impl From<eric_sdk::EricResponse> for EricResponse {
+    fn from(response: eric_sdk::EricResponse) -> Self {
+        Self::new(
+            response.error_code,
+            response.validation_response,
+            response.server_response,
+        )
+    }
+}
+
+#[cfg(feature = "eric")]
+impl EricBackend for Eric {
+    fn validate(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        self.inner
+            .validate(xml, tax_type, tax_version, pdf_path)
+            .map(EricResponse::from)
+    }
+
+    fn send(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        credentials: &Credentials,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        // The SDK reads the credentials from the environment, which must not be
+        // modified here, e.g. on a worker thread (see `Credentials::export`).
+        if !credentials.is_exported() {
+            return Err(anyhow!(
+                "Missing credentials in the environment: they must be exported before ERiC is started"
+            ));
+        }
+
+        self.inner
+            .send(xml, tax_type, tax_version, pdf_path)
+            .map(EricResponse::from)
+    }
+
+    fn get_error_text(&self, error_code: i32) -> Result<String, anyhow::Error> {
+        self.inner.get_error_text(error_code)
+    }
+}
+
+#[cfg(not(feature = "eric"))]
+impl EricBackend for Eric {
+    fn validate(
+        &self,
+        _xml: String,
+        _tax_type: &str,
+        _tax_version: &str,
+        _pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        unreachable!("Eric can't be initialized without the feature 'eric'")
+    }
+
+    fn send(
+        &self,
+        _xml: String,
+        _tax_type: &str,
+        _tax_version: &str,
+        _credentials: &Credentials,
+        _pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        unreachable!("Eric can't be initialized without the feature 'eric'")
+    }
+
+    fn get_error_text(&self, _error_code: i32) -> Result<String, anyhow::Error> {
+        unreachable!("Eric can't be initialized without the feature 'eric'")
+    }
+}
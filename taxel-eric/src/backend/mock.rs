@@ -0,0 +1,165 @@
+use super::EricBackend;
+use crate::{error_code, Credentials, EricResponse};
+use anyhow::anyhow;
+use std::{cell::RefCell, collections::VecDeque, fs};
+
+/// The content of the pdf file printed by [`MockEric`].
+pub const PDF_STUB: &[u8] = b"%PDF-1.4\n% taxel mock confirmation\n%%EOF\n";
+
+/// A request received by [`MockEric`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct MockRequest {
+    /// Either `validate` or `send`.
+    pub action: String,
+    pub xml: String,
+    pub tax_type: String,
+    pub tax_version: String,
+    pub pdf_path: Option<String>,
+    /// The certificate of a sent xml file; the PIN is not recorded.
+    pub certificate_path: Option<String>,
+}
+
+/// A stand-in for ERiC which returns scripted responses in the given order.
+///
+/// If a pdf path is given and the scripted response is successful, a pdf stub
+/// is written to the pdf path.
+#[derive(Debug, Default)]
+pub struct MockEric {
+    responses: RefCell<VecDeque<EricResponse>>,
+    requests: RefCell<Vec<MockRequest>>,
+}
+
+impl MockEric {
+    pub fn new(responses: impl IntoIterator<Item = EricResponse>) -> Self {
+        Self {
+            responses: RefCell::new(responses.into_iter().collect()),
+            requests: RefCell::new(vec![]),
+        }
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.borrow().clone()
+    }
+
+    /// A successful response; the server response is only present for a
+    /// sent xml file.
+    pub fn success(sent: bool) -> EricResponse {
+        let server_response = if sent {
+            String::from(SERVER_RESPONSE_SUCCESS)
+        } else {
+            String::new()
+        };
+
+        EricResponse::new(
+            error_code::ERIC_OK,
+            String::from(VALIDATION_RESPONSE_SUCCESS),
+            server_response,
+        )
+    }
+
+    /// A response with errors of the plausibility check.
+    pub fn validation_error() -> EricResponse {
+        EricResponse::new(
+            error_code::ERIC_GLOBAL_PRUEF_FEHLER,
+            String::from(VALIDATION_RESPONSE_ERROR),
+            String::new(),
+        )
+    }
+
+    /// A response with an error of the server of the tax authorities.
+    pub fn server_error() -> EricResponse {
+        EricResponse::new(
+            error_code::ERIC_TRANSFER_ERR_XML_NHEADER,
+            String::from(VALIDATION_RESPONSE_SUCCESS),
+            String::from(SERVER_RESPONSE_ERROR),
+        )
+    }
+
+    fn process(
+        &self,
+        action: &str,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        credentials: Option<&Credentials>,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        self.requests.borrow_mut().push(MockRequest {
+            action: action.to_owned(),
+            xml,
+            tax_type: tax_type.to_owned(),
+            tax_version: tax_version.to_owned(),
+            pdf_path: pdf_path.map(|pdf_path| pdf_path.to_owned()),
+            certificate_path: credentials
+                .map(|credentials| credentials.certificate_path.display().to_string()),
+        });
+
+        let response = self
+            .responses
+            .borrow_mut()
+            .pop_front()
+            .ok_or(anyhow!("Missing scripted response for '{action}'"))?;
+
+        if let Some(pdf_path) = pdf_path {
+            if response.error_code == error_code::ERIC_OK {
+                fs::write(pdf_path, PDF_STUB)?;
+            }
+        }
+
+        Ok(response)
+    }
+}
+
+impl EricBackend for MockEric {
+    fn validate(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        self.process("validate", xml, tax_type, tax_version, None, pdf_path)
+    }
+
+    fn send(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        credentials: &Credentials,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error> {
+        self.process(
+            "send",
+            xml,
+            tax_type,
+            tax_version,
+            Some(credentials),
+            pdf_path,
+        )
+    }
+
+    fn get_error_text(&self, error_code: i32) -> Result<String, anyhow::Error> {
+        let text = match error_code {
+            x if x == error_code::ERIC_OK => "Verarbeitung fehlerfrei.",
+            x if x == error_code::ERIC_GLOBAL_PRUEF_FEHLER => {
+                "Fehler während der Plausibilitätsprüfung, Datensatz nicht plausibel."
+            }
+            x if x == error_code::ERIC_TRANSFER_ERR_XML_NHEADER => {
+                "Der Server hat einen Fehler im Nutzdaten-Header gemeldet."
+            }
+            _ => "Keine Klartextfehlermeldung vorhanden.",
+        };
+
+        Ok(text.to_owned())
+    }
+}
+
+const VALIDATION_RESPONSE_SUCCESS: &str = r#"<?xml version="1.0" encoding="UTF-8"?><EricBearbeiteVorgang xmlns="http://www.elster.de/EricXML/1.1/EricBearbeiteVorgang"><Erfolg><Telenummer>1</Telenummer></Erfolg></EricBearbeiteVorgang>"#;
+
+const VALIDATION_RESPONSE_ERROR: &str = r#"<?xml version="1.0" encoding="UTF-8"?><EricBearbeiteVorgang xmlns="http://www.elster.de/EricXML/1.1/EricBearbeiteVorgang"><Fehlerregelpruefung><Nutzdatenticket>1</Nutzdatenticket><Feldidentifikator>de-gaap-ci:bs.ass</Feldidentifikator><PrivateKennnummer>/ebilanz:EBilanz/xbrli:xbrl/de-gaap-ci:bs.ass</PrivateKennnummer><RegelName>Summenpruefung</RegelName><FachlicheFehlerId>E_BS_001</FachlicheFehlerId><Text>Die Summe der Aktiva stimmt nicht mit der Summe der Passiva überein.</Text></Fehlerregelpruefung><Hinweis><Nutzdatenticket>1</Nutzdatenticket><Feldidentifikator>de-gcd:genInfo.company.id.location.street</Feldidentifikator><RegelName>Adresse</RegelName><FachlicheHinweisId>H_GCD_002</FachlicheHinweisId><Text>Die Straße des Unternehmens fehlt.</Text></Hinweis></EricBearbeiteVorgang>"#;
+
+const SERVER_RESPONSE_SUCCESS: &str = r#"<?xml version="1.0" encoding="UTF-8"?><Elster xmlns="http://www.elster.de/elsterxml/schema/v11"><TransferHeader version="11"><Verfahren>ElsterBilanz</Verfahren><DatenArt>Bilanz</DatenArt><Vorgang>send-Auth</Vorgang><TransferTicket>mock-transfer-ticket</TransferTicket><Testmerker>700000004</Testmerker><RC><Rueckgabe><Code>0</Code><Text>Daten wurden erfolgreich angenommen.</Text></Rueckgabe></RC></TransferHeader></Elster>"#;
+
+const SERVER_RESPONSE_ERROR: &str = r#"<?xml version="1.0" encoding="UTF-8"?><Elster xmlns="http://www.elster.de/elsterxml/schema/v11"><TransferHeader version="11"><Verfahren>ElsterBilanz</Verfahren><DatenArt>Bilanz</DatenArt><Vorgang>send-Auth</Vorgang><TransferTicket>mock-transfer-ticket</TransferTicket><Testmerker>700000004</Testmerker><RC><Rueckgabe><Code>610101292</Code><Text>Fehler im Nutzdaten-Header.</Text></Rueckgabe></RC></TransferHeader></Elster>"#;
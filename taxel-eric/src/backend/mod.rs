@@ -0,0 +1,39 @@
+//! The backends to validate and send xml files.
+//!
+//! The ELSTER Rich Client (ERiC) is used by default. A mock backend returning
+//! scripted responses is available to run the commands without the ERiC
+//! library and an Elster certificate.
+
+mod eric;
+mod mock;
+
+pub use eric::Eric;
+pub use mock::{MockEric, MockRequest, PDF_STUB};
+
+use crate::{Credentials, EricResponse};
+
+/// The processing of xml files by ERiC.
+pub trait EricBackend {
+    /// Validate a xml file; optionally, print a confirmation to `pdf_path`.
+    fn validate(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error>;
+
+    /// Send a xml file to the tax authorities signed with the given
+    /// credentials; optionally, print a confirmation to `pdf_path`.
+    fn send(
+        &self,
+        xml: String,
+        tax_type: &str,
+        tax_version: &str,
+        credentials: &Credentials,
+        pdf_path: Option<&str>,
+    ) -> Result<EricResponse, anyhow::Error>;
+
+    /// Get the error text for an error code.
+    fn get_error_text(&self, error_code: i32) -> Result<String, anyhow::Error>;
+}
@@ -0,0 +1,55 @@
+use std::{env, fmt, path::PathBuf};
+
+/// The environment variable of the certificate path used by default.
+pub const CERTIFICATE_PATH: &str = "CERTIFICATE_PATH";
+/// The environment variable of the PIN used by default.
+pub const CERTIFICATE_PASSWORD: &str = "CERTIFICATE_PASSWORD";
+
+/// The certificate and the PIN to sign the transmission.
+#[derive(Clone)]
+pub struct Credentials {
+    pub certificate_path: PathBuf,
+    pin: String,
+}
+
+impl Credentials {
+    pub fn new(certificate_path: impl Into<PathBuf>, pin: impl Into<String>) -> Self {
+        Self {
+            certificate_path: certificate_path.into(),
+            pin: pin.into(),
+        }
+    }
+
+    pub fn pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// Set the environment variables [`CERTIFICATE_PATH`] and
+    /// [`CERTIFICATE_PASSWORD`] to the credentials.
+    ///
+    /// eric-sdk only reads the certificate and the PIN from the environment.
+    /// Modifying the environment while another thread reads it, e.g. ERiC
+    /// through `getenv`, is undefined behavior, so the credentials are
+    /// exported once on the main thread before ERiC is started, and they are
+    /// not removed while ERiC may still run.
+    pub fn export(&self) {
+        env::set_var(CERTIFICATE_PATH, &self.certificate_path);
+        env::set_var(CERTIFICATE_PASSWORD, &self.pin);
+    }
+
+    /// Check if the credentials are exported to the environment.
+    pub fn is_exported(&self) -> bool {
+        env::var_os(CERTIFICATE_PATH).as_deref() == Some(self.certificate_path.as_os_str())
+            && env::var(CERTIFICATE_PASSWORD).as_deref() == Ok(self.pin.as_str())
+    }
+}
+
+impl fmt::Debug for Credentials {
+    /// The PIN is redacted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("certificate_path", &self.certificate_path)
+            .field("pin", &"***")
+            .finish()
+    }
+}
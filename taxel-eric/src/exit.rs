@@ -0,0 +1,36 @@
+//! The exit codes of the processing of a filing.
+//!
+//! Besides `0` for success and `1` for any other error, the following exit
+//! codes are used.
+
+use std::fmt;
+
+/// The validation of the filing reported errors.
+pub const VALIDATION_ERROR: i32 = 2;
+/// The transmission of the filing failed or was rejected by the server of the
+/// tax authorities.
+pub const TRANSMISSION_ERROR: i32 = 3;
+
+/// An error which terminates the processing with the given exit code.
+#[derive(Debug, PartialEq)]
+pub struct ExitError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl ExitError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ExitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ExitError {}
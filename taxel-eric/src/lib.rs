@@ -0,0 +1,19 @@
+//! The validation and transmission of xml files with the ELSTER Rich Client
+//! (ERiC).
+//!
+//! ERiC is only linked with the feature `eric`, which requires the ERiC
+//! library. Without the feature, [`Eric::new`] returns an error, and the
+//! [`MockEric`] backend can be used to run the processing without ERiC, e.g.
+//! in tests.
+
+pub mod archive;
+mod backend;
+mod credentials;
+pub mod exit;
+mod outcome;
+mod response;
+
+pub use backend::{Eric, EricBackend, MockEric, MockRequest, PDF_STUB};
+pub use credentials::{Credentials, CERTIFICATE_PASSWORD, CERTIFICATE_PATH};
+pub use outcome::{report_response, Outcome};
+pub use response::{error_code, EricResponse};
@@ -0,0 +1,109 @@
+//! The outcome of the validation or transmission of a xml file.
+
+use crate::{
+    archive, error_code,
+    exit::{self, ExitError},
+    EricBackend, EricResponse,
+};
+use anyhow::anyhow;
+use std::{fs::File, path::Path};
+use taxel::{Origin, SourceMap, ValidationReport};
+
+/// The report of a processed xml file and whether it passed.
+#[derive(Debug)]
+pub struct Outcome {
+    pub report: ValidationReport,
+    /// The error code of ERiC.
+    pub error_code: i32,
+    pub transfer_ticket: Option<String>,
+    /// An [`ExitError`] if the filing is not plausible or the transmission
+    /// failed.
+    pub result: Result<(), anyhow::Error>,
+}
+
+/// Print the errors and warnings of the response, and optionally write them
+/// to a json file.
+///
+/// Diagnostics of a concept are pointed to the location of its value in the
+/// given source files.
+pub fn report_response<E>(
+    eric: &E,
+    response: &EricResponse,
+    report_file: Option<&Path>,
+    sources: &SourceMap,
+) -> Result<Outcome, anyhow::Error>
+where
+    E: EricBackend,
+{
+    let mut report =
+        ValidationReport::parse(&response.validation_response, &response.server_response)?;
+    report.locate(sources);
+
+    print!("{report}");
+
+    if let Some(report_file) = report_file {
+        println!("Writing report to '{}'", report_file.display());
+        let report_file = File::create(report_file)?;
+        serde_json::to_writer_pretty(report_file, &report)?;
+    }
+
+    let result = check_response(eric, response, &report);
+
+    Ok(Outcome {
+        report,
+        error_code: response.error_code,
+        transfer_ticket: archive::transfer_ticket(&response.server_response),
+        result,
+    })
+}
+
+/// Returns an [`ExitError`] if the filing is not plausible or the transmission
+/// failed.
+fn check_response<E>(
+    eric: &E,
+    response: &EricResponse,
+    report: &ValidationReport,
+) -> Result<(), anyhow::Error>
+where
+    E: EricBackend,
+{
+    let error_count = report.errors().count();
+    let is_rejected = report
+        .errors()
+        .any(|diagnostic| diagnostic.origin == Origin::Server);
+
+    if is_rejected {
+        return Err(ExitError::new(
+            exit::TRANSMISSION_ERROR,
+            format!("Transmission rejected with {error_count} error(s)"),
+        )
+        .into());
+    }
+
+    if error_count > 0 {
+        return Err(ExitError::new(
+            exit::VALIDATION_ERROR,
+            format!("Validation failed with {error_count} error(s)"),
+        )
+        .into());
+    }
+
+    match response.error_code {
+        code if code == error_code::ERIC_OK || code == error_code::ERIC_GLOBAL_HINWEISE => Ok(()),
+        code if code == error_code::ERIC_GLOBAL_PRUEF_FEHLER => Err(ExitError::new(
+            exit::VALIDATION_ERROR,
+            format!("Validation failed: {}", eric.get_error_text(code)?),
+        )
+        .into()),
+        // Error codes of the transmission are in the range 610101200..610102000
+        code if (610101200..610102000).contains(&code) => Err(ExitError::new(
+            exit::TRANSMISSION_ERROR,
+            format!("Transmission failed: {}", eric.get_error_text(code)?),
+        )
+        .into()),
+        code => Err(anyhow!(
+            "Processing failed with error code {code}: {}",
+            eric.get_error_text(code)?
+        )),
+    }
+}
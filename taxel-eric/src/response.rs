@@ -0,0 +1,33 @@
+/// The error codes of ERiC which are handled by taxel, see `eric_fehlercodes.h`
+/// of the ERiC library.
+pub mod error_code {
+    /// The processing was successful.
+    pub const ERIC_OK: i32 = 0;
+    /// The xml file is not plausible.
+    pub const ERIC_GLOBAL_PRUEF_FEHLER: i32 = 610001002;
+    /// The xml file is plausible, but there are hints.
+    pub const ERIC_GLOBAL_HINWEISE: i32 = 610001003;
+    /// The server reported an error in the header of the payload.
+    pub const ERIC_TRANSFER_ERR_XML_NHEADER: i32 = 610101292;
+}
+
+/// The response of ERiC.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EricResponse {
+    /// The error code returned by ERiC.
+    pub error_code: i32,
+    /// The response of the validation of a xml file.
+    pub validation_response: String,
+    /// The response of the server of the tax authorities to a sent xml file.
+    pub server_response: String,
+}
+
+impl EricResponse {
+    pub fn new(error_code: i32, validation_response: String, server_response: String) -> Self {
+        Self {
+            error_code,
+            validation_response,
+            server_response,
+        }
+    }
+}
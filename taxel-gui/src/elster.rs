@@ -0,0 +1,293 @@
+//! The dialogs to send the filing and to show the responses of ERiC.
+
+use anyhow::anyhow;
+use eframe::egui::{self, Color32, Context, Grid, ScrollArea, Window};
+use rfd::FileDialog;
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use taxel_eric::{archive::Submission, Credentials, Outcome, CERTIFICATE_PATH};
+
+/// The file ERiC prints the confirmation to before it is saved.
+const PDF_FILE: &str = "confirmation.pdf";
+
+/// The outcome of a validation or transmission, and the archived submission
+/// of a transmission.
+pub type EricResult = Result<(Outcome, Option<Submission>), anyhow::Error>;
+
+/// The confirmation of a transmission to the tax authorities.
+pub struct SendDialog {
+    certificate_path: Option<PathBuf>,
+    pin: String,
+    is_confirmed: bool,
+}
+
+/// The button clicked in the confirmation.
+pub enum SendAction {
+    Send(Credentials),
+    Cancel,
+}
+
+impl Default for SendDialog {
+    fn default() -> Self {
+        Self {
+            certificate_path: env::var_os(CERTIFICATE_PATH).map(PathBuf::from),
+            pin: String::new(),
+            is_confirmed: false,
+        }
+    }
+}
+
+impl SendDialog {
+    pub fn show(&mut self, ctx: &Context) -> Option<SendAction> {
+        let mut action = None;
+
+        Window::new("Send")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.colored_label(
+                    Color32::RED,
+                    "The filing is sent to the tax authorities. This can't be undone.",
+                );
+                ui.separator();
+
+                Grid::new("send").num_columns(2).show(ui, |ui| {
+                    ui.label("Certificate");
+                    ui.horizontal(|ui| {
+                        match &self.certificate_path {
+                            Some(path) => ui.label(path.display().to_string()),
+                            None => ui.label("-"),
+                        };
+
+                        if ui.button("Choose").clicked() {
+                            if let Some(path) = FileDialog::new()
+                                .add_filter("Certificate", &["pfx"])
+                                .add_filter("All", &["*"])
+                                .pick_file()
+                            {
+                                self.certificate_path = Some(path);
+                            }
+                        }
+                    });
+                    ui.end_row();
+
+                    ui.label("PIN");
+                    ui.add(egui::TextEdit::singleline(&mut self.pin).password(true));
+                    ui.end_row();
+                });
+
+                ui.checkbox(
+                    &mut self.is_confirmed,
+                    "I have checked the filing and want to send it",
+                );
+
+                ui.horizontal(|ui| {
+                    let is_enabled = self.is_confirmed
+                        && self.certificate_path.is_some()
+                        && !self.pin.is_empty();
+
+                    if ui
+                        .add_enabled(is_enabled, egui::Button::new("Send"))
+                        .clicked()
+                    {
+                        if let Some(path) = &self.certificate_path {
+                            let credentials = Credentials::new(path, self.pin.as_str());
+                            action = Some(SendAction::Send(credentials));
+                        }
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        action = Some(SendAction::Cancel);
+                    }
+                });
+            });
+
+        action
+    }
+}
+
+/// A validation or transmission with ERiC which runs on a worker thread, so
+/// that the UI stays responsive.
+pub struct EricTask {
+    title: &'static str,
+    /// The directory of this run which ERiC prints the confirmation to.
+    run_dir: PathBuf,
+    receiver: Receiver<EricResult>,
+}
+
+impl EricTask {
+    /// Start `run` on a worker thread with the path of the confirmation; the
+    /// UI is repainted when it is finished.
+    pub fn spawn<F>(ctx: &Context, title: &'static str, run: F) -> Result<Self, anyhow::Error>
+    where
+        F: FnOnce(&Path) -> EricResult + Send + 'static,
+    {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        let run_dir = env::temp_dir().join(format!("taxel-gui-{}-{nanos}", process::id()));
+        fs::create_dir_all(&run_dir)?;
+
+        let pdf_path = run_dir.join(PDF_FILE);
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+
+        thread::spawn(move || {
+            let _ = sender.send(run(&pdf_path));
+            ctx.request_repaint();
+        });
+
+        Ok(Self {
+            title,
+            run_dir,
+            receiver,
+        })
+    }
+
+    pub fn title(&self) -> &'static str {
+        self.title
+    }
+
+    /// The dialog with the responses, or the error, once ERiC is finished.
+    pub fn poll(&self) -> Option<Result<ResponseDialog, anyhow::Error>> {
+        let result = match self.receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err(anyhow!("ERiC terminated unexpectedly")),
+        };
+
+        match result {
+            Ok((outcome, submission)) => Some(Ok(ResponseDialog {
+                title: self.title,
+                outcome,
+                submission,
+                run_dir: self.run_dir.clone(),
+                status_message: None,
+            })),
+            Err(err) => {
+                let _ = fs::remove_dir_all(&self.run_dir);
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// The parsed validation and server responses of ERiC.
+pub struct ResponseDialog {
+    title: &'static str,
+    outcome: Outcome,
+    /// The archived submission of a transmission.
+    submission: Option<Submission>,
+    /// The directory of the run with the confirmation printed by ERiC, if
+    /// any; it is removed when the dialog is dropped.
+    run_dir: PathBuf,
+    status_message: Option<String>,
+}
+
+impl ResponseDialog {
+    /// The confirmation printed by ERiC, if any.
+    fn pdf_path(&self) -> Option<PathBuf> {
+        Some(self.run_dir.join(PDF_FILE)).filter(|pdf_path| pdf_path.is_file())
+    }
+
+    /// Show the dialog; returns `false` if it was closed.
+    pub fn show(&mut self, ctx: &Context) -> bool {
+        let mut is_open = true;
+
+        Window::new(self.title)
+            .collapsible(false)
+            .default_width(700.0)
+            .show(ctx, |ui| {
+                match &self.outcome.result {
+                    Ok(()) => ui.colored_label(Color32::DARK_GREEN, "Processed successfully"),
+                    Err(err) => ui.colored_label(Color32::RED, err.to_string()),
+                };
+
+                ui.label(format!("Response code: {}", self.outcome.error_code));
+
+                if let Some(transfer_ticket) = &self.outcome.transfer_ticket {
+                    ui.label(format!("Transfer ticket: {transfer_ticket}"));
+                }
+
+                if let Some(submission) = &self.submission {
+                    ui.label(format!("Archived as submission '{}'", submission.id));
+                }
+
+                ui.separator();
+
+                if self.outcome.report.diagnostics.is_empty() {
+                    ui.label("No errors or warnings");
+                } else {
+                    ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                        Grid::new("diagnostics").striped(true).show(ui, |ui| {
+                            ui.strong("Severity");
+                            ui.strong("Origin");
+                            ui.strong("Rule");
+                            ui.strong("Concept");
+                            ui.strong("Message");
+                            ui.end_row();
+
+                            for diagnostic in &self.outcome.report.diagnostics {
+                                ui.label(diagnostic.severity.as_str());
+                                ui.label(format!("{:?}", diagnostic.origin));
+                                ui.label(diagnostic.rule_id.as_deref().unwrap_or("-"));
+                                ui.label(diagnostic.concept.as_deref().unwrap_or("-"));
+                                ui.label(&diagnostic.message);
+                                ui.end_row();
+                            }
+                        });
+                    });
+                }
+
+                ui.separator();
+
+                if let Some(status) = &self.status_message {
+                    ui.label(status);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.pdf_path().is_some(), egui::Button::new("Save PDF"))
+                        .clicked()
+                    {
+                        self.save_pdf();
+                    }
+
+                    if ui.button("Close").clicked() {
+                        is_open = false;
+                    }
+                });
+            });
+
+        is_open
+    }
+
+    fn save_pdf(&mut self) {
+        let Some(pdf_path) = self.pdf_path() else {
+            return;
+        };
+
+        let Some(path) = FileDialog::new()
+            .add_filter("PDF", &["pdf"])
+            .set_file_name("ebilanz.pdf")
+            .save_file()
+        else {
+            return;
+        };
+
+        self.status_message = Some(match fs::copy(pdf_path, &path) {
+            Ok(_) => format!("Saved PDF to '{}'", path.display()),
+            Err(err) => format!("Failed to save PDF: {err}"),
+        });
+    }
+}
+
+impl Drop for ResponseDialog {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.run_dir);
+    }
+}
@@ -0,0 +1,90 @@
+//! The preview of the values of a csv or ods file before they are applied to
+//! a template.
+
+use eframe::egui::{self, CollapsingHeader, Context, Grid, ScrollArea, Ui, Window};
+use std::path::PathBuf;
+use taxel::XbrlElement;
+use taxel_gui::ImportRow;
+
+/// The imported file and the template its values are applied to.
+pub struct Import {
+    pub path: PathBuf,
+    /// The template file, or `None` if the values are applied to the current
+    /// document.
+    pub template_path: Option<PathBuf>,
+    pub template: XbrlElement,
+    pub rows: Vec<ImportRow>,
+}
+
+/// The button clicked in the preview.
+pub enum ImportAction {
+    Apply,
+    Cancel,
+}
+
+impl Import {
+    pub fn show(&self, ctx: &Context) -> Option<ImportAction> {
+        let mut action = None;
+        let matched = self.rows.iter().filter(|row| row.matched).count();
+
+        Window::new("Import values")
+            .collapsible(false)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                ui.label(format!("File: {}", self.path.display()));
+                ui.label(match &self.template_path {
+                    Some(template_path) => format!("Template: {}", template_path.display()),
+                    None => String::from("Template: current document"),
+                });
+                ui.label(format!(
+                    "{matched} of {} keys match the template",
+                    self.rows.len()
+                ));
+                ui.separator();
+
+                CollapsingHeader::new(format!("Matched keys ({matched})"))
+                    .default_open(true)
+                    .show(ui, |ui| draw_rows(ui, "matched_keys", &self.rows, true));
+
+                CollapsingHeader::new(format!("Unmatched keys ({})", self.rows.len() - matched))
+                    .default_open(matched < self.rows.len())
+                    .show(ui, |ui| draw_rows(ui, "unmatched_keys", &self.rows, false));
+
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(matched > 0, egui::Button::new("Apply"))
+                        .clicked()
+                    {
+                        action = Some(ImportAction::Apply);
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        action = Some(ImportAction::Cancel);
+                    }
+                });
+            });
+
+        action
+    }
+}
+
+fn draw_rows(ui: &mut Ui, id: &str, rows: &[ImportRow], matched: bool) {
+    ScrollArea::vertical()
+        .id_salt(id)
+        .max_height(300.0)
+        .show(ui, |ui| {
+            Grid::new(id).striped(true).show(ui, |ui| {
+                ui.strong("Key");
+                ui.strong("Value");
+                ui.end_row();
+
+                for row in rows.iter().filter(|row| row.matched == matched) {
+                    ui.label(&row.key);
+                    ui.label(row.value.as_deref().unwrap_or("-"));
+                    ui.end_row();
+                }
+            });
+        });
+}
@@ -1,94 +1,1814 @@
-use anyhow::Result;
-use quick_xml::{
-    events::{BytesStart, Event},
-    Reader,
+use anyhow::{anyhow, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
 };
+use taxel::{
+    CalculationMismatch, CsvReaderBuilder, Decimal, DecimalsPolicy, Fact, Filing, ItemType, Reader,
+    Rounding, SourceMap, Tag, Tags, TaxType, TaxonomySchema, TaxonomyVersion, Trim, Writer,
+    XbrlElement,
+};
+use taxel_eric::{
+    archive::{self, Archive, Submission, Transmission},
+    report_response, Credentials, EricBackend, Outcome,
+};
+
+/// The ELSTER template of the eBilanz, see `TaxType::Bilanz.template()`.
+const EBILANZ_TEMPLATE: &str = include_str!("../../templates/elster_v11/taxonomy_v6.5/ebilanz.xml");
 
 #[derive(Debug, Clone)]
 pub struct TableRow {
+    /// The position of the fact in the document.
+    pub index: usize,
     pub concept: String,
     // Human-readable label
     pub label: Option<String>,
     pub context: String,
+    /// The explicit members of the context, i.e. pairs of dimension and
+    /// member.
+    pub dimensions: Vec<(String, String)>,
     pub unit: Option<String>,
+    pub decimals: Option<String>,
+    /// Whether the fact is marked as `xsi:nil="true"`.
+    pub nil: bool,
     pub value: String,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct XbrlTable {
     pub rows: Vec<TableRow>,
 }
 
-// TODO: replace by XBRL parser
-/// Read XBRL in table format.
-pub fn read_xbrl(xml: &str) -> Result<XbrlTable> {
-    let mut reader = Reader::from_str(xml);
-    reader.trim_text(true);
+impl XbrlTable {
+    /// Build the table from the facts of the document.
+    pub fn new(element: &XbrlElement) -> Self {
+        let dimensions = element.context_dimensions();
+        let rows = element
+            .facts()
+            .into_iter()
+            .enumerate()
+            .map(|(index, fact)| TableRow {
+                index,
+                dimensions: dimensions.get(&fact.context).cloned().unwrap_or_default(),
+                concept: fact.concept,
+                label: None,
+                context: fact.context,
+                unit: fact.unit,
+                decimals: fact.decimals,
+                nil: fact.nil,
+                value: fact.value.filter(|_| !fact.nil).unwrap_or_default(),
+            })
+            .collect();
 
-    let mut buf = Vec::new();
-    let mut table = XbrlTable::default();
-    let mut inside_xbrl = false;
+        Self { rows }
+    }
 
-    loop {
-        match reader.read_event_into(&mut buf)? {
-            Event::Start(event) => {
-                let name = event.name();
+    /// Set the German labels of the concepts from the taxonomy.
+    pub fn set_labels(&mut self, schema: &TaxonomySchema) {
+        for row in &mut self.rows {
+            row.label = schema.label(&row.concept).map(String::from);
+        }
+    }
+}
 
-                // detect start of XBRL instance
-                if name.as_ref().ends_with(b"xbrl") {
-                    inside_xbrl = true;
-                }
+/// A change of the value of a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    /// The position of the row in the table.
+    pub row: usize,
+    pub old: String,
+    pub new: String,
+}
 
-                if inside_xbrl {
-                    parse_xbrl_fact(&mut reader, &event, &mut table)?;
-                }
-            }
+/// The undo and redo stacks of the edited values.
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Edit>,
+    redo: Vec<Edit>,
+    /// The next edit of the same row is merged into the last step.
+    is_merging: bool,
+}
+
+impl History {
+    /// Record an edit; the redo stack is cleared.
+    ///
+    /// Consecutive edits of the same row, e.g. typing a value, are merged into
+    /// one step until the step is sealed.
+    pub fn record(&mut self, edit: Edit) {
+        self.redo.clear();
 
-            Event::End(event) => {
-                if event.name().as_ref().ends_with(b"xbrl") {
-                    break;
+        if let Some(last) = self.undo.last_mut().filter(|_| self.is_merging) {
+            if last.row == edit.row && last.new == edit.old {
+                last.new = edit.new;
+
+                if last.old == last.new {
+                    self.undo.pop();
+                    self.is_merging = false;
                 }
+
+                return;
             }
+        }
+
+        self.undo.push(edit);
+        self.is_merging = true;
+    }
 
-            Event::Eof => break,
-            _ => {}
+    /// End the last step, e.g. when the edited value loses the focus; the
+    /// next edit of the same row is a new step.
+    pub fn seal(&mut self) {
+        self.is_merging = false;
+    }
+
+    /// Revert the last edit; returns the position of the changed row.
+    pub fn undo(&mut self, table: &mut XbrlTable) -> Option<usize> {
+        let edit = self.undo.pop()?;
+        let row = edit.row;
+        self.is_merging = false;
+
+        if let Some(table_row) = table.rows.get_mut(row) {
+            table_row.value = edit.old.clone();
+        }
+
+        self.redo.push(edit);
+
+        Some(row)
+    }
+
+    /// Repeat the last reverted edit; returns the position of the changed
+    /// row.
+    pub fn redo(&mut self, table: &mut XbrlTable) -> Option<usize> {
+        let edit = self.redo.pop()?;
+        let row = edit.row;
+        self.is_merging = false;
+
+        if let Some(table_row) = table.rows.get_mut(row) {
+            table_row.value = edit.new.clone();
         }
 
-        buf.clear();
+        self.undo.push(edit);
+
+        Some(row)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
     }
 
-    Ok(table)
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.is_merging = false;
+    }
 }
 
-fn parse_xbrl_fact(
-    reader: &mut Reader<&[u8]>,
-    e: &BytesStart,
-    table: &mut XbrlTable,
-) -> Result<()> {
-    let mut context = None;
-    let mut unit = None;
+/// A value which differs from the value when the document was loaded or
+/// saved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    /// The position of the row in the table.
+    pub row: usize,
+    pub original: String,
+    pub edited: String,
+}
+
+/// Compare the values of the table with the original table of the same
+/// document; leading and trailing whitespace is ignored.
+pub fn value_changes(original: &XbrlTable, table: &XbrlTable) -> Vec<ValueChange> {
+    original
+        .rows
+        .iter()
+        .zip(&table.rows)
+        .enumerate()
+        .filter(|(_, (original, edited))| original.value.trim() != edited.value.trim())
+        .map(|(row, (original, edited))| ValueChange {
+            row,
+            original: original.value.clone(),
+            edited: edited.value.clone(),
+        })
+        .collect()
+}
+
+/// The search and the toggles which filter the rows of the table.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RowFilter {
+    /// Case-insensitive search in the concept, label, context and value.
+    pub search: String,
+    pub only_filled: bool,
+    pub only_nil: bool,
+    /// Only concepts which are a `Mussfeld` or `Summenmussfeld`.
+    pub only_mandatory: bool,
+    /// Only rows with issues of the local checks.
+    pub only_errors: bool,
+}
+
+impl RowFilter {
+    /// Check if the filter shows all rows.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check if the row is shown; `has_issues` tells if the local checks
+    /// found issues in the row.
+    pub fn matches(
+        &self,
+        row: &TableRow,
+        schema: Option<&TaxonomySchema>,
+        has_issues: bool,
+    ) -> bool {
+        let is_empty = row.value.trim().is_empty();
 
-    for attr in e.attributes().flatten() {
-        match attr.key.as_ref() {
-            b"contextRef" => context = Some(attr.unescape_value()?.to_string()),
-            b"unitRef" => unit = Some(attr.unescape_value()?.to_string()),
-            _ => {}
+        if self.only_filled && is_empty
+            || self.only_nil && !is_empty
+            || self.only_errors && !has_issues
+        {
+            return false;
         }
+
+        if self.only_mandatory && !schema.is_some_and(|schema| schema.is_mandatory(&row.concept)) {
+            return false;
+        }
+
+        let search = self.search.trim().to_lowercase();
+
+        search.is_empty()
+            || [
+                Some(row.concept.as_str()),
+                row.label.as_deref(),
+                Some(row.context.as_str()),
+                Some(row.value.as_str()),
+            ]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(&search))
     }
+}
+
+/// A concept of the presentation hierarchy for which the document contains
+/// facts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    pub concept: String,
+    /// The German label or the concept if the label is unknown.
+    pub label: String,
+    /// The reported value of the concept in the current year without
+    /// dimensions, or the weighted sum of its summands by the calculation
+    /// linkbase if the concept isn't reported.
+    pub subtotal: Option<Decimal>,
+    pub children: Vec<TreeNode>,
+}
 
-    if let Some(ctx) = context {
-        let concept = String::from_utf8_lossy(e.name().as_ref()).to_string();
+/// Build the presentation hierarchy of the facts in the table, e.g. Bilanz →
+/// Aktiva → Anlagevermögen.
+///
+/// Branches without facts are left out. Concepts which are roots in several
+/// roles are shown once.
+pub fn presentation_tree(schema: &TaxonomySchema, table: &XbrlTable) -> Vec<TreeNode> {
+    let concepts = table
+        .rows
+        .iter()
+        .map(|row| row.concept.as_str())
+        .collect::<HashSet<_>>();
+    let mut values = HashMap::new();
 
-        if let Event::Text(t) = reader.read_event()? {
-            table.rows.push(TableRow {
-                concept,
-                label: None,
-                context: ctx,
-                unit,
-                value: t.unescape()?.to_string(),
+    for row in &table.rows {
+        if row.nil || !row.dimensions.is_empty() || period(&row.context) != PERIODS[0] {
+            continue;
+        }
+
+        if let Ok(value) = row.value.parse::<Decimal>() {
+            values.entry(row.concept.as_str()).or_insert(value);
+        }
+    }
+
+    let mut roots = Vec::<TreeNode>::new();
+
+    for role in schema.presentation.roles() {
+        for root in schema.presentation.roots(role) {
+            if roots.iter().any(|node| node.concept == root) {
+                continue;
+            }
+
+            let mut path = vec![];
+
+            if let Some(node) = tree_node(schema, role, root, &concepts, &values, &mut path) {
+                roots.push(node);
+            }
+        }
+    }
+
+    roots
+}
+
+fn tree_node<'a>(
+    schema: &'a TaxonomySchema,
+    role: &str,
+    concept: &'a str,
+    concepts: &HashSet<&str>,
+    values: &HashMap<&str, Decimal>,
+    path: &mut Vec<&'a str>,
+) -> Option<TreeNode> {
+    // Guard against cycles in inconsistent linkbases.
+    if path.contains(&concept) {
+        return None;
+    }
+
+    path.push(concept);
+    let children = schema
+        .presentation
+        .children(role, concept)
+        .into_iter()
+        .filter_map(|child| tree_node(schema, role, child, concepts, values, path))
+        .collect::<Vec<_>>();
+    path.pop();
+
+    if children.is_empty() && !concepts.contains(concept) {
+        return None;
+    }
+
+    let subtotal = calculated_value(
+        schema,
+        role,
+        concept,
+        &|concept| values.get(concept).copied(),
+        &mut vec![],
+    );
+
+    Some(TreeNode {
+        concept: concept.to_owned(),
+        label: schema.label(concept).unwrap_or(concept).to_owned(),
+        subtotal,
+        children,
+    })
+}
+
+/// The reported value of the concept, or the weighted sum of its summands by
+/// the calculation linkbase of the role if the concept isn't reported.
+///
+/// Summands which aren't reported are calculated recursively. Returns `None`
+/// if the concept isn't reported and has no summation-item relationship.
+fn calculated_value(
+    schema: &TaxonomySchema,
+    role: &str,
+    concept: &str,
+    reported: &dyn Fn(&str) -> Option<Decimal>,
+    path: &mut Vec<String>,
+) -> Option<Decimal> {
+    if let Some(value) = reported(concept) {
+        return Some(value);
+    }
+
+    // Guard against cycles in inconsistent linkbases.
+    if path.iter().any(|other| other == concept) {
+        return None;
+    }
+
+    path.push(concept.to_owned());
+    let sum = schema.calculations.sum(role, concept, |summand| {
+        calculated_value(schema, role, summand, reported, path)
+    });
+    path.pop();
+
+    sum
+}
+
+/// A problem with the value of a fact found by the local checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// The position of the row in the table.
+    pub row: usize,
+    pub message: String,
+}
+
+/// Check the values of the table without ERiC: the number format, the item
+/// type of the concepts, missing values of a `Mussfeld` and the calculations.
+///
+/// Without the taxonomy only the number format of facts with a unit can be
+/// checked. The issues are ordered by row.
+pub fn check_table(table: &XbrlTable, schema: Option<&TaxonomySchema>) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    for (position, row) in table.rows.iter().enumerate() {
+        let value = row.value.trim();
+        let item_type = schema
+            .and_then(|schema| schema.concept_types.get(&row.concept))
+            .unwrap_or_else(|| ItemType::from_unit(row.unit.as_deref()));
+
+        if value.is_empty() {
+            if schema.is_some_and(|schema| schema.is_mandatory(&row.concept)) {
+                issues.push(Issue {
+                    row: position,
+                    message: String::from("Missing value of a Mussfeld"),
+                });
+            }
+
+            continue;
+        }
+
+        if let Some(message) = check_value(value, item_type) {
+            issues.push(Issue {
+                row: position,
+                message,
             });
         }
     }
 
-    Ok(())
+    if let Some(schema) = schema {
+        let facts = table
+            .rows
+            .iter()
+            .map(|row| {
+                let value = Some(row.value.trim()).filter(|value| !value.is_empty());
+
+                Fact::new(
+                    &row.concept,
+                    &row.context,
+                    row.unit.clone(),
+                    row.decimals.clone(),
+                    value.map(String::from),
+                    value.is_none(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for mismatch in schema.calculations.check(&facts, Rounding::default()) {
+            let position = table
+                .rows
+                .iter()
+                .position(|row| row.concept == mismatch.concept && row.context == mismatch.context);
+
+            if let Some(position) = position {
+                issues.push(Issue {
+                    row: position,
+                    message: format!(
+                        "Sum of items is {}, but total is {}",
+                        mismatch.expected, mismatch.actual
+                    ),
+                });
+            }
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.row);
+    issues
+}
+
+/// The messages of the issues by row, one per line, e.g. for the hover text
+/// of a value.
+pub fn issue_messages(issues: &[Issue]) -> HashMap<usize, String> {
+    let mut messages = HashMap::<usize, String>::new();
+
+    for issue in issues {
+        let message = messages.entry(issue.row).or_default();
+
+        if !message.is_empty() {
+            message.push('\n');
+        }
+
+        message.push_str(&issue.message);
+    }
+
+    messages
+}
+
+/// Check a non-empty value against the item type of its concept.
+fn check_value(value: &str, item_type: ItemType) -> Option<String> {
+    if !item_type.is_numeric() {
+        return None;
+    }
+
+    let Ok(number) = value.parse::<Decimal>() else {
+        let hint = if value.contains(',') {
+            ", use a point as decimal separator and no thousands separators"
+        } else {
+            ""
+        };
+
+        return Some(format!("Invalid number '{value}'{hint}"));
+    };
+
+    if item_type == ItemType::Integer && number.round(0, Rounding::HalfEven) != number {
+        return Some(format!("Expected an integer instead of '{value}'"));
+    }
+
+    None
+}
+
+/// The periods of the contexts in the order of the columns of a statement.
+const PERIODS: [&str; 2] = ["AKTJAHR", "VORJAHR"];
+
+/// The period of the context id, e.g. `AKTJAHR` of `I-AKTJAHR`.
+fn period(context: &str) -> &str {
+    context
+        .rsplit_once('-')
+        .map(|(_, period)| period)
+        .unwrap_or(context)
+}
+
+/// A column of a statement, i.e. a period and the explicit members of the
+/// contexts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatementColumn {
+    /// The period of the context id, e.g. `AKTJAHR` of `I-AKTJAHR`.
+    pub period: String,
+    pub dimensions: Vec<(String, String)>,
+}
+
+impl StatementColumn {
+    fn new(row: &TableRow) -> Self {
+        Self {
+            period: period(&row.context).to_owned(),
+            dimensions: row.dimensions.clone(),
+        }
+    }
+
+    /// The period and the members, e.g. `AKTJAHR` or `AKTJAHR dim_taxBal`.
+    pub fn title(&self) -> String {
+        let mut title = self.period.clone();
+
+        for (_, member) in &self.dimensions {
+            let member = member
+                .split_once(':')
+                .map(|(_, name)| name)
+                .unwrap_or(member);
+            title.push(' ');
+            title.push_str(member);
+        }
+
+        title
+    }
+
+    /// Order the current year first, then the previous year, and the columns
+    /// without dimensions before the members.
+    fn sort_key(&self) -> (usize, &str, &[(String, String)]) {
+        let period = PERIODS
+            .iter()
+            .position(|period| *period == self.period)
+            .unwrap_or(PERIODS.len());
+
+        (period, &self.period, &self.dimensions)
+    }
+}
+
+/// A cell of a statement.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementCell {
+    Empty,
+    /// The position of the fact in the table.
+    Fact(usize),
+    /// The weighted sum of the summands of a concept which isn't reported by
+    /// the calculation linkbase.
+    Total(Decimal),
+}
+
+/// A concept of a statement with one cell per column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatementRow {
+    pub concept: String,
+    /// The German label or the concept if the label is unknown.
+    pub label: String,
+    /// The level in the presentation hierarchy.
+    pub depth: usize,
+    pub cells: Vec<StatementCell>,
+}
+
+/// The facts of a presentation role pivoted like a printed statement: one
+/// row per concept in presentation order and one column per period and
+/// dimension member.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StatementView {
+    pub columns: Vec<StatementColumn>,
+    pub rows: Vec<StatementRow>,
+}
+
+impl StatementView {
+    /// Whether the fact at the position in the table is part of the
+    /// statement.
+    pub fn contains(&self, position: usize) -> bool {
+        self.rows
+            .iter()
+            .flat_map(|row| &row.cells)
+            .any(|cell| *cell == StatementCell::Fact(position))
+    }
+}
+
+/// The presentation roles with facts in the table, e.g. the balance sheet and
+/// the income statement.
+pub fn statement_roles<'a>(schema: &'a TaxonomySchema, table: &XbrlTable) -> Vec<&'a str> {
+    let concepts = table
+        .rows
+        .iter()
+        .map(|row| row.concept.as_str())
+        .collect::<HashSet<_>>();
+
+    schema
+        .presentation
+        .roles()
+        .filter(|role| {
+            let mut role_concepts = HashSet::new();
+
+            for root in schema.presentation.roots(role) {
+                collect_concepts(schema, role, root, &mut role_concepts);
+            }
+
+            role_concepts
+                .iter()
+                .any(|concept| concepts.contains(concept))
+        })
+        .collect()
+}
+
+/// Pivot the facts of the presentation role.
+///
+/// Concepts without facts are left out unless a concept below them has facts;
+/// their cells show the weighted sum of their summands by the calculation
+/// linkbase, and are empty without a summation-item relationship.
+pub fn statement_view(schema: &TaxonomySchema, table: &XbrlTable, role: &str) -> StatementView {
+    let mut concepts = HashSet::new();
+
+    for root in schema.presentation.roots(role) {
+        collect_concepts(schema, role, root, &mut concepts);
+    }
+
+    let mut columns = Vec::<StatementColumn>::new();
+    let mut facts = HashMap::new();
+
+    for (position, row) in table.rows.iter().enumerate() {
+        if !concepts.contains(row.concept.as_str()) {
+            continue;
+        }
+
+        let column = StatementColumn::new(row);
+        let index = match columns.iter().position(|other| *other == column) {
+            Some(index) => index,
+            None => {
+                columns.push(column);
+                columns.len() - 1
+            }
+        };
+
+        facts
+            .entry((row.concept.as_str(), index))
+            .or_insert(position);
+    }
+
+    // Sort the columns and map the facts to the sorted columns.
+    let mut order = (0..columns.len()).collect::<Vec<_>>();
+    order.sort_by(|lhs, rhs| columns[*lhs].sort_key().cmp(&columns[*rhs].sort_key()));
+    let facts = facts
+        .into_iter()
+        .map(|((concept, index), position)| {
+            let sorted = order
+                .iter()
+                .position(|other| *other == index)
+                .unwrap_or(index);
+            ((concept, sorted), position)
+        })
+        .collect::<HashMap<_, _>>();
+    let columns = order
+        .into_iter()
+        .map(|index| columns[index].clone())
+        .collect::<Vec<_>>();
+
+    let mut builder = StatementBuilder {
+        schema,
+        table,
+        role,
+        facts,
+        columns: columns.len(),
+        path: vec![],
+        rows: vec![],
+    };
+
+    for root in schema.presentation.roots(role) {
+        builder.visit(root, 0);
+    }
+
+    StatementView {
+        columns,
+        rows: builder.rows,
+    }
+}
+
+fn collect_concepts<'a>(
+    schema: &'a TaxonomySchema,
+    role: &str,
+    concept: &'a str,
+    concepts: &mut HashSet<&'a str>,
+) {
+    // Guard against cycles in inconsistent linkbases.
+    if !concepts.insert(concept) {
+        return;
+    }
+
+    for child in schema.presentation.children(role, concept) {
+        collect_concepts(schema, role, child, concepts);
+    }
+}
+
+struct StatementBuilder<'a> {
+    schema: &'a TaxonomySchema,
+    table: &'a XbrlTable,
+    role: &'a str,
+    /// The positions of the facts by concept and column.
+    facts: HashMap<(&'a str, usize), usize>,
+    columns: usize,
+    path: Vec<&'a str>,
+    rows: Vec<StatementRow>,
+}
+
+impl<'a> StatementBuilder<'a> {
+    /// Add the rows of the concept and its children in presentation order;
+    /// returns `false` if neither the concept nor its children have facts.
+    fn visit(&mut self, concept: &'a str, depth: usize) -> bool {
+        if self.path.contains(&concept) {
+            return false;
+        }
+
+        let index = self.rows.len();
+        self.rows.push(StatementRow {
+            concept: concept.to_owned(),
+            label: self.schema.label(concept).unwrap_or(concept).to_owned(),
+            depth,
+            cells: vec![],
+        });
+
+        self.path.push(concept);
+        let mut has_children = false;
+
+        for child in self.schema.presentation.children(self.role, concept) {
+            has_children |= self.visit(child, depth + 1);
+        }
+
+        self.path.pop();
+
+        let has_facts = (0..self.columns).any(|column| self.facts.contains_key(&(concept, column)));
+
+        if !has_children && !has_facts {
+            self.rows.pop();
+            return false;
+        }
+
+        let cells = (0..self.columns)
+            .map(|column| match self.facts.get(&(concept, column)) {
+                Some(position) => StatementCell::Fact(*position),
+                None => calculated_value(
+                    self.schema,
+                    self.role,
+                    concept,
+                    &|concept| self.value(concept, column),
+                    &mut vec![],
+                )
+                .map_or(StatementCell::Empty, StatementCell::Total),
+            })
+            .collect();
+
+        self.rows[index].cells = cells;
+
+        true
+    }
+
+    /// The numeric value of the fact of the concept in the column.
+    fn value(&self, concept: &str, column: usize) -> Option<Decimal> {
+        let position = self.facts.get(&(concept, column))?;
+
+        self.table.rows[*position].value.trim().parse().ok()
+    }
+}
+
+/// Parse the xml file with the XBRL parser of taxel.
+pub fn read_xbrl(xml: &str) -> Result<XbrlElement> {
+    let mut reader = Reader::from_str(xml);
+    reader.trim_text(true);
+
+    XbrlElement::parse(&mut reader)
+}
+
+/// Create a new filing with empty facts for the selected statements like
+/// `taxel generate`.
+///
+/// Returns the document and the taxonomy discovered in the taxonomy
+/// directory.
+pub fn new_filing(filing: &Filing, taxonomy_dir: &Path) -> Result<(XbrlElement, TaxonomySchema)> {
+    let schema = TaxonomySchema::discover(taxonomy_dir, &filing.version.shells())?;
+
+    if schema.concept_types.is_empty() {
+        return Err(anyhow!(
+            "Missing taxonomy {} ({}) in directory '{}'",
+            filing.version.version,
+            filing.version.date,
+            taxonomy_dir.display()
+        ));
+    }
+
+    let template = read_xbrl(EBILANZ_TEMPLATE)?;
+    let document = filing.generate(template, &schema)?;
+
+    Ok((document, schema))
+}
+
+/// A row of an imported csv or ods file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportRow {
+    /// The `ebilanz_key`, e.g. `de-gaap-ci:bs.ass`.
+    pub key: String,
+    pub value: Option<String>,
+    /// Whether the template contains an element with the key.
+    pub matched: bool,
+}
+
+/// Read the `ebilanz_key` and `ebilanz_value` columns of a csv or ods file
+/// like `taxel generate`.
+///
+/// The rows are matched with the elements of the template and sorted by key.
+pub fn read_import(path: &Path, template: &XbrlElement) -> Result<Vec<ImportRow>> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    let tags = match extension.as_deref() {
+        Some("csv") => {
+            let mut reader = CsvReaderBuilder::new()
+                .delimiter(b',')
+                .has_headers(true)
+                .trim(Trim::All)
+                .from_path(path)?;
+            taxel::read_tags(Some(&mut reader))?
+        }
+        Some("ods") => taxel::read_tags_ods(path)?,
+        _ => {
+            return Err(anyhow!(
+                "Unsupported file '{}'; expected a csv or ods file",
+                path.display()
+            ))
+        }
+    };
+
+    Ok(match_import(template, tags.into_vec()))
+}
+
+/// Match the keys of the tags with the elements of the template.
+pub fn match_import(template: &XbrlElement, tags: Vec<Tag>) -> Vec<ImportRow> {
+    let names = template.element_names();
+
+    tags.into_iter()
+        .map(|tag| ImportRow {
+            matched: names.contains(tag.name.as_str()),
+            key: tag.name,
+            value: tag.value,
+        })
+        .collect()
+}
+
+/// Set the values of the matched rows in the template like `taxel generate`,
+/// i.e. the transfer header is set as well.
+///
+/// Returns the totals which don't add up.
+pub fn apply_template_import(
+    template: &mut XbrlElement,
+    rows: &[ImportRow],
+    schema: &TaxonomySchema,
+) -> Vec<CalculationMismatch> {
+    let (mismatches, _) = template.generate_values(
+        import_tags(rows),
+        TaxType::Bilanz,
+        schema,
+        Rounding::default(),
+    );
+
+    mismatches
+}
+
+/// Set the values of the matched rows in an existing document.
+///
+/// Unlike [`apply_template_import`], only the values and their `decimals`
+/// and `unitRef` attributes are set; the transfer header is kept. The values
+/// are imported into the current year; facts of prior years, e.g. of a
+/// rolled-over filing, are kept. Returns the totals which don't add up in
+/// each context.
+pub fn apply_import(
+    document: &mut XbrlElement,
+    rows: &[ImportRow],
+    schema: &TaxonomySchema,
+) -> Vec<CalculationMismatch> {
+    let rounding = Rounding::default();
+    let policy = DecimalsPolicy::new(schema.concept_types.clone(), rounding);
+    document.add_values_with(&import_tags(rows), &policy);
+    document.add_missing_units();
+
+    schema.calculations.check(&document.facts(), rounding)
+}
+
+fn import_tags(rows: &[ImportRow]) -> Tags {
+    let mut tags = Tags::new();
+
+    for row in rows.iter().filter(|row| row.matched) {
+        tags.insert(&row.key, row.value.as_deref());
+    }
+
+    tags
+}
+
+/// Write the values of the table to the document.
+///
+/// Only facts whose value was edited are updated; an empty value marks the
+/// fact as nil. The `decimals` and `unitRef` attributes are set by the item
+/// type of the concept in the taxonomy like in `taxel generate`; without a
+/// taxonomy, by the unit of the fact. Returns the number of updated facts.
+pub fn apply_table(
+    element: &mut XbrlElement,
+    table: &XbrlTable,
+    schema: Option<&TaxonomySchema>,
+) -> usize {
+    let values = element
+        .facts()
+        .into_iter()
+        .map(|fact| if fact.nil { None } else { fact.value })
+        .collect::<Vec<_>>();
+    let policy = match schema {
+        Some(schema) => DecimalsPolicy::new(schema.concept_types.clone(), Rounding::default()),
+        None => DecimalsPolicy::default(),
+    };
+    let mut count = 0;
+
+    for row in &table.rows {
+        let value = Some(row.value.trim()).filter(|value| !value.is_empty());
+
+        if values.get(row.index).map(|value| value.as_deref()) == Some(value) {
+            continue;
+        }
+
+        if element.set_fact_value(row.index, value, &policy) {
+            count += 1;
+        }
+    }
+
+    count
+}
+
+/// Validate the document with ERiC like `taxel validate`; the confirmation
+/// is printed to `pdf_path` if the document is valid.
+pub fn validate_document<E>(eric: &E, document: &XbrlElement, pdf_path: &Path) -> Result<Outcome>
+where
+    E: EricBackend,
+{
+    let xml = String::from_utf8(write_xbrl(document)?)?;
+    let tax_version = tax_version(&xml)?;
+
+    let response = eric.validate(
+        xml,
+        TaxType::Bilanz.as_str(),
+        tax_version,
+        Some(path_to_str(pdf_path)?),
+    )?;
+
+    report_response(eric, &response, None, &SourceMap::new())
+}
+
+/// Send the document to the tax authorities with ERiC like `taxel send`; the
+/// confirmation is printed to `pdf_path` if the document is accepted.
+///
+/// The submission is stored in the archive. A document which was accepted
+/// before is not sent again.
+pub fn send_document<E>(
+    eric: &E,
+    document: &XbrlElement,
+    credentials: &Credentials,
+    archive: &Archive,
+    pdf_path: &Path,
+) -> Result<(Outcome, Submission)>
+where
+    E: EricBackend,
+{
+    let xml = String::from_utf8(write_xbrl(document)?)?;
+
+    if let Some(submission) = archive.find_accepted(&archive::sha256(xml.as_bytes()))? {
+        return Err(anyhow!(
+            "Identical xml file was already sent at {} with transfer ticket '{}' (submission '{}')",
+            submission.timestamp,
+            submission.transfer_ticket.as_deref().unwrap_or_default(),
+            submission.id,
+        ));
+    }
+
+    let tax_version = tax_version(&xml)?;
+    let response = eric.send(
+        xml.clone(),
+        TaxType::Bilanz.as_str(),
+        tax_version,
+        credentials,
+        Some(path_to_str(pdf_path)?),
+    )?;
+
+    let outcome = report_response(eric, &response, None, &SourceMap::new())?;
+    let transmission = Transmission {
+        xml: &xml,
+        xml_file: "taxel-gui",
+        tax_type: TaxType::Bilanz.as_str(),
+        tax_version,
+        response: &response,
+        taxel_version: env!("CARGO_PKG_VERSION"),
+        pdf_path: Some(pdf_path),
+    };
+    let submission = archive.store(&transmission, outcome.result.is_ok())?;
+
+    Ok((outcome, submission))
+}
+
+/// The taxonomy version of the namespaces of the document, or the latest
+/// version of the eBilanz.
+fn tax_version(xml: &str) -> Result<&'static str> {
+    let version = TaxonomyVersion::detect(xml)?
+        .map(|taxonomy| taxonomy.version)
+        .unwrap_or_else(|| TaxType::Bilanz.latest_version());
+
+    Ok(version)
+}
+
+fn path_to_str(path: &Path) -> Result<&str> {
+    path.to_str()
+        .ok_or(anyhow!("Invalid path '{}'", path.display()))
+}
+
+/// Serialize the document as xml file.
+pub fn write_xbrl(element: &XbrlElement) -> Result<Vec<u8>> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 4);
+    taxel::write_declaration(&mut writer)?;
+    element.serialize(&mut writer)?;
+
+    Ok(writer.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs, mem, process};
+    use taxel::{Arc, Statement, LEGAL_FORMS, REPORT_TYPES};
+    use taxel_eric::MockEric;
+
+    fn find<'a>(table: &'a XbrlTable, concept: &str, context: &str) -> &'a TableRow {
+        table
+            .rows
+            .iter()
+            .find(|row| row.concept == concept && row.context == context)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_xbrl_table() {
+        let xml =
+            fs::read_to_string("../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml")
+                .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+
+        let table = XbrlTable::new(&element);
+
+        assert_eq!(table.rows.len(), element.facts().len());
+        assert!(table.rows.iter().any(|row| row.nil && row.value.is_empty()));
+        assert!(table.rows.iter().any(|row| row.dimensions.contains(&(
+            String::from("de-gaap-ci:dim_taxTrans"),
+            String::from("de-gaap-ci:dim_taxBal")
+        ))));
+    }
+
+    #[test]
+    fn test_apply_table() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut element = read_xbrl(&xml).unwrap();
+        let mut table = XbrlTable::new(&element);
+
+        let row = table
+            .rows
+            .iter_mut()
+            .find(|row| row.concept == "de-gaap-ci:bs.ass" && row.context == "I-AKTJAHR")
+            .unwrap();
+        row.value = String::from("1234.5");
+
+        assert_eq!(apply_table(&mut element, &table, None), 1);
+
+        let xml = String::from_utf8(write_xbrl(&element).unwrap()).unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let table = XbrlTable::new(&element);
+        let row = find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR");
+        assert_eq!(row.value, "1234.50");
+        assert_eq!(row.decimals.as_deref(), Some("2"));
+        assert!(xml.contains(r#"<de-gaap-ci:bs.ass decimals="2" contextRef="I-AKTJAHR" unitRef="EUR">1234.50</de-gaap-ci:bs.ass>"#));
+        assert!(xml.contains(r#"xmlns:de-gcd="http://www.xbrl.de/taxonomies/de-gcd-2021-04-14""#));
+
+        // Unchanged values are not updated.
+        let mut element = element;
+        assert_eq!(apply_table(&mut element, &table, None), 0);
+
+        // The decimals are set by the item type of the taxonomy.
+        let concept = "de-gcd:genInfo.company.id.shareholder.currentnumber";
+        let mut schema = TaxonomySchema::default();
+        schema.concept_types.insert(concept, ItemType::Integer);
+        let mut table = table;
+        let row = table
+            .rows
+            .iter_mut()
+            .find(|row| row.concept == concept)
+            .unwrap();
+        row.value = String::from("3");
+
+        assert_eq!(apply_table(&mut element, &table, Some(&schema)), 1);
+
+        let table = XbrlTable::new(&element);
+        let row = find(&table, concept, "D-AKTJAHR");
+        assert_eq!(row.value, "3");
+        assert_eq!(row.decimals.as_deref(), Some("0"));
+    }
+
+    /// Edit the value of a row like the value column of taxel-gui.
+    fn edit(table: &mut XbrlTable, history: &mut History, row: usize, value: &str) {
+        let old = mem::replace(&mut table.rows[row].value, String::from(value));
+        history.record(Edit {
+            row,
+            old,
+            new: String::from(value),
+        });
+    }
+
+    #[test]
+    fn test_history() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let original = XbrlTable::new(&element);
+        let mut table = original.clone();
+        let mut history = History::default();
+
+        assert!(!history.can_undo());
+
+        // Typing a value is one step.
+        let old_value = table.rows[10].value.clone();
+        edit(&mut table, &mut history, 10, "1");
+        edit(&mut table, &mut history, 10, "12");
+        edit(&mut table, &mut history, 11, "x");
+
+        assert_eq!(
+            value_changes(&original, &table),
+            vec![
+                ValueChange {
+                    row: 10,
+                    original: old_value.clone(),
+                    edited: String::from("12"),
+                },
+                ValueChange {
+                    row: 11,
+                    original: original.rows[11].value.clone(),
+                    edited: String::from("x"),
+                },
+            ]
+        );
+
+        assert_eq!(history.undo(&mut table), Some(11));
+        assert_eq!(history.undo(&mut table), Some(10));
+        assert_eq!(history.undo(&mut table), None);
+        assert_eq!(table.rows[10].value, old_value);
+        assert_eq!(value_changes(&original, &table), vec![]);
+
+        assert!(history.can_redo());
+        assert_eq!(history.redo(&mut table), Some(10));
+        assert_eq!(table.rows[10].value, "12");
+
+        // A new edit clears the redo stack.
+        edit(&mut table, &mut history, 12, "y");
+        assert!(!history.can_redo());
+
+        // Reverting the value while typing removes the step.
+        let old_value = table.rows[13].value.clone();
+        edit(&mut table, &mut history, 13, "z");
+        edit(&mut table, &mut history, 13, &old_value);
+        assert_eq!(history.undo(&mut table), Some(12));
+
+        // Editing the same row again after leaving it is a new step.
+        edit(&mut table, &mut history, 14, "1");
+        history.seal();
+        edit(&mut table, &mut history, 14, "12");
+        assert_eq!(history.undo(&mut table), Some(14));
+        assert_eq!(table.rows[14].value, "1");
+    }
+
+    #[test]
+    fn test_row_filter() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let mut table = XbrlTable::new(&element);
+        let mut schema = TaxonomySchema::default();
+        schema
+            .labels
+            .insert(String::from("de-gaap-ci:bs.ass"), String::from("Aktiva"));
+        schema
+            .fiscal_requirements
+            .insert(String::from("de-gaap-ci:bs.ass"), String::from("Mussfeld"));
+        table.set_labels(&schema);
+        let row = find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR");
+
+        let mut filter = RowFilter::default();
+        assert!(filter.is_empty());
+        assert!(filter.matches(row, None, false));
+
+        for search in ["BS.ASS", "aktiva", "i-aktjahr", "178616"] {
+            filter.search = String::from(search);
+            assert!(filter.matches(row, None, false), "{search}");
+        }
+
+        filter.search = String::from("bs.eqLiab");
+        assert!(!filter.matches(row, None, false));
+
+        let filter = RowFilter {
+            only_filled: true,
+            only_mandatory: true,
+            ..Default::default()
+        };
+        assert!(filter.matches(row, Some(&schema), false));
+        assert!(!filter.matches(row, None, false));
+
+        let filter = RowFilter {
+            only_nil: true,
+            ..Default::default()
+        };
+        assert!(!filter.matches(row, None, false));
+        assert!(table
+            .rows
+            .iter()
+            .filter(|row| row.nil)
+            .all(|row| filter.matches(row, None, false)));
+
+        let filter = RowFilter {
+            only_errors: true,
+            ..Default::default()
+        };
+        assert!(!filter.matches(row, None, false));
+        assert!(filter.matches(row, None, true));
+    }
+
+    #[test]
+    fn test_presentation_tree() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let mut table = XbrlTable::new(&element);
+        let mut schema = TaxonomySchema::default();
+
+        for (from, to, order) in [
+            ("bs.head", "bs.ass", "1"),
+            ("bs.ass", "bs.ass.currAss", "2"),
+            ("bs.ass", "bs.ass.unknown", "3"),
+            ("bs.ass.currAss", "bs.ass.currAss.cashEquiv", "1"),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.bank",
+                "1",
+            ),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.cash",
+                "2",
+            ),
+        ] {
+            schema
+                .presentation
+                .insert(Arc {
+                    role: String::from("balanceSheet"),
+                    kind: String::from("presentationArc"),
+                    from: format!("de-gaap-ci:{from}"),
+                    to: format!("de-gaap-ci:{to}"),
+                    order: Some(String::from(order)),
+                    weight: None,
+                })
+                .unwrap();
+        }
+
+        // The weight -1 subtracts a summand, e.g. the expenses in a GuV.
+        for (to, weight) in [("bs.ass", "1"), ("bs.ass.currAss.cashEquiv.cash", "-1")] {
+            schema
+                .calculations
+                .insert(Arc {
+                    role: String::from("balanceSheet"),
+                    kind: String::from("calculationArc"),
+                    from: String::from("de-gaap-ci:bs.head"),
+                    to: format!("de-gaap-ci:{to}"),
+                    order: None,
+                    weight: Some(String::from(weight)),
+                })
+                .unwrap();
+        }
+
+        schema
+            .labels
+            .insert(String::from("de-gaap-ci:bs.ass"), String::from("Aktiva"));
+        table.set_labels(&schema);
+
+        // The value of the previous year precedes the current year.
+        let position = table
+            .rows
+            .iter()
+            .position(|row| row.concept == "de-gaap-ci:bs.ass" && row.context == "I-AKTJAHR")
+            .unwrap();
+        let mut previous_year = table.rows[position].clone();
+        previous_year.context = String::from("I-VORJAHR");
+        previous_year.value = String::from("1.00");
+        table.rows.insert(position, previous_year);
+
+        let tree = presentation_tree(&schema, &table);
+
+        assert_eq!(tree.len(), 1);
+        let head = &tree[0];
+        assert_eq!(head.label, "de-gaap-ci:bs.head");
+        // 178616.11 - 8015.83
+        assert_eq!(head.subtotal, Some("170600.28".parse().unwrap()));
+
+        let ass = &head.children[0];
+        assert_eq!(ass.label, "Aktiva");
+        assert_eq!(ass.subtotal, Some("178616.11".parse().unwrap()));
+        assert_eq!(
+            ass.children
+                .iter()
+                .map(|node| node.concept.as_str())
+                .collect::<Vec<_>>(),
+            vec!["de-gaap-ci:bs.ass.currAss"]
+        );
+        assert_eq!(
+            ass.children[0].children[0].subtotal,
+            Some("30606.18".parse().unwrap())
+        );
+        assert_eq!(
+            find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR")
+                .label
+                .as_deref(),
+            Some("Aktiva")
+        );
+    }
+
+    #[test]
+    fn test_check_table() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let mut table = XbrlTable::new(&element);
+        let mut schema = TaxonomySchema::default();
+
+        assert_eq!(check_table(&table, None), vec![]);
+
+        for (to, weight) in [("bs.ass.fixAss", "1"), ("bs.ass.currAss", "1")] {
+            schema
+                .calculations
+                .insert(Arc {
+                    role: String::from("balanceSheet"),
+                    kind: String::from("calculationArc"),
+                    from: String::from("de-gaap-ci:bs.ass"),
+                    to: format!("de-gaap-ci:{to}"),
+                    order: None,
+                    weight: Some(String::from(weight)),
+                })
+                .unwrap();
+        }
+
+        schema
+            .concept_types
+            .insert("de-gaap-ci:bs.ass.currAss", ItemType::Integer);
+        schema.fiscal_requirements.insert(
+            String::from("de-gaap-ci:bs.ass.fixAss"),
+            String::from("Mussfeld"),
+        );
+
+        let position = |concept: &str| {
+            table
+                .rows
+                .iter()
+                .position(|row| row.concept == concept && row.context == "I-AKTJAHR")
+                .unwrap()
+        };
+        let (fix_ass, curr_ass, ass) = (
+            position("de-gaap-ci:bs.ass.fixAss"),
+            position("de-gaap-ci:bs.ass.currAss"),
+            position("de-gaap-ci:bs.ass"),
+        );
+
+        table.rows[fix_ass].value = String::new();
+        table.rows[curr_ass].value = String::from("100.5");
+        table.rows[ass].value = String::from("1.234,50");
+
+        assert_eq!(
+            check_table(&table, Some(&schema)),
+            vec![
+                Issue {
+                    row: ass,
+                    message: String::from(
+                        "Invalid number '1.234,50', use a point as decimal separator and no \
+                         thousands separators"
+                    ),
+                },
+                Issue {
+                    row: curr_ass,
+                    message: String::from("Expected an integer instead of '100.5'"),
+                },
+                Issue {
+                    row: fix_ass,
+                    message: String::from("Missing value of a Mussfeld"),
+                },
+            ]
+        );
+
+        table.rows[ass].value = String::from("1000");
+        table.rows[fix_ass].value = String::from("800");
+
+        assert_eq!(
+            check_table(&table, Some(&schema))
+                .into_iter()
+                .map(|issue| (issue.row, issue.message))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    ass,
+                    String::from("Sum of items is 900.50, but total is 1000")
+                ),
+                (
+                    curr_ass,
+                    String::from("Expected an integer instead of '100.5'")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_issue_messages() {
+        let issues = [
+            (3, "Missing value of a Mussfeld"),
+            (3, "Sum of items is 1"),
+            (5, "x"),
+        ]
+        .into_iter()
+        .map(|(row, message)| Issue {
+            row,
+            message: String::from(message),
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(
+            issue_messages(&issues),
+            HashMap::from([
+                (
+                    3,
+                    String::from("Missing value of a Mussfeld\nSum of items is 1")
+                ),
+                (5, String::from("x")),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_import() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut template = read_xbrl(&xml).unwrap();
+        template.remove_values();
+        let dir = env::temp_dir().join(format!("taxel_test_import_{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let csv_path = dir.join("import.csv");
+        fs::write(
+            &csv_path,
+            "ebilanz_key,ebilanz_value\n\
+             de-gaap-ci:bs.ass,1234.5\n\
+             de-gaap-ci:unknown,1\n\
+             de-gcd:genInfo.company.id.name,Muster GmbH\n",
+        )
+        .unwrap();
+
+        let rows = read_import(&csv_path, &template).unwrap();
+
+        assert_eq!(
+            rows.iter()
+                .map(|row| (row.key.as_str(), row.matched))
+                .collect::<Vec<_>>(),
+            vec![
+                ("de-gaap-ci:bs.ass", true),
+                ("de-gaap-ci:unknown", false),
+                ("de-gcd:genInfo.company.id.name", true),
+            ]
+        );
+
+        // Import into a template sets the transfer header like `taxel generate`.
+        let mut document = template;
+        let mismatches = apply_template_import(&mut document, &rows, &TaxonomySchema::default());
+        let table = XbrlTable::new(&document);
+
+        assert_eq!(mismatches, vec![]);
+        assert_eq!(
+            find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR").value,
+            "1234.50"
+        );
+        assert_eq!(
+            find(&table, "de-gcd:genInfo.company.id.name", "D-AKTJAHR").value,
+            "Muster GmbH"
+        );
+        let written = String::from_utf8(write_xbrl(&document).unwrap()).unwrap();
+        assert!(written.contains("<HerstellerID>00000</HerstellerID>"));
+
+        // Import into the current document keeps the transfer header.
+        let mut document = read_xbrl(&xml).unwrap();
+        let mismatches = apply_import(&mut document, &rows, &TaxonomySchema::default());
+        let table = XbrlTable::new(&document);
+
+        assert_eq!(mismatches, vec![]);
+        assert_eq!(
+            find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR").value,
+            "1234.50"
+        );
+        let written = String::from_utf8(write_xbrl(&document).unwrap()).unwrap();
+        assert!(written.contains("<HerstellerID>74931</HerstellerID>"));
+        assert!(written.contains("<ProduktName>xyz</ProduktName>"));
+
+        assert_eq!(
+            read_import(&dir.join("import.txt"), &document)
+                .unwrap_err()
+                .to_string(),
+            format!(
+                "Unsupported file '{}'; expected a csv or ods file",
+                dir.join("import.txt").display()
+            )
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_import_into_rolled_over_document() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let mut document = read_xbrl(&xml).unwrap();
+        document.rollover().unwrap();
+        let mut schema = TaxonomySchema::default();
+
+        for to in ["bank", "cash"] {
+            schema
+                .calculations
+                .insert(Arc {
+                    role: String::from("balanceSheet"),
+                    kind: String::from("calculationArc"),
+                    from: String::from("de-gaap-ci:bs.ass.currAss.cashEquiv"),
+                    to: format!("de-gaap-ci:bs.ass.currAss.cashEquiv.{to}"),
+                    order: None,
+                    weight: None,
+                })
+                .unwrap();
+        }
+
+        let rows = [
+            ("de-gaap-ci:bs.ass.currAss.cashEquiv", "999.00"),
+            ("de-gaap-ci:bs.ass.currAss.cashEquiv.bank", "500.00"),
+        ]
+        .map(|(key, value)| ImportRow {
+            key: String::from(key),
+            value: Some(String::from(value)),
+            matched: true,
+        });
+
+        let mismatches = apply_import(&mut document, &rows, &schema);
+        let table = XbrlTable::new(&document);
+
+        // The prior-year values are kept and add up in their own context.
+        let cash_equiv = "de-gaap-ci:bs.ass.currAss.cashEquiv";
+        assert_eq!(find(&table, cash_equiv, "I-AKTJAHR").value, "999.00");
+        assert_eq!(find(&table, cash_equiv, "I-VORJAHR").value, "30606.18");
+        assert_eq!(
+            mismatches,
+            vec![CalculationMismatch {
+                concept: String::from(cash_equiv),
+                context: String::from("I-AKTJAHR"),
+                expected: "500.00".parse().unwrap(),
+                actual: "999.00".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_and_send_document() {
+        let xml =
+            fs::read_to_string("../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml")
+                .unwrap();
+        let document = read_xbrl(&xml).unwrap();
+        let dir = env::temp_dir().join("taxel_test_validate_and_send_document");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let pdf_path = dir.join("ebilanz.pdf");
+        let archive = Archive::new(dir.join("archive"));
+        let credentials = Credentials::new("../test_data/test-certificate.pfx", "123456");
+
+        let eric = MockEric::new([MockEric::validation_error()]);
+        let outcome = validate_document(&eric, &document, &pdf_path).unwrap();
+
+        assert!(outcome.report.has_errors());
+        assert_eq!(
+            outcome.result.unwrap_err().to_string(),
+            format!(
+                "Validation failed with {} error(s)",
+                outcome.report.errors().count()
+            )
+        );
+        assert_eq!(eric.requests()[0].tax_version, "6.5");
+        assert!(!pdf_path.exists());
+
+        let eric = MockEric::new([MockEric::success(true)]);
+        let (outcome, submission) =
+            send_document(&eric, &document, &credentials, &archive, &pdf_path).unwrap();
+
+        assert!(outcome.result.is_ok());
+        assert_eq!(
+            outcome.transfer_ticket.as_deref(),
+            Some("mock-transfer-ticket")
+        );
+        assert!(submission.accepted);
+        assert!(submission.has_pdf);
+        assert_eq!(eric.requests()[0].action, "send");
+
+        // An accepted document is not sent again.
+        let eric = MockEric::new([MockEric::success(true)]);
+        let err = send_document(&eric, &document, &credentials, &archive, &pdf_path).unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Identical xml file was already sent"));
+        assert!(eric.requests().is_empty());
+    }
+
+    #[test]
+    fn test_statement_view() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.5/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        let element = read_xbrl(&xml).unwrap();
+        let mut table = XbrlTable::new(&element);
+        let mut schema = TaxonomySchema::default();
+        let role = "balanceSheet";
+
+        for (from, to, order) in [
+            ("bs.head", "bs.ass", "1"),
+            ("bs.ass", "bs.ass.currAss", "2"),
+            ("bs.ass", "bs.ass.unknown", "3"),
+            ("bs.ass.currAss", "bs.ass.currAss.cashEquiv", "1"),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.bank",
+                "1",
+            ),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.cash",
+                "2",
+            ),
+        ] {
+            schema
+                .presentation
+                .insert(Arc {
+                    role: String::from(role),
+                    kind: String::from("presentationArc"),
+                    from: format!("de-gaap-ci:{from}"),
+                    to: format!("de-gaap-ci:{to}"),
+                    order: Some(String::from(order)),
+                    weight: None,
+                })
+                .unwrap();
+        }
+
+        // A fact of the previous year.
+        let mut previous_year = find(
+            &table,
+            "de-gaap-ci:bs.ass.currAss.cashEquiv.bank",
+            "I-AKTJAHR",
+        )
+        .clone();
+        previous_year.context = String::from("I-VORJAHR");
+        previous_year.value = String::from("100.00");
+        table.rows.insert(0, previous_year);
+
+        let mut previous_year = find(
+            &table,
+            "de-gaap-ci:bs.ass.currAss.cashEquiv.cash",
+            "I-AKTJAHR",
+        )
+        .clone();
+        previous_year.context = String::from("I-VORJAHR");
+        previous_year.value = String::from("30.00");
+        table.rows.insert(1, previous_year);
+
+        // The head has no summation-item relationship, and the weight -1
+        // subtracts a summand.
+        for (from, to, weight) in [
+            ("bs.ass", "bs.ass.currAss", "1"),
+            ("bs.ass.currAss", "bs.ass.currAss.cashEquiv", "1"),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.bank",
+                "1",
+            ),
+            (
+                "bs.ass.currAss.cashEquiv",
+                "bs.ass.currAss.cashEquiv.cash",
+                "-1",
+            ),
+        ] {
+            schema
+                .calculations
+                .insert(Arc {
+                    role: String::from(role),
+                    kind: String::from("calculationArc"),
+                    from: format!("de-gaap-ci:{from}"),
+                    to: format!("de-gaap-ci:{to}"),
+                    order: None,
+                    weight: Some(String::from(weight)),
+                })
+                .unwrap();
+        }
+
+        assert_eq!(statement_roles(&schema, &table), vec![role]);
+
+        let view = statement_view(&schema, &table, role);
+
+        assert_eq!(
+            view.columns
+                .iter()
+                .map(|column| column.title())
+                .collect::<Vec<_>>(),
+            vec!["AKTJAHR", "VORJAHR"]
+        );
+        assert_eq!(
+            view.rows
+                .iter()
+                .map(|row| (row.concept.as_str(), row.depth))
+                .collect::<Vec<_>>(),
+            vec![
+                ("de-gaap-ci:bs.head", 0),
+                ("de-gaap-ci:bs.ass", 1),
+                ("de-gaap-ci:bs.ass.currAss", 2),
+                ("de-gaap-ci:bs.ass.currAss.cashEquiv", 3),
+                ("de-gaap-ci:bs.ass.currAss.cashEquiv.bank", 4),
+                ("de-gaap-ci:bs.ass.currAss.cashEquiv.cash", 4),
+            ]
+        );
+
+        assert!(view.contains(0));
+        assert!(!view.contains(table.rows.len()));
+
+        let bank = &view.rows[4];
+        assert_eq!(bank.cells[1], StatementCell::Fact(0));
+        assert!(matches!(bank.cells[0], StatementCell::Fact(position) if position > 0));
+
+        // The head isn't reported and has no summands.
+        assert_eq!(
+            view.rows[0].cells,
+            vec![StatementCell::Empty, StatementCell::Empty]
+        );
+
+        // The totals of the previous year are the weighted sums of the
+        // summands, calculated recursively.
+        for row in &view.rows[1..4] {
+            assert_eq!(
+                row.cells[1],
+                StatementCell::Total("70.00".parse().unwrap()),
+                "{}",
+                row.concept
+            );
+        }
+    }
+
+    #[test]
+    fn test_new_filing() {
+        let mut filing = Filing {
+            company_name: String::from("Muster GmbH"),
+            legal_form: LEGAL_FORMS[0],
+            tax_number: String::from("5192050001265"),
+            tax_office: String::from("5192"),
+            fiscal_year_begin: String::from("2024-01-01"),
+            fiscal_year_end: String::from("2024-12-31"),
+            version: TaxonomyVersion::get("6.6").unwrap(),
+            report_type: REPORT_TYPES[0],
+            statements: vec![Statement::BalanceSheet],
+        };
+        let taxonomy_dir = Path::new("../test_data/schema/taxonomy/v6.6");
+
+        let (document, schema) = new_filing(&filing, taxonomy_dir).unwrap();
+        let table = XbrlTable::new(&document);
+
+        assert!(!schema.concept_types.is_empty());
+        assert_eq!(
+            find(&table, "de-gcd:genInfo.company.id.name", "D-AKTJAHR").value,
+            "Muster GmbH"
+        );
+        assert!(find(&table, "de-gaap-ci:bs.ass", "I-AKTJAHR").nil);
+
+        filing.version = TaxonomyVersion::get("6.5").unwrap();
+        assert_eq!(
+            new_filing(&filing, taxonomy_dir).unwrap_err().to_string(),
+            "Missing taxonomy 6.5 (2021-04-14) in directory '../test_data/schema/taxonomy/v6.6'"
+        );
+    }
 }
@@ -1,12 +1,44 @@
+mod elster;
+mod import;
+mod statement;
+mod wizard;
+
 use dioxus_devtools::subsecond;
 use eframe::{
-    egui::{self, CentralPanel, Color32, Context, Grid, ScrollArea, Ui, Visuals},
+    egui::{
+        self, collapsing_header::CollapsingState, CentralPanel, Color32, Context, Grid, Key,
+        KeyboardShortcut, Modifiers, ScrollArea, SidePanel, Ui, ViewportCommand, Visuals, Window,
+    },
     App, Frame,
 };
+use elster::{EricTask, ResponseDialog, SendAction, SendDialog};
+use import::{Import, ImportAction};
 use log::debug;
 use rfd::FileDialog;
-use std::{fs, path::PathBuf};
-use taxel_gui::{read_xbrl, TableRow, XbrlTable};
+use statement::{draw_roles, draw_statement, remember_value, value_cell};
+use std::{
+    collections::{HashMap, HashSet},
+    env::current_dir,
+    fs,
+    path::{Path, PathBuf},
+};
+use taxel::{Filing, TaxonomySchema, XbrlElement};
+use taxel_eric::{archive::Archive, Credentials, Eric};
+use taxel_gui::{
+    apply_import, apply_table, apply_template_import, check_table, issue_messages, new_filing,
+    presentation_tree, read_import, read_xbrl, send_document, statement_roles, statement_view,
+    validate_document, value_changes, write_xbrl, Edit, History, Issue, RowFilter, StatementView,
+    TableRow, TreeNode, ValueChange, XbrlTable,
+};
+use wizard::{Wizard, WizardAction};
+
+/// The archive directory of submissions, like the default of `taxel send`.
+const ARCHIVE_DIR: &str = "archive";
+
+const UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+const REDO_ALT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Y);
 
 fn main() -> Result<(), anyhow::Error> {
     // TODO: remove hot reloading support for release builds
@@ -31,37 +63,443 @@ fn main() -> Result<(), anyhow::Error> {
 
 pub struct XbrlApp {
     table: Option<XbrlTable>,
+    /// The imported document which the edited values are written to.
+    document: Option<XbrlElement>,
+    /// The path of the imported or last saved xml file.
+    path: Option<PathBuf>,
+    /// The directory with the taxonomy schemas, e.g. `taxonomy/v6.5`.
+    taxonomy_dir: Option<PathBuf>,
+    /// The taxonomy discovered from the schema references of the document.
+    schema: Option<TaxonomySchema>,
+    /// The presentation hierarchy shown in the navigator.
+    tree: Vec<TreeNode>,
+    /// The selected node of the navigator and the concepts below it.
+    selection: Option<Selection>,
+    /// The search and the toggles of the fact table.
+    filter: RowFilter,
+    /// Show the facts pivoted like a printed statement instead of the fact
+    /// table.
+    show_statement: bool,
+    /// The presentation roles with facts, e.g. the balance sheet.
+    roles: Vec<String>,
+    /// The presentation role of the statement.
+    role: Option<String>,
+    /// The statement of the role, updated on every change.
+    statement: StatementView,
+    /// The table of the loaded or last saved document to track the changes.
+    original: Option<XbrlTable>,
+    /// The values which differ from the original table, updated on every
+    /// change.
+    changes: Vec<ValueChange>,
+    history: History,
+    /// Show the changes since the document was loaded.
+    show_changes: bool,
+    /// The focused widget, to end the merged step of the history when a
+    /// value loses the focus.
+    focused: Option<egui::Id>,
+    /// The action which waits for the confirmation to discard the changes.
+    pending: Option<Pending>,
+    /// The title of the window, e.g. `Taxel - ebilanz.xml *`.
+    title: String,
+    /// The issues of the local checks, updated on every change.
+    issues: Vec<Issue>,
+    /// The messages of the issues by row.
+    messages: HashMap<usize, String>,
+    /// The row to scroll to and focus in the next frame.
+    jump_to: Option<usize>,
+    /// The "New filing" wizard, if open.
+    wizard: Option<Wizard>,
+    /// The preview of an imported csv or ods file, if open.
+    import: Option<Import>,
+    /// The confirmation of a transmission, if open.
+    send_dialog: Option<SendDialog>,
+    /// The validation or transmission which is running, if any.
+    eric_task: Option<EricTask>,
+    /// The responses of the last validation or transmission, if open.
+    response_dialog: Option<ResponseDialog>,
     error_message: Option<String>,
+    status_message: Option<String>,
+}
+
+/// An action which replaces the current document.
+#[derive(Clone, Copy)]
+enum Pending {
+    NewFiling,
+    ImportXml,
+}
+
+struct Selection {
+    concept: String,
+    concepts: HashSet<String>,
+    /// Scroll to the first fact of the selection in the next frame.
+    scroll: bool,
 }
 
 impl XbrlApp {
     pub fn new(table: Option<XbrlTable>, error_message: Option<String>) -> XbrlApp {
         Self {
             table,
+            document: None,
+            path: None,
+            taxonomy_dir: None,
+            schema: None,
+            tree: vec![],
+            selection: None,
+            filter: RowFilter::default(),
+            show_statement: false,
+            roles: vec![],
+            role: None,
+            statement: StatementView::default(),
+            original: None,
+            changes: vec![],
+            history: History::default(),
+            show_changes: false,
+            focused: None,
+            pending: None,
+            title: String::new(),
+            issues: vec![],
+            messages: HashMap::new(),
+            jump_to: None,
+            wizard: None,
+            import: None,
+            send_dialog: None,
+            eric_task: None,
+            response_dialog: None,
             error_message,
+            status_message: None,
         }
     }
 
     fn import_button(&mut self, ui: &mut Ui) {
-        if ui.button("Import XML").clicked() {
-            if let Some(path) = FileDialog::new()
-                .add_filter("XML", &["xml"])
-                .add_filter("All", &["*"])
-                .pick_file()
+        ui.horizontal(|ui| {
+            if ui.button("New filing").clicked() {
+                self.confirm_discard(Pending::NewFiling);
+            }
+
+            if ui.button("Import XML").clicked() {
+                self.confirm_discard(Pending::ImportXml);
+            }
+
+            if ui.button("Import CSV/ODS").clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("CSV/ODS", &["csv", "ods"])
+                    .add_filter("All", &["*"])
+                    .pick_file()
+                {
+                    self.import_values(path);
+                }
+            }
+
+            self.save_buttons(ui);
+            self.history_buttons(ui);
+
+            if ui.button("Open taxonomy").clicked() {
+                if let Some(path) = FileDialog::new().pick_folder() {
+                    self.taxonomy_dir = Some(path);
+                    self.load_schema();
+                }
+            }
+
+            let is_ready = self.document.is_some() && self.eric_task.is_none();
+
+            if ui
+                .add_enabled(is_ready, egui::Button::new("Validate with ERiC"))
+                .clicked()
             {
-                self.load_xml(&path);
+                self.validate_with_eric(ui.ctx());
+            }
+
+            if ui
+                .add_enabled(is_ready, egui::Button::new("Send"))
+                .clicked()
+            {
+                self.send_dialog = Some(SendDialog::default());
             }
-        }
+
+            if let Some(task) = &self.eric_task {
+                ui.spinner();
+                ui.label(format!("{} ...", task.title()));
+            }
+        });
 
         ui.separator();
 
         // Display error if present
         if let Some(err) = &self.error_message {
-            ui.colored_label(Color32::RED, format!("{err}"));
+            ui.colored_label(Color32::RED, err.to_string());
             if ui.button("Dismiss").clicked() {
                 self.error_message = None;
             }
         }
+
+        if let Some(status) = &self.status_message {
+            ui.label(status);
+        }
+    }
+
+    /// Check if the document has unsaved changes.
+    fn is_dirty(&self) -> bool {
+        !self.changes.is_empty() || self.document.is_some() && self.path.is_none()
+    }
+
+    /// Run the action, or ask to discard the unsaved changes first.
+    fn confirm_discard(&mut self, pending: Pending) {
+        if self.is_dirty() {
+            self.pending = Some(pending);
+        } else {
+            self.run_pending(pending);
+        }
+    }
+
+    fn run_pending(&mut self, pending: Pending) {
+        match pending {
+            Pending::NewFiling => self.wizard = Some(Wizard::default()),
+            Pending::ImportXml => {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("XML", &["xml"])
+                    .add_filter("All", &["*"])
+                    .pick_file()
+                {
+                    self.load_xml(&path);
+                }
+            }
+        }
+    }
+
+    fn draw_discard_dialog(&mut self, ctx: &Context) {
+        let Some(pending) = self.pending else {
+            return;
+        };
+
+        let mut action = None;
+
+        Window::new("Discard changes?")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if self.changes.is_empty() {
+                    ui.label("The document was not saved yet.");
+                } else {
+                    ui.label(format!("There are {} unsaved changes.", self.changes.len()));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Discard").clicked() {
+                        action = Some(true);
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        action = Some(false);
+                    }
+                });
+            });
+
+        if let Some(discard) = action {
+            self.pending = None;
+
+            if discard {
+                self.run_pending(pending);
+            }
+        }
+    }
+
+    fn history_buttons(&mut self, ui: &mut Ui) {
+        if ui
+            .add_enabled(self.history.can_undo(), egui::Button::new("Undo"))
+            .on_hover_text(ui.ctx().format_shortcut(&UNDO))
+            .clicked()
+        {
+            self.undo(ui.ctx());
+        }
+
+        if ui
+            .add_enabled(self.history.can_redo(), egui::Button::new("Redo"))
+            .on_hover_text(ui.ctx().format_shortcut(&REDO))
+            .clicked()
+        {
+            self.redo(ui.ctx());
+        }
+
+        if ui
+            .add_enabled(
+                self.original.is_some(),
+                egui::Button::new(format!("Changes ({})", self.changes.len())),
+            )
+            .clicked()
+        {
+            self.show_changes = !self.show_changes;
+        }
+    }
+
+    fn undo(&mut self, ctx: &Context) {
+        if let Some(table) = &mut self.table {
+            if let Some(row) = self.history.undo(table) {
+                remember_value(ctx, row, &table.rows[row].value);
+                self.check();
+            }
+        }
+    }
+
+    fn redo(&mut self, ctx: &Context) {
+        if let Some(table) = &mut self.table {
+            if let Some(row) = self.history.redo(table) {
+                remember_value(ctx, row, &table.rows[row].value);
+                self.check();
+            }
+        }
+    }
+
+    /// Undo and redo with the keyboard; the shortcuts are consumed before
+    /// the text fields handle them.
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        let (undo, redo) = ctx.input_mut(|input| {
+            let redo = input.consume_shortcut(&REDO) || input.consume_shortcut(&REDO_ALT);
+            let undo = input.consume_shortcut(&UNDO);
+            (undo, redo)
+        });
+
+        if redo {
+            self.redo(ctx);
+        } else if undo {
+            self.undo(ctx);
+        }
+    }
+
+    /// Show the file name in the title; unsaved changes are marked with `*`.
+    fn update_title(&mut self, ctx: &Context) {
+        let mut title = String::from("Taxel");
+
+        if self.document.is_some() {
+            let file_name = self
+                .path
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|file_name| file_name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("Untitled"));
+            title.push_str(&format!(" - {file_name}"));
+
+            if self.is_dirty() {
+                title.push_str(" *");
+            }
+        }
+
+        if title != self.title {
+            ctx.send_viewport_cmd(ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+    }
+
+    /// List the original and the edited values.
+    fn draw_changes(&mut self, ctx: &Context) {
+        let Some(table) = &self.table else {
+            return;
+        };
+
+        let mut is_open = self.show_changes;
+        let mut clicked = None;
+
+        Window::new("Changes since load")
+            .open(&mut is_open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                if self.changes.is_empty() {
+                    ui.label("No changes");
+                    return;
+                }
+
+                ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                    Grid::new("changes").striped(true).show(ui, |ui| {
+                        ui.strong("Key");
+                        ui.strong("Context");
+                        ui.strong("Original");
+                        ui.strong("Edited");
+                        ui.end_row();
+
+                        for change in &self.changes {
+                            let row = &table.rows[change.row];
+
+                            if ui.link(&row.concept).clicked() {
+                                clicked = Some(change.row);
+                            }
+
+                            ui.label(&row.context);
+                            ui.label(value_or_nil(&change.original));
+                            ui.label(value_or_nil(&change.edited));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        self.show_changes = is_open;
+
+        if let Some(position) = clicked {
+            self.selection = None;
+            self.filter = RowFilter::default();
+            self.jump_to = Some(position);
+        }
+    }
+
+    fn save_buttons(&mut self, ui: &mut Ui) {
+        let has_document = self.document.is_some();
+
+        if ui
+            .add_enabled(
+                has_document && self.path.is_some(),
+                egui::Button::new("Save"),
+            )
+            .clicked()
+        {
+            if let Some(path) = self.path.clone() {
+                self.save_xml(&path);
+            }
+        }
+
+        if ui
+            .add_enabled(has_document, egui::Button::new("Save as"))
+            .clicked()
+        {
+            let mut dialog = FileDialog::new()
+                .add_filter("XML", &["xml"])
+                .add_filter("All", &["*"]);
+
+            if let Some(file_name) = self.path.as_deref().and_then(Path::file_name) {
+                dialog = dialog.set_file_name(file_name.to_string_lossy());
+            }
+
+            if let Some(path) = dialog.save_file() {
+                self.save_xml(&path);
+            }
+        }
+    }
+
+    fn save_xml(&mut self, path: &Path) {
+        let (Some(document), Some(table)) = (&mut self.document, &self.table) else {
+            return;
+        };
+
+        debug!("Write xml file: {}", path.display());
+
+        let count = apply_table(document, table, self.schema.as_ref());
+        let res = write_xbrl(document).and_then(|xml| Ok(fs::write(path, xml)?));
+
+        match res {
+            Ok(()) => {
+                // Show the decimals and units as written.
+                let table = XbrlTable::new(document);
+                self.original = Some(table.clone());
+                self.set_table(table);
+                self.path = Some(path.to_owned());
+                self.error_message = None;
+                self.status_message = Some(format!(
+                    "Saved {count} changed values to '{}'",
+                    path.display()
+                ));
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to save file: {err}"));
+            }
+        }
     }
 
     fn load_xml(&mut self, path: &PathBuf) {
@@ -72,9 +510,18 @@ impl XbrlApp {
                 debug!("Parse xml file: {}", path.display());
 
                 match read_xbrl(&xml) {
-                    Ok(table) => {
-                        self.table = Some(table);
+                    Ok(document) => {
+                        let table = XbrlTable::new(&document);
+                        self.document = Some(document);
+                        self.path = Some(path.to_owned());
+                        self.selection = None;
                         self.error_message = None;
+                        self.status_message = None;
+                        self.schema = None;
+                        self.original = Some(table.clone());
+                        self.history.clear();
+                        self.set_table(table);
+                        self.load_schema();
                     }
                     Err(err) => {
                         self.error_message = Some(format!("Failed to parse XML: {err}",));
@@ -86,6 +533,458 @@ impl XbrlApp {
             }
         }
     }
+
+    /// Read the values of a csv or ods file and show the preview.
+    ///
+    /// The values are applied to the current document with the edited
+    /// values; without a document a template file is asked for.
+    fn import_values(&mut self, path: PathBuf) {
+        let res = match (&self.document, &self.table) {
+            (Some(document), Some(table)) => {
+                let mut template = document.clone();
+                apply_table(&mut template, table, self.schema.as_ref());
+                Ok((None, template))
+            }
+            _ => {
+                let Some(template_path) = FileDialog::new()
+                    .set_title("Open template")
+                    .add_filter("XML", &["xml"])
+                    .add_filter("All", &["*"])
+                    .pick_file()
+                else {
+                    return;
+                };
+
+                debug!("Read template: {}", template_path.display());
+
+                fs::read_to_string(&template_path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|xml| read_xbrl(&xml))
+                    .map(|mut template| {
+                        // Like `taxel generate`, the example values of the
+                        // template are removed.
+                        template.remove_values();
+                        (Some(template_path), template)
+                    })
+            }
+        };
+
+        let res = res.and_then(|(template_path, template)| {
+            let rows = read_import(&path, &template)?;
+
+            Ok(Import {
+                path,
+                template_path,
+                template,
+                rows,
+            })
+        });
+
+        match res {
+            Ok(import) => {
+                self.import = Some(import);
+                self.error_message = None;
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to import file: {err}"));
+            }
+        }
+    }
+
+    /// Apply the matched values of the import to its template.
+    fn apply_import(&mut self, import: Import) {
+        if import.template_path.is_some() {
+            // The imported values are changes of the template.
+            self.original = Some(XbrlTable::new(&import.template));
+        }
+
+        // The import can't be undone.
+        self.history.clear();
+        self.document = Some(import.template);
+
+        if import.template_path.is_some() {
+            // Ask for the file name on the first save.
+            self.path = None;
+            self.schema = None;
+            self.selection = None;
+            self.load_schema();
+        }
+
+        let Some(document) = &mut self.document else {
+            return;
+        };
+
+        let default_schema = TaxonomySchema::default();
+        let schema = self.schema.as_ref().unwrap_or(&default_schema);
+        let mismatches = match import.template_path {
+            Some(_) => apply_template_import(document, &import.rows, schema),
+            None => apply_import(document, &import.rows, schema),
+        };
+        let count = import.rows.iter().filter(|row| row.matched).count();
+        let table = XbrlTable::new(document);
+
+        let mut status = format!("Imported {count} values from '{}'", import.path.display());
+
+        if !mismatches.is_empty() {
+            status.push_str(&format!("; {} totals don't add up", mismatches.len()));
+        }
+
+        self.status_message = Some(status);
+        self.set_table(table);
+    }
+
+    /// Create a new filing from the input of the wizard; the taxonomy
+    /// directory is asked for if not opened yet.
+    fn create_filing(&mut self, filing: Filing) {
+        if self.taxonomy_dir.is_none() {
+            self.taxonomy_dir = FileDialog::new().set_title("Open taxonomy").pick_folder();
+        }
+
+        let Some(taxonomy_dir) = &self.taxonomy_dir else {
+            return;
+        };
+
+        match new_filing(&filing, taxonomy_dir) {
+            Ok((document, schema)) => {
+                let table = XbrlTable::new(&document);
+                self.document = Some(document);
+                self.schema = Some(schema);
+                // Ask for the file name on the first save.
+                self.path = None;
+                self.selection = None;
+                self.wizard = None;
+                self.error_message = None;
+                self.status_message = Some(format!(
+                    "Created new filing for '{}' with {} facts",
+                    filing.company_name,
+                    table.rows.len()
+                ));
+                self.original = Some(table.clone());
+                self.history.clear();
+                self.set_table(table);
+            }
+            Err(err) => {
+                if let Some(wizard) = &mut self.wizard {
+                    wizard.error_message = Some(format!("Failed to create filing: {err}"));
+                }
+            }
+        }
+    }
+
+    /// The document with the edited values.
+    fn edited_document(&self) -> Option<XbrlElement> {
+        let (Some(document), Some(table)) = (&self.document, &self.table) else {
+            return None;
+        };
+
+        let mut document = document.clone();
+        apply_table(&mut document, table, self.schema.as_ref());
+
+        Some(document)
+    }
+
+    /// Validate the edited document with ERiC on a worker thread.
+    fn validate_with_eric(&mut self, ctx: &Context) {
+        let Some(document) = self.edited_document() else {
+            return;
+        };
+
+        let task = EricTask::spawn(ctx, "Validation with ERiC", move |pdf_path| {
+            let eric = Eric::new(&current_dir()?)?;
+            let outcome = validate_document(&eric, &document, pdf_path)?;
+
+            Ok((outcome, None))
+        });
+
+        self.start(task);
+    }
+
+    /// Send the edited document after the confirmation on a worker thread.
+    fn send(&mut self, ctx: &Context, credentials: Credentials) {
+        let Some(document) = self.edited_document() else {
+            return;
+        };
+
+        // Export the credentials on the UI thread before ERiC is started; no
+        // other ERiC task is running at this point.
+        credentials.export();
+
+        let task = EricTask::spawn(ctx, "Transmission with ERiC", move |pdf_path| {
+            let eric = Eric::new(&current_dir()?)?;
+            let archive = Archive::new(ARCHIVE_DIR);
+            let (outcome, submission) =
+                send_document(&eric, &document, &credentials, &archive, pdf_path)?;
+
+            Ok((outcome, Some(submission)))
+        });
+
+        self.start(task);
+    }
+
+    fn start(&mut self, task: Result<EricTask, anyhow::Error>) {
+        match task {
+            Ok(task) => self.eric_task = Some(task),
+            Err(err) => self.error_message = Some(format!("Failed to start ERiC: {err}")),
+        }
+    }
+
+    /// Typing a value is one step of the history until the value loses the
+    /// focus.
+    fn seal_history(&mut self, ctx: &Context) {
+        let focused = ctx.memory(|memory| memory.focused());
+
+        if focused != self.focused {
+            self.history.seal();
+            self.focused = focused;
+        }
+    }
+
+    /// Show the responses once the running validation or transmission is
+    /// finished.
+    fn poll_eric_task(&mut self) {
+        let Some(task) = &self.eric_task else {
+            return;
+        };
+
+        let Some(result) = task.poll() else {
+            return;
+        };
+
+        match result {
+            Ok(response_dialog) => self.response_dialog = Some(response_dialog),
+            Err(err) => self.error_message = Some(format!("{} failed: {err}", task.title())),
+        }
+
+        self.eric_task = None;
+    }
+
+    /// Discover the taxonomy of the document in the taxonomy directory.
+    fn load_schema(&mut self) {
+        let (Some(document), Some(taxonomy_dir)) = (&self.document, &self.taxonomy_dir) else {
+            return;
+        };
+
+        debug!("Discover taxonomy in: {}", taxonomy_dir.display());
+
+        match TaxonomySchema::discover(taxonomy_dir, &document.schema_refs()) {
+            Ok(schema) if schema.concept_types.is_empty() => {
+                self.error_message = Some(format!(
+                    "Missing taxonomy of the xml file in directory '{}'",
+                    taxonomy_dir.display()
+                ));
+            }
+            Ok(schema) => {
+                self.schema = Some(schema);
+
+                if let Some(table) = self.table.take() {
+                    self.set_table(table);
+                }
+            }
+            Err(err) => {
+                self.error_message = Some(format!("Failed to read taxonomy: {err}"));
+            }
+        }
+    }
+
+    /// Show the table with the labels and the presentation hierarchy of the
+    /// taxonomy, if loaded.
+    fn set_table(&mut self, mut table: XbrlTable) {
+        if let Some(schema) = &self.schema {
+            table.set_labels(schema);
+            self.roles = statement_roles(schema, &table)
+                .into_iter()
+                .map(String::from)
+                .collect();
+        } else {
+            self.roles = vec![];
+        }
+
+        // Keep the statement of the previous document if it has facts.
+        if !self
+            .role
+            .as_ref()
+            .is_some_and(|role| self.roles.contains(role))
+        {
+            self.role = self.roles.first().cloned();
+        }
+
+        self.table = Some(table);
+        self.check();
+    }
+
+    /// Re-run the local checks on the values of the table, compare them with
+    /// the original values, and update the subtotals of the navigator and the
+    /// statement.
+    ///
+    /// The expanded nodes of the navigator are stored by concept in the egui
+    /// memory and the selection by concept, so both survive the rebuild.
+    fn check(&mut self) {
+        self.tree = match (&self.schema, &self.table) {
+            (Some(schema), Some(table)) => presentation_tree(schema, table),
+            _ => vec![],
+        };
+        self.issues = match &self.table {
+            Some(table) => check_table(table, self.schema.as_ref()),
+            None => vec![],
+        };
+        self.messages = issue_messages(&self.issues);
+        self.changes = match (&self.original, &self.table) {
+            (Some(original), Some(table)) => value_changes(original, table),
+            _ => vec![],
+        };
+        self.update_statement();
+    }
+
+    fn update_statement(&mut self) {
+        self.statement = match (&self.schema, &self.table, &self.role) {
+            (Some(schema), Some(table), Some(role)) => statement_view(schema, table, role),
+            _ => StatementView::default(),
+        };
+    }
+
+    fn draw_checks(&mut self, ui: &mut Ui) {
+        ui.heading("Checks");
+
+        let Some(table) = &self.table else {
+            ui.label("Import an xml file to check the values.");
+            return;
+        };
+
+        if self.schema.is_none() {
+            ui.label("Open the taxonomy to check item types, Mussfelder and calculations.");
+        }
+
+        if self.issues.is_empty() {
+            ui.label("No issues found.");
+            return;
+        }
+
+        ui.label(format!("{} issues", self.issues.len()));
+        ui.separator();
+
+        let mut clicked = None;
+
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                for issue in &self.issues {
+                    let row = &table.rows[issue.row];
+                    let text = format!("{} ({}): {}", row.concept, row.context, issue.message);
+
+                    if ui.link(text).clicked() {
+                        clicked = Some(issue.row);
+                    }
+                }
+            });
+
+        if let Some(position) = clicked {
+            let row = &table.rows[position];
+
+            // Show all facts if the selection of the navigator hides the row.
+            if self
+                .selection
+                .as_ref()
+                .is_some_and(|selection| !selection.concepts.contains(&row.concept))
+            {
+                self.selection = None;
+            }
+
+            if !self.filter.matches(row, self.schema.as_ref(), true) {
+                self.filter = RowFilter::default();
+            }
+
+            // Show the fact table if the row isn't part of the statement.
+            if self.show_statement && !self.statement.contains(position) {
+                self.show_statement = false;
+            }
+
+            self.jump_to = Some(position);
+        }
+    }
+
+    fn draw_view_toggle(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.show_statement, false, "Facts");
+
+            if ui
+                .add_enabled(
+                    self.schema.is_some(),
+                    egui::Button::selectable(self.show_statement, "Statement"),
+                )
+                .on_disabled_hover_text("Open the taxonomy to show the statements.")
+                .clicked()
+            {
+                self.show_statement = true;
+            }
+        });
+    }
+
+    fn draw_filter(&mut self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter.search)
+                    .hint_text("Search concept, label, context or value"),
+            );
+            ui.checkbox(&mut self.filter.only_filled, "Only filled");
+            ui.checkbox(&mut self.filter.only_nil, "Only nil");
+            ui.add_enabled(
+                self.schema.is_some(),
+                egui::Checkbox::new(&mut self.filter.only_mandatory, "Only mandatory"),
+            );
+            ui.checkbox(&mut self.filter.only_errors, "Only errors");
+
+            if ui
+                .add_enabled(!self.filter.is_empty(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                self.filter = RowFilter::default();
+            }
+        });
+    }
+
+    fn draw_navigator(&mut self, ui: &mut Ui) {
+        ui.heading("Navigator");
+
+        if self.schema.is_none() {
+            ui.label("Open the taxonomy to navigate the facts.");
+            return;
+        }
+
+        if ui
+            .add_enabled(
+                self.selection.is_some(),
+                egui::Button::new("Show all facts"),
+            )
+            .clicked()
+        {
+            self.selection = None;
+        }
+
+        ui.separator();
+
+        let mut selected = None;
+
+        ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                let concept = self
+                    .selection
+                    .as_ref()
+                    .map(|selection| selection.concept.as_str());
+
+                for node in &self.tree {
+                    draw_tree_node(node, concept, &mut selected, ui);
+                }
+            });
+
+        if let (Some(concept), Some(schema)) = (selected, &self.schema) {
+            self.selection = Some(Selection {
+                concepts: schema.presentation.subtree(&concept),
+                concept,
+                scroll: true,
+            });
+        }
+    }
 }
 
 // Note: dioxus hot reloading support requires the app in main.rs (see
@@ -94,16 +993,109 @@ impl App for XbrlApp {
     fn update(&mut self, ctx: &Context, _: &mut Frame) {
         // TODO: remove hot reloading support for release builds
         subsecond::call(|| {
+            self.handle_shortcuts(ctx);
+            self.seal_history(ctx);
+            self.update_title(ctx);
+            self.draw_discard_dialog(ctx);
+            self.draw_changes(ctx);
+            self.poll_eric_task();
+
+            if let Some(wizard) = &mut self.wizard {
+                match wizard.show(ctx) {
+                    Some(WizardAction::Create(filing)) => self.create_filing(*filing),
+                    Some(WizardAction::Cancel) => self.wizard = None,
+                    None => (),
+                }
+            }
+
+            if let Some(send_dialog) = &mut self.send_dialog {
+                match send_dialog.show(ctx) {
+                    Some(SendAction::Send(credentials)) => {
+                        self.send_dialog = None;
+                        self.send(ctx, credentials);
+                    }
+                    Some(SendAction::Cancel) => self.send_dialog = None,
+                    None => (),
+                }
+            }
+
+            if let Some(response_dialog) = &mut self.response_dialog {
+                if !response_dialog.show(ctx) {
+                    self.response_dialog = None;
+                }
+            }
+
+            if let Some(import) = &self.import {
+                match import.show(ctx) {
+                    Some(ImportAction::Apply) => {
+                        if let Some(import) = self.import.take() {
+                            self.apply_import(import);
+                        }
+                    }
+                    Some(ImportAction::Cancel) => self.import = None,
+                    None => (),
+                }
+            }
+
+            SidePanel::left("navigator")
+                .resizable(true)
+                .default_width(300.0)
+                .show(ctx, |ui| self.draw_navigator(ui));
+
+            SidePanel::right("checks")
+                .resizable(true)
+                .default_width(300.0)
+                .show(ctx, |ui| self.draw_checks(ui));
+
             CentralPanel::default().show(ctx, |ui| {
                 self.import_button(ui);
+                self.draw_view_toggle(ui);
+
+                if self.show_statement {
+                    if draw_roles(ui, &self.roles, &mut self.role) {
+                        self.update_statement();
+                    }
+                } else {
+                    self.draw_filter(ui);
+                }
+
+                ui.separator();
 
                 ScrollArea::vertical()
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
                         ui.heading("eBilanz");
 
-                        if let Some(table) = &mut self.table {
-                            draw_xbrl_table(&mut table.rows, ui);
+                        if self.show_statement {
+                            if let Some(table) = &mut self.table {
+                                let edit = draw_statement(
+                                    &self.statement,
+                                    &mut table.rows,
+                                    &self.messages,
+                                    &mut self.jump_to,
+                                    ui,
+                                );
+
+                                if let Some(edit) = edit {
+                                    self.history.record(edit);
+                                    self.check();
+                                }
+                            }
+                        } else if let Some(table) = &mut self.table {
+                            let edit = draw_xbrl_table(
+                                &mut table.rows,
+                                self.selection.as_mut(),
+                                &self.filter,
+                                self.schema.as_ref(),
+                                &self.messages,
+                                &mut self.jump_to,
+                                ui,
+                            );
+
+                            if let Some(edit) = edit {
+                                self.history.record(edit);
+                                self.check();
+                            }
                         }
                     });
             })
@@ -111,18 +1103,110 @@ impl App for XbrlApp {
     }
 }
 
-fn draw_xbrl_table(rows: &mut [TableRow], ui: &mut Ui) {
+/// Draw a node of the presentation hierarchy with its label and subtotal.
+fn draw_tree_node(
+    node: &TreeNode,
+    selection: Option<&str>,
+    selected: &mut Option<String>,
+    ui: &mut Ui,
+) {
+    let text = match &node.subtotal {
+        Some(subtotal) => format!("{}: {subtotal}", node.label),
+        None => node.label.clone(),
+    };
+    let is_selected = selection == Some(node.concept.as_str());
+
+    if node.children.is_empty() {
+        let response = ui
+            .selectable_label(is_selected, text)
+            .on_hover_text(&node.concept);
+
+        if response.clicked() {
+            *selected = Some(node.concept.clone());
+        }
+
+        return;
+    }
+
+    let id = ui.make_persistent_id(&node.concept);
+    let (_, header, _) = CollapsingState::load_with_default_open(ui.ctx(), id, false)
+        .show_header(ui, |ui| {
+            ui.selectable_label(is_selected, text)
+                .on_hover_text(&node.concept)
+        })
+        .body(|ui| {
+            for child in &node.children {
+                draw_tree_node(child, selection, selected, ui);
+            }
+        });
+
+    if header.inner.clicked() {
+        *selected = Some(node.concept.clone());
+    }
+}
+
+/// Draw the facts which match the filter; if a node of the navigator is
+/// selected, only the facts below it are shown. Values with issues are
+/// highlighted.
+///
+/// Returns the edit of a value, if any.
+fn draw_xbrl_table(
+    rows: &mut [TableRow],
+    mut selection: Option<&mut Selection>,
+    filter: &RowFilter,
+    schema: Option<&TaxonomySchema>,
+    messages: &HashMap<usize, String>,
+    jump_to: &mut Option<usize>,
+    ui: &mut Ui,
+) -> Option<Edit> {
+    let mut edit = None;
+
     Grid::new("xbrl_table").show(ui, |ui| {
         ui.label("Key");
+        ui.label("Label");
         ui.label("Context");
+        ui.label("Dimensions");
         ui.label("Unit");
+        ui.label("Decimals");
         ui.label("Value");
         ui.end_row();
 
-        for row in rows {
-            ui.label(&row.concept);
+        for (position, row) in rows.iter_mut().enumerate() {
+            if let Some(selection) = &mut selection {
+                if !selection.concepts.contains(&row.concept) {
+                    continue;
+                }
+            }
+
+            if !filter.matches(row, schema, messages.contains_key(&position)) {
+                continue;
+            }
+
+            let response = ui.label(&row.concept);
+
+            if let Some(selection) = &mut selection {
+                if selection.scroll {
+                    response.scroll_to_me(Some(egui::Align::TOP));
+                    selection.scroll = false;
+                }
+            }
+
+            ui.label(row.label.as_deref().unwrap_or("-"));
             ui.label(&row.context);
+
+            if row.dimensions.is_empty() {
+                ui.label("-");
+            } else {
+                let dimensions = row
+                    .dimensions
+                    .iter()
+                    .map(|(dimension, member)| format!("{dimension} = {member}"))
+                    .collect::<Vec<_>>();
+                ui.label(dimensions.join("\n"));
+            }
+
             ui.label(row.unit.as_deref().unwrap_or("-"));
+            ui.label(row.decimals.as_deref().unwrap_or("-"));
 
             egui::Frame::new()
                 .inner_margin(egui::Margin::ZERO)
@@ -131,11 +1215,26 @@ fn draw_xbrl_table(rows: &mut [TableRow], ui: &mut Ui) {
                         egui::vec2(600.0, ui.spacing().interact_size.y),
                         egui::Layout::left_to_right(egui::Align::Min),
                         |ui| {
-                            ui.add(egui::TextEdit::singleline(&mut row.value));
+                            if let Some(value_edit) =
+                                value_cell(ui, row, position, messages, jump_to)
+                            {
+                                edit = Some(value_edit);
+                            }
                         },
                     );
                 });
             ui.end_row();
         }
     });
+
+    edit
+}
+
+/// Show empty values as `nil`.
+fn value_or_nil(value: &str) -> &str {
+    if value.trim().is_empty() {
+        "nil"
+    } else {
+        value
+    }
 }
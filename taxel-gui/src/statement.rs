@@ -0,0 +1,180 @@
+//! The statements pivoted by period and dimension member, like a printed
+//! Bilanz and GuV.
+
+use eframe::egui::{self, Color32, ComboBox, Context, Grid, Id, Response, RichText, Ui};
+use std::collections::HashMap;
+use taxel_gui::{Edit, StatementCell, StatementView, TableRow};
+
+/// The width of the indentation per level of the presentation hierarchy.
+const INDENT: f32 = 16.0;
+
+/// Select one of the presentation roles with facts.
+pub fn draw_roles(ui: &mut Ui, roles: &[String], role: &mut Option<String>) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Statement");
+        ComboBox::from_id_salt("statement_role")
+            .selected_text(role.as_deref().map(role_name).unwrap_or("-"))
+            .show_ui(ui, |ui| {
+                for other in roles {
+                    let is_selected = role.as_ref() == Some(other);
+
+                    if ui
+                        .selectable_label(is_selected, role_name(other))
+                        .on_hover_text(other)
+                        .clicked()
+                        && !is_selected
+                    {
+                        *role = Some(other.clone());
+                        changed = true;
+                    }
+                }
+            });
+    });
+
+    changed
+}
+
+/// The last segment of the role, e.g. `balanceSheet` of
+/// `http://www.xbrl.de/taxonomies/de-gaap-ci/role/balanceSheet`.
+fn role_name(role: &str) -> &str {
+    role.rsplit('/').next().unwrap_or(role)
+}
+
+/// Draw the statement with one row per concept and one column per period
+/// and dimension member. Reported values can be edited; the totals of
+/// concepts which aren't reported are computed by the calculation linkbase.
+///
+/// Returns the edit of a value, if any.
+pub fn draw_statement(
+    statement: &StatementView,
+    rows: &mut [TableRow],
+    messages: &HashMap<usize, String>,
+    jump_to: &mut Option<usize>,
+    ui: &mut Ui,
+) -> Option<Edit> {
+    let mut edit = None;
+
+    if statement.rows.is_empty() {
+        ui.label("No facts in this statement.");
+        return None;
+    }
+
+    Grid::new("statement").striped(true).show(ui, |ui| {
+        ui.spacing_mut().text_edit_width = 120.0;
+        ui.strong("Position");
+
+        for column in &statement.columns {
+            ui.strong(column.title());
+        }
+
+        ui.end_row();
+
+        for statement_row in &statement.rows {
+            ui.horizontal(|ui| {
+                ui.add_space(statement_row.depth as f32 * INDENT);
+                ui.label(&statement_row.label)
+                    .on_hover_text(&statement_row.concept);
+            });
+
+            for cell in &statement_row.cells {
+                match cell {
+                    StatementCell::Empty => {
+                        ui.label("");
+                    }
+                    StatementCell::Total(total) => {
+                        ui.label(RichText::new(total.to_string()).italics().weak())
+                            .on_hover_text("Calculated from the summands of the position");
+                    }
+                    StatementCell::Fact(position) => {
+                        let position = *position;
+
+                        if let Some(value_edit) =
+                            value_cell(ui, &mut rows[position], position, messages, jump_to)
+                        {
+                            edit = Some(value_edit);
+                        }
+                    }
+                }
+            }
+
+            ui.end_row();
+        }
+    });
+
+    edit
+}
+
+/// Draw the text field of the value of the row at `position`; values with
+/// issues are highlighted and show the messages on hover. The field is
+/// scrolled to and focused if it is the row to jump to.
+///
+/// Returns the edit of the value, if any.
+pub fn value_cell(
+    ui: &mut Ui,
+    row: &mut TableRow,
+    position: usize,
+    messages: &HashMap<usize, String>,
+    jump_to: &mut Option<usize>,
+) -> Option<Edit> {
+    let mut text_edit = egui::TextEdit::singleline(&mut row.value);
+
+    if row.nil {
+        text_edit = text_edit.hint_text("nil");
+    }
+
+    let messages = messages.get(&position);
+
+    if messages.is_some() {
+        text_edit = text_edit.background_color(Color32::LIGHT_RED);
+    }
+
+    let mut response = ui.add(text_edit);
+    let edit = value_edit(ui, &response, position, &row.value);
+
+    if let Some(messages) = messages {
+        response = response.on_hover_text(messages);
+    }
+
+    if *jump_to == Some(position) {
+        response.scroll_to_me(Some(egui::Align::Center));
+        response.request_focus();
+        *jump_to = None;
+    }
+
+    edit
+}
+
+/// The edit of the value of the row at `position`, if the text field changed.
+///
+/// The value before the change is remembered when the field gains the focus,
+/// so that it isn't cloned on every frame.
+fn value_edit(ui: &Ui, response: &Response, position: usize, value: &str) -> Option<Edit> {
+    if response.changed() {
+        let old = ui.data(|data| data.get_temp::<String>(value_id(position)))?;
+        remember_value(ui.ctx(), position, value);
+
+        return Some(Edit {
+            row: position,
+            old,
+            new: value.to_owned(),
+        });
+    }
+
+    if response.gained_focus() {
+        remember_value(ui.ctx(), position, value);
+    }
+
+    None
+}
+
+/// Remember the value of the row at `position` as the value before the next
+/// change, e.g. after undo and redo.
+pub fn remember_value(ctx: &Context, position: usize, value: &str) {
+    ctx.data_mut(|data| data.insert_temp(value_id(position), value.to_owned()));
+}
+
+fn value_id(position: usize) -> Id {
+    Id::new(("value", position))
+}
@@ -0,0 +1,191 @@
+//! The "New filing" wizard which asks for the master data of the company.
+
+use anyhow::anyhow;
+use eframe::egui::{self, Color32, ComboBox, Context, Grid, Window};
+use taxel::{Choice, Filing, Statement, TaxType, TaxonomyVersion, LEGAL_FORMS, REPORT_TYPES};
+
+/// The input of the wizard.
+pub struct Wizard {
+    company_name: String,
+    legal_form: Choice,
+    tax_number: String,
+    tax_office: String,
+    fiscal_year: String,
+    fiscal_year_begin: String,
+    fiscal_year_end: String,
+    version: &'static str,
+    report_type: Choice,
+    statements: Vec<(Statement, bool)>,
+    pub error_message: Option<String>,
+}
+
+/// The button clicked in the wizard.
+pub enum WizardAction {
+    Create(Box<Filing>),
+    Cancel,
+}
+
+impl Default for Wizard {
+    fn default() -> Self {
+        Self {
+            company_name: String::new(),
+            legal_form: LEGAL_FORMS[0],
+            tax_number: String::new(),
+            tax_office: String::new(),
+            fiscal_year: String::new(),
+            fiscal_year_begin: String::new(),
+            fiscal_year_end: String::new(),
+            version: TaxType::Bilanz.latest_version(),
+            report_type: REPORT_TYPES[0],
+            statements: Statement::ALL
+                .iter()
+                .map(|statement| {
+                    let is_selected = matches!(
+                        statement,
+                        Statement::BalanceSheet | Statement::IncomeStatement
+                    );
+                    (*statement, is_selected)
+                })
+                .collect(),
+            error_message: None,
+        }
+    }
+}
+
+impl Wizard {
+    pub fn show(&mut self, ctx: &Context) -> Option<WizardAction> {
+        let mut action = None;
+
+        Window::new("New filing")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                Grid::new("new_filing").num_columns(2).show(ui, |ui| {
+                    ui.label("Company name");
+                    ui.text_edit_singleline(&mut self.company_name);
+                    ui.end_row();
+
+                    ui.label("Legal form");
+                    choice_box(ui, "legal_form", &mut self.legal_form, &LEGAL_FORMS);
+                    ui.end_row();
+
+                    ui.label("Steuernummer");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.tax_number)
+                            .hint_text("13 digits, e.g. 5192050001265"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Finanzamt");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.tax_office)
+                            .hint_text("4 digits, e.g. 5192"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Fiscal year");
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.fiscal_year).hint_text("e.g. 2024"),
+                    );
+
+                    // A calendar year by default; the dates can be changed
+                    // for a different fiscal year.
+                    if response.changed() && self.fiscal_year.len() == 4 {
+                        self.fiscal_year_begin = format!("{}-01-01", self.fiscal_year);
+                        self.fiscal_year_end = format!("{}-12-31", self.fiscal_year);
+                    }
+                    ui.end_row();
+
+                    ui.label("Begin of fiscal year");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.fiscal_year_begin)
+                            .hint_text("YYYY-MM-DD"),
+                    );
+                    ui.end_row();
+
+                    ui.label("End of fiscal year");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.fiscal_year_end)
+                            .hint_text("YYYY-MM-DD"),
+                    );
+                    ui.end_row();
+
+                    ui.label("Taxonomy version");
+                    ComboBox::from_id_salt("version")
+                        .selected_text(self.version)
+                        .show_ui(ui, |ui| {
+                            for version in TaxType::Bilanz.versions() {
+                                ui.selectable_value(&mut self.version, version, version);
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Report type");
+                    choice_box(ui, "report_type", &mut self.report_type, &REPORT_TYPES);
+                    ui.end_row();
+
+                    ui.label("Statements");
+                    ui.vertical(|ui| {
+                        for (statement, is_selected) in &mut self.statements {
+                            ui.checkbox(is_selected, statement.report_element().label);
+                        }
+                    });
+                    ui.end_row();
+                });
+
+                if let Some(err) = &self.error_message {
+                    ui.colored_label(Color32::RED, err);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Create").clicked() {
+                        match self.filing() {
+                            Ok(filing) => action = Some(WizardAction::Create(Box::new(filing))),
+                            Err(err) => self.error_message = Some(err.to_string()),
+                        }
+                    }
+
+                    if ui.button("Cancel").clicked() {
+                        action = Some(WizardAction::Cancel);
+                    }
+                });
+            });
+
+        action
+    }
+
+    fn filing(&self) -> Result<Filing, anyhow::Error> {
+        let version = TaxonomyVersion::get(self.version)
+            .ok_or(anyhow!("Unknown taxonomy version '{}'", self.version))?;
+        let filing = Filing {
+            company_name: self.company_name.clone(),
+            legal_form: self.legal_form,
+            tax_number: self.tax_number.trim().to_owned(),
+            tax_office: self.tax_office.trim().to_owned(),
+            fiscal_year_begin: self.fiscal_year_begin.trim().to_owned(),
+            fiscal_year_end: self.fiscal_year_end.trim().to_owned(),
+            version,
+            report_type: self.report_type,
+            statements: self
+                .statements
+                .iter()
+                .filter(|(_, is_selected)| *is_selected)
+                .map(|(statement, _)| *statement)
+                .collect(),
+        };
+
+        filing.check()?;
+
+        Ok(filing)
+    }
+}
+
+fn choice_box(ui: &mut egui::Ui, id: &str, selected: &mut Choice, choices: &[Choice]) {
+    ComboBox::from_id_salt(id)
+        .selected_text(selected.label)
+        .show_ui(ui, |ui| {
+            for choice in choices {
+                ui.selectable_value(selected, *choice, choice.label);
+            }
+        });
+}
@@ -0,0 +1,347 @@
+//! Check the summation-item relationships of the calculation linkbases.
+
+use crate::{
+    decimal::{Decimal, Rounding},
+    linkbase::Arc,
+    Fact,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// A weighted summand of a calculated total.
+#[derive(Debug, PartialEq, Clone)]
+struct Summand {
+    concept: String,
+    weight: Decimal,
+}
+
+/// The summation-item relationships of the calculation linkbases.
+///
+/// The relationships are grouped by the role of the extended link and the
+/// concept of the total.
+#[derive(Debug, Default)]
+pub struct Calculations(BTreeMap<(String, String), Vec<Summand>>);
+
+impl Calculations {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Insert a `calculationArc`; duplicate arcs are ignored.
+    pub fn insert(&mut self, arc: Arc) -> Result<(), anyhow::Error> {
+        let weight = arc.weight.as_deref().unwrap_or("1").parse()?;
+        let summands = self.0.entry((arc.role, arc.from)).or_default();
+
+        if !summands.iter().any(|summand| summand.concept == arc.to) {
+            summands.push(Summand {
+                concept: arc.to,
+                weight,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The number of summation-item relationships.
+    pub fn len(&self) -> usize {
+        self.0.values().map(|summands| summands.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The weighted sum of the summands of a total in the given role, e.g.
+    /// with the weight `-1` for expenses in the income statement; the values
+    /// of the summands are given by `value`.
+    ///
+    /// Returns `None` if the total has no summation-item relationship in the
+    /// role, or none of its summands has a value.
+    pub fn sum<F>(&self, role: &str, total: &str, mut value: F) -> Option<Decimal>
+    where
+        F: FnMut(&str) -> Option<Decimal>,
+    {
+        let summands = self.0.get(&(role.to_owned(), total.to_owned()))?;
+        let mut sum = None;
+
+        for summand in summands {
+            if let Some(summand_value) = value(&summand.concept) {
+                let weighted = summand.weight.checked_mul(&summand_value)?;
+                sum = Some(sum.unwrap_or_else(Decimal::zero).checked_add(&weighted)?);
+            }
+        }
+
+        sum
+    }
+
+    /// Check that the totals of the given facts add up.
+    ///
+    /// A total is only checked if at least one of its summands is reported
+    /// in the same context and unit. The sum of the summands is rounded to the
+    /// `decimals` of the total with the given rounding mode before comparison.
+    pub fn check(&self, facts: &[Fact], rounding: Rounding) -> Vec<CalculationMismatch> {
+        let values = NumericValues::new(facts);
+        let mut mismatches: Vec<CalculationMismatch> = vec![];
+
+        for ((_, total), summands) in &self.0 {
+            for (key, total_value) in values.get_concept(total) {
+                let mut sum = Some(Decimal::zero());
+                let mut has_summands = false;
+
+                for summand in summands {
+                    let summand_key = (summand.concept.as_str(), key.1, key.2);
+
+                    if let Some(summand_value) = values.get(&summand_key) {
+                        has_summands = true;
+                        sum = sum.and_then(|sum| {
+                            summand
+                                .weight
+                                .checked_mul(&summand_value.value)
+                                .and_then(|weighted| sum.checked_add(&weighted))
+                        });
+                    }
+                }
+
+                let sum = match sum {
+                    Some(sum) if has_summands => sum,
+                    _ => continue,
+                };
+                let rounded_sum = match total_value.decimals {
+                    Some(decimals) => sum.round(decimals, rounding),
+                    None => sum,
+                };
+
+                if rounded_sum != total_value.value {
+                    let mismatch = CalculationMismatch {
+                        concept: total.clone(),
+                        context: key.1.to_owned(),
+                        expected: rounded_sum,
+                        actual: total_value.value,
+                    };
+
+                    if !mismatches.contains(&mismatch) {
+                        mismatches.push(mismatch);
+                    }
+                }
+            }
+        }
+
+        mismatches
+    }
+}
+
+/// A total which doesn't match the sum of its summands.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CalculationMismatch {
+    pub concept: String,
+    pub context: String,
+    /// The weighted sum of the summands.
+    pub expected: Decimal,
+    /// The reported value of the total.
+    pub actual: Decimal,
+}
+
+impl fmt::Display for CalculationMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Calculation mismatch for '{}' in context '{}': sum of items is {}, but total is {}",
+            self.concept, self.context, self.expected, self.actual
+        )
+    }
+}
+
+struct NumericValue {
+    value: Decimal,
+    decimals: Option<u32>,
+}
+
+/// The numeric facts indexed by concept, context and unit.
+struct NumericValues<'a>(HashMap<(&'a str, &'a str, &'a str), NumericValue>);
+
+impl<'a> NumericValues<'a> {
+    fn new(facts: &'a [Fact]) -> Self {
+        let mut values = HashMap::new();
+
+        for fact in facts {
+            let (Some(unit), Some(value)) = (&fact.unit, &fact.value) else {
+                continue;
+            };
+
+            if fact.nil {
+                continue;
+            }
+
+            let Ok(value) = value.parse::<Decimal>() else {
+                continue;
+            };
+            let decimals = fact
+                .decimals
+                .as_deref()
+                .and_then(|decimals| decimals.parse().ok());
+
+            values
+                .entry((fact.concept.as_str(), fact.context.as_str(), unit.as_str()))
+                .or_insert(NumericValue { value, decimals });
+        }
+
+        Self(values)
+    }
+
+    fn get(&self, key: &(&'a str, &'a str, &'a str)) -> Option<&NumericValue> {
+        self.0.get(key)
+    }
+
+    fn get_concept<'b>(
+        &'b self,
+        concept: &'b str,
+    ) -> impl Iterator<Item = (&'b (&'a str, &'a str, &'a str), &'b NumericValue)> {
+        self.0.iter().filter(move |(key, _)| key.0 == concept)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculations() -> Calculations {
+        let mut calculations = Calculations::new();
+
+        for (to, weight) in [
+            ("de-gaap-ci:bs.ass.fixAss", "1"),
+            ("de-gaap-ci:bs.ass.currAss", "1"),
+        ] {
+            calculations
+                .insert(Arc {
+                    role: String::from("balanceSheet"),
+                    kind: String::from("calculationArc"),
+                    from: String::from("de-gaap-ci:bs.ass"),
+                    to: String::from(to),
+                    order: None,
+                    weight: Some(String::from(weight)),
+                })
+                .unwrap();
+        }
+
+        calculations
+    }
+
+    fn fact(concept: &str, value: &str) -> Fact {
+        Fact::new(
+            concept,
+            "I-AKTJAHR",
+            Some(String::from("EUR")),
+            Some(String::from("2")),
+            Some(String::from(value)),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_sum() {
+        let mut calculations = calculations();
+        calculations
+            .insert(Arc {
+                role: String::from("incomeStatement"),
+                kind: String::from("calculationArc"),
+                from: String::from("de-gaap-ci:is.netIncome"),
+                to: String::from("de-gaap-ci:is.netIncome.expenses"),
+                order: None,
+                weight: Some(String::from("-1")),
+            })
+            .unwrap();
+        let values = HashMap::from([
+            (
+                "de-gaap-ci:bs.ass.fixAss",
+                "100.00".parse::<Decimal>().unwrap(),
+            ),
+            ("de-gaap-ci:bs.ass.currAss", "200.00".parse().unwrap()),
+            ("de-gaap-ci:is.netIncome.expenses", "50.00".parse().unwrap()),
+        ]);
+        let value = |concept: &str| values.get(concept).copied();
+
+        assert_eq!(
+            calculations.sum("balanceSheet", "de-gaap-ci:bs.ass", value),
+            Some("300.00".parse().unwrap())
+        );
+        assert_eq!(
+            calculations.sum("incomeStatement", "de-gaap-ci:is.netIncome", value),
+            Some("-50.00".parse().unwrap())
+        );
+        // Without a summation-item relationship in the role
+        assert_eq!(
+            calculations.sum("incomeStatement", "de-gaap-ci:bs.ass", value),
+            None
+        );
+        assert_eq!(
+            calculations.sum("balanceSheet", "de-gaap-ci:bs.ass", |_| None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_check_calculations() {
+        let facts = vec![
+            fact("de-gaap-ci:bs.ass", "300.00"),
+            fact("de-gaap-ci:bs.ass.fixAss", "100.00"),
+            fact("de-gaap-ci:bs.ass.currAss", "200.00"),
+        ];
+
+        assert!(calculations().check(&facts, Rounding::default()).is_empty());
+    }
+
+    #[test]
+    fn test_check_calculations_mismatch() {
+        let facts = vec![
+            fact("de-gaap-ci:bs.ass", "300.00"),
+            fact("de-gaap-ci:bs.ass.fixAss", "100.01"),
+            fact("de-gaap-ci:bs.ass.currAss", "200.00"),
+        ];
+
+        assert_eq!(
+            calculations().check(&facts, Rounding::default()),
+            vec![CalculationMismatch {
+                concept: String::from("de-gaap-ci:bs.ass"),
+                context: String::from("I-AKTJAHR"),
+                expected: "300.01".parse().unwrap(),
+                actual: "300.00".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_calculations_rounding() {
+        let facts = vec![
+            fact("de-gaap-ci:bs.ass", "0.13"),
+            Fact::new(
+                "de-gaap-ci:bs.ass.fixAss",
+                "I-AKTJAHR",
+                Some(String::from("EUR")),
+                None,
+                Some(String::from("0.125")),
+                false,
+            ),
+            fact("de-gaap-ci:bs.ass.currAss", "0.00"),
+        ];
+
+        assert!(calculations().check(&facts, Rounding::HalfUp).is_empty());
+        assert_eq!(
+            calculations().check(&facts, Rounding::HalfEven),
+            vec![CalculationMismatch {
+                concept: String::from("de-gaap-ci:bs.ass"),
+                context: String::from("I-AKTJAHR"),
+                expected: "0.12".parse().unwrap(),
+                actual: "0.13".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_check_calculations_missing_summands() {
+        let facts = vec![fact("de-gaap-ci:bs.ass", "300.00")];
+
+        assert!(calculations().check(&facts, Rounding::default()).is_empty());
+    }
+}
@@ -1,8 +1,10 @@
-use crate::{Tag, Tags};
+use crate::{Provenance, Tag, Tags};
 pub use csv::{Reader, ReaderBuilder, Trim, Writer, WriterBuilder};
 use log::{debug, info};
 use serde::{Deserialize, Serialize};
 
+const VALUE_HEADER: &str = "ebilanz_value";
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CsvRow {
     #[serde(rename = "ebilanz_key")]
@@ -29,13 +31,7 @@ where
     let mut target_tags = Tags::new();
 
     if let Some(reader) = reader {
-        let records = reader.deserialize();
-
-        for record in records {
-            let row: CsvRow = record?;
-            let value = row.value.filter(|value| !value.is_empty());
-            target_tags.insert(row.key, value);
-        }
+        read_rows(reader, None, &mut target_tags)?;
     }
 
     debug!("Target tags read: {target_tags:#?}");
@@ -43,6 +39,55 @@ where
     Ok(target_tags)
 }
 
+/// Read target tags from the csv file with the given name.
+///
+/// The provenance of each value, i.e. the line and the column of
+/// `ebilanz_value`, is recorded.
+pub fn read_tags_from<R>(reader: &mut Reader<R>, file: &str) -> Result<Tags, anyhow::Error>
+where
+    R: std::io::Read,
+{
+    info!("Read target tags from '{file}'");
+
+    let mut target_tags = Tags::new();
+    read_rows(reader, Some(file), &mut target_tags)?;
+
+    debug!("Target tags read: {target_tags:#?}");
+
+    Ok(target_tags)
+}
+
+fn read_rows<R>(
+    reader: &mut Reader<R>,
+    file: Option<&str>,
+    target_tags: &mut Tags,
+) -> Result<(), anyhow::Error>
+where
+    R: std::io::Read,
+{
+    let headers = reader.headers()?.clone();
+    let value_column = headers
+        .iter()
+        .position(|header| header == VALUE_HEADER)
+        .map_or(2, |index| index as u64 + 1);
+
+    for record in reader.records() {
+        let record = record?;
+        let row: CsvRow = record.deserialize(Some(&headers))?;
+        let value = row.value.filter(|value| !value.is_empty());
+
+        match (file, record.position()) {
+            (Some(file), Some(position)) => {
+                let provenance = Provenance::new(file, position.line(), value_column);
+                target_tags.insert_with_provenance(row.key, value, provenance);
+            }
+            _ => target_tags.insert(row.key, value),
+        }
+    }
+
+    Ok(())
+}
+
 /// Write target tags to a csv file.
 pub fn write_tags<W>(writer: &mut Writer<W>, extracted_tags: Vec<Tag>) -> Result<(), anyhow::Error>
 where
@@ -87,8 +132,8 @@ mod tests {
 
         let target_tags = res.unwrap();
         assert_eq!(
-            target_tags,
-            Tags(HashMap::from_iter(vec![
+            target_tags.values,
+            HashMap::from_iter(vec![
                 (String::from("Empfaenger"), Some(String::from("1111"))),
                 (
                     String::from("ebilanz:stichtag"),
@@ -114,7 +159,37 @@ mod tests {
                     String::from("de-gcd:genInfo.company.id.location.country.isoCode"),
                     Some(String::from("DE"))
                 ),
-            ]))
-        )
+            ])
+        );
+        assert!(target_tags.provenance.is_empty());
+    }
+
+    #[test]
+    fn test_read_tags_from() {
+        let data = "ebilanz_key,ebilanz_value\n\
+            ebilanz:stichtag,20201231\n\
+            de-gaap-ci:bs.ass,300.00\n";
+        let mut reader = ReaderBuilder::new()
+            .has_headers(true)
+            .trim(Trim::All)
+            .from_reader(data.as_bytes());
+
+        let target_tags = read_tags_from(&mut reader, "balance.csv").unwrap();
+
+        assert_eq!(
+            target_tags.get("de-gaap-ci:bs.ass"),
+            Some(&Some(String::from("300.00")))
+        );
+        assert_eq!(
+            target_tags.provenance("de-gaap-ci:bs.ass"),
+            Some(&Provenance::new("balance.csv", 3, 2))
+        );
+        assert_eq!(
+            target_tags
+                .sources()
+                .get("ebilanz:stichtag")
+                .map(|provenance| provenance.to_string()),
+            Some(String::from("balance.csv:2:2"))
+        );
     }
 }
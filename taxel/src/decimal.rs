@@ -0,0 +1,375 @@
+use anyhow::anyhow;
+use serde::{Serialize, Serializer};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// The rounding mode used for numeric facts.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Rounding {
+    /// Round to the nearest neighbour; ties are rounded away from zero
+    /// (commercial rounding, _kaufmännisches Runden_).
+    #[default]
+    HalfUp,
+    /// Round to the nearest neighbour; ties are rounded to the even neighbour
+    /// (banker's rounding).
+    HalfEven,
+}
+
+impl Rounding {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::HalfUp => "half-up",
+            Self::HalfEven => "half-even",
+        }
+    }
+}
+
+impl FromStr for Rounding {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "half-up" => Ok(Self::HalfUp),
+            "half-even" => Ok(Self::HalfEven),
+            other => Err(anyhow!("Invalid rounding mode: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for Rounding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The maximum number of decimal places, i.e. the largest power of ten which
+/// fits into the mantissa.
+const MAX_SCALE: u32 = 38;
+
+/// A fixed-point decimal number to calculate with fact values without
+/// floating point errors.
+///
+/// The numeric value is `mantissa * 10^(-scale)`, with at most 38 decimal
+/// places.
+#[derive(Debug, Clone, Copy)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    pub fn zero() -> Self {
+        Self::new(0, 0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// Round to the given number of decimal places.
+    ///
+    /// If the value can't be represented with more decimal places, it keeps
+    /// its decimal places.
+    pub fn round(&self, decimals: u32, rounding: Rounding) -> Self {
+        if decimals >= self.scale {
+            return self.rescale(decimals).unwrap_or(*self);
+        }
+
+        // The mantissa is less than half of a divisor which doesn't fit into
+        // an i128, i.e. the value rounds to zero.
+        let Some(divisor) = 10i128.checked_pow(self.scale - decimals) else {
+            return Self::new(0, decimals);
+        };
+        let quotient = self.mantissa / divisor;
+        let remainder = (self.mantissa % divisor).abs();
+        let half = divisor / 2;
+        let sign = self.mantissa.signum();
+
+        let round_away = match remainder.cmp(&half) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => match rounding {
+                Rounding::HalfUp => true,
+                Rounding::HalfEven => quotient % 2 != 0,
+            },
+        };
+
+        let mantissa = if round_away {
+            quotient + sign
+        } else {
+            quotient
+        };
+
+        Self::new(mantissa, decimals)
+    }
+
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let scale = self.scale.max(other.scale);
+        let lhs = self.rescale(scale)?;
+        let rhs = other.rescale(scale)?;
+
+        lhs.mantissa
+            .checked_add(rhs.mantissa)
+            .map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&Self::new(-other.mantissa, other.scale))
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let scale = self.scale + other.scale;
+
+        if scale > MAX_SCALE {
+            return None;
+        }
+
+        self.mantissa
+            .checked_mul(other.mantissa)
+            .map(|mantissa| Self::new(mantissa, scale))
+    }
+
+    /// Divide by `other` and round the quotient to the given number of
+    /// decimal places; returns `None` for a division by zero or on overflow.
+    pub fn checked_div(&self, other: &Self, decimals: u32, rounding: Rounding) -> Option<Self> {
+        if other.is_zero() {
+            return None;
+        }
+
+        // Calculate one more digit than needed for rounding, and keep track of
+        // a non-zero remainder so that ties are only detected if exact.
+        let precision = decimals + 1;
+        let numerator = self
+            .mantissa
+            .checked_mul(10i128.checked_pow(other.scale + precision)?)?;
+        let quotient = numerator / other.mantissa;
+        let remainder = numerator % other.mantissa;
+        let sign = if (numerator < 0) != (other.mantissa < 0) {
+            -1
+        } else {
+            1
+        };
+        let quotient = if remainder == 0 {
+            Self::new(quotient, self.scale + precision)
+        } else {
+            Self::new(quotient.checked_mul(10)? + sign, self.scale + precision + 1)
+        };
+
+        Some(quotient.round(decimals, rounding))
+    }
+
+    /// The same value with more decimal places; returns `None` on overflow.
+    fn rescale(&self, scale: u32) -> Option<Self> {
+        if scale <= self.scale {
+            return Some(*self);
+        }
+
+        let mantissa = self
+            .mantissa
+            .checked_mul(10i128.checked_pow(scale - self.scale)?)?;
+
+        Some(Self::new(mantissa, scale))
+    }
+
+    /// The integer part and the fractional part of the mantissa, which have
+    /// the same sign.
+    fn split(&self) -> (i128, i128) {
+        match 10i128.checked_pow(self.scale) {
+            Some(divisor) => (self.mantissa / divisor, self.mantissa % divisor),
+            None => (0, self.mantissa),
+        }
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    /// Compare the integer parts first, so that values with many integer
+    /// digits and values with many decimal places can't overflow.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (lhs_integer, lhs_fraction) = self.split();
+        let (rhs_integer, rhs_fraction) = other.split();
+
+        // The fractions are less than `10^scale`, so they fit into an i128
+        // with the larger scale of at most 38 decimal places.
+        let scale = self.scale.max(other.scale);
+        let lhs_fraction = lhs_fraction.saturating_mul(10i128.saturating_pow(scale - self.scale));
+        let rhs_fraction = rhs_fraction.saturating_mul(10i128.saturating_pow(scale - other.scale));
+
+        lhs_integer
+            .cmp(&rhs_integer)
+            .then(lhs_fraction.cmp(&rhs_fraction))
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = s.trim();
+        let (negative, digits) = match value.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, value.strip_prefix('+').unwrap_or(value)),
+        };
+        let (integer, fraction) = match digits.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (digits, ""),
+        };
+
+        if integer.is_empty() && fraction.is_empty()
+            || !integer.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(anyhow!("Invalid decimal number: '{value}'"));
+        }
+
+        if fraction.len() > MAX_SCALE as usize {
+            return Err(anyhow!(
+                "Invalid decimal number '{value}': more than {MAX_SCALE} decimal places"
+            ));
+        }
+
+        let mantissa = format!("{integer}{fraction}")
+            .parse::<i128>()
+            .map_err(|err| anyhow!("Invalid decimal number '{value}': {err}"))?;
+        let mantissa = if negative { -mantissa } else { mantissa };
+
+        Ok(Self::new(mantissa, fraction.len() as u32))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let digits = self.mantissa.unsigned_abs().to_string();
+
+        if self.scale == 0 {
+            return write!(f, "{sign}{digits}");
+        }
+
+        let scale = self.scale as usize;
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+
+        write!(f, "{sign}{integer}.{fraction}")
+    }
+}
+
+impl Serialize for Decimal {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round(value: &str, decimals: u32, rounding: Rounding) -> String {
+        value
+            .parse::<Decimal>()
+            .unwrap()
+            .round(decimals, rounding)
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse_decimal() {
+        assert_eq!("550.50".parse::<Decimal>().unwrap(), Decimal::new(55050, 2));
+        assert_eq!("-0.5".parse::<Decimal>().unwrap(), Decimal::new(-5, 1));
+        assert_eq!("1000".parse::<Decimal>().unwrap(), Decimal::new(1000, 0));
+        assert!("1,5".parse::<Decimal>().is_err());
+        assert!("".parse::<Decimal>().is_err());
+
+        // The scale and the mantissa must fit into an i128.
+        assert!(format!("0.{}1", "0".repeat(40)).parse::<Decimal>().is_err());
+        assert!("1".repeat(40).parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn test_compare_decimal() {
+        let parse = |value: &str| value.parse::<Decimal>().unwrap();
+
+        assert_eq!(parse("1.50"), parse("1.5"));
+        assert!(parse("-1.5") < parse("-1.25"));
+        assert!(parse("-0.5") < parse("0.1"));
+        assert!(parse("12345678901234567890") > parse("0.12345678901234567890"));
+        assert!(parse("-12345678901234567890") < parse("-0.12345678901234567890"));
+        assert!(parse(&"9".repeat(38)) > parse(&format!("0.{}", "9".repeat(38))));
+    }
+
+    #[test]
+    fn test_decimal_overflow() {
+        // The divisor of 41 decimal places doesn't fit into an i128.
+        assert_eq!(
+            Decimal::new(1, 41).round(2, Rounding::HalfUp).to_string(),
+            "0.00"
+        );
+
+        // A value which can't be represented with more decimal places keeps
+        // its decimal places.
+        let large = "1".repeat(38).parse::<Decimal>().unwrap();
+        assert_eq!(large.round(2, Rounding::HalfUp), large);
+        assert_eq!(large.checked_add(&"0.01".parse().unwrap()), None);
+        assert_eq!(large.checked_mul(&large), None);
+    }
+
+    #[test]
+    fn test_display_decimal() {
+        assert_eq!(Decimal::new(55050, 2).to_string(), "550.50");
+        assert_eq!(Decimal::new(-5, 2).to_string(), "-0.05");
+        assert_eq!(Decimal::new(1000, 0).to_string(), "1000");
+    }
+
+    #[test]
+    fn test_round_half_even() {
+        assert_eq!(round("0.125", 2, Rounding::HalfEven), "0.12");
+        assert_eq!(round("0.135", 2, Rounding::HalfEven), "0.14");
+        assert_eq!(round("-0.125", 2, Rounding::HalfEven), "-0.12");
+        assert_eq!(round("0.1251", 2, Rounding::HalfEven), "0.13");
+        assert_eq!(round("550.5", 2, Rounding::HalfEven), "550.50");
+    }
+
+    #[test]
+    fn test_round_half_up() {
+        assert_eq!(round("0.125", 2, Rounding::HalfUp), "0.13");
+        assert_eq!(round("-0.125", 2, Rounding::HalfUp), "-0.13");
+        assert_eq!(round("0.124", 2, Rounding::HalfUp), "0.12");
+        assert_eq!(round("2.5", 0, Rounding::HalfUp), "3");
+    }
+
+    #[test]
+    fn test_checked_div() {
+        let div = |lhs: &str, rhs: &str, decimals| {
+            lhs.parse::<Decimal>()
+                .unwrap()
+                .checked_div(&rhs.parse().unwrap(), decimals, Rounding::HalfEven)
+                .map(|quotient| quotient.to_string())
+        };
+
+        assert_eq!(div("1", "3", 2), Some(String::from("0.33")));
+        assert_eq!(div("-2", "3", 2), Some(String::from("-0.67")));
+        assert_eq!(div("100.00", "0.5", 0), Some(String::from("200")));
+        assert_eq!(div("1", "8", 2), Some(String::from("0.12")));
+        assert_eq!(div("1.0000001", "8", 2), Some(String::from("0.13")));
+        assert_eq!(div("1", "0.00", 2), None);
+    }
+}
@@ -0,0 +1,291 @@
+//! Compare the facts of two XBRL instances.
+
+use crate::{
+    decimal::{Decimal, Rounding},
+    Fact,
+};
+use serde::Serialize;
+use std::{collections::HashMap, fmt};
+
+/// The kind of change of a fact.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Change {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The difference of a fact identified by concept, context, and occurrence.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct FactDiff {
+    pub change: Change,
+    pub concept: String,
+    pub context: String,
+    /// The occurrence of the fact among the facts with the same concept and
+    /// context, e.g. in the rows of a tuple; `1` for the first fact.
+    pub occurrence: usize,
+    pub old: Option<Fact>,
+    pub new: Option<Fact>,
+    /// The absolute delta `new - old` if both values are numeric.
+    pub delta: Option<Decimal>,
+    /// The delta in percent of the old value if both values are numeric and
+    /// the old value is not zero.
+    pub percentage: Option<Decimal>,
+    /// The attributes which differ, e.g. `xsi:nil`, `unitRef`, or `decimals`.
+    pub attributes: Vec<String>,
+}
+
+impl FactDiff {
+    fn new(old: Option<&Fact>, new: Option<&Fact>, occurrence: usize) -> Self {
+        let (change, fact) = match (old, new) {
+            (Some(old), Some(_)) => (Change::Changed, old),
+            (Some(old), None) => (Change::Removed, old),
+            (None, Some(new)) => (Change::Added, new),
+            (None, None) => unreachable!("Either old or new fact must be present"),
+        };
+        let old_value = old.and_then(numeric_value);
+        let new_value = new.and_then(numeric_value);
+        let delta = match (old_value, new_value) {
+            (Some(old), Some(new)) => new.checked_sub(&old),
+            _ => None,
+        };
+        let percentage = match (delta, old_value) {
+            (Some(delta), Some(old)) => delta
+                .checked_mul(&Decimal::new(100, 0))
+                .and_then(|delta| delta.checked_div(&old, 2, Rounding::HalfUp)),
+            _ => None,
+        };
+        let attributes = match (old, new) {
+            (Some(old), Some(new)) => changed_attributes(old, new),
+            _ => vec![],
+        };
+
+        Self {
+            change,
+            concept: fact.concept.clone(),
+            context: fact.context.clone(),
+            occurrence,
+            old: old.cloned(),
+            new: new.cloned(),
+            delta,
+            percentage,
+            attributes,
+        }
+    }
+}
+
+/// The concept, context, and occurrence of a fact.
+type FactKey<'a> = (&'a str, &'a str, usize);
+
+/// Compare the facts of two XBRL instances by concept and context.
+///
+/// Facts with the same concept and context, e.g. in the rows of a tuple, are
+/// compared in the order of their occurrence. Removed and changed facts are
+/// returned in the order of `old`, followed by the added facts in the order of
+/// `new`. Unchanged facts are omitted.
+pub fn diff_facts(old: &[Fact], new: &[Fact]) -> Vec<FactDiff> {
+    let old_facts = key_facts(old);
+    let new_facts = key_facts(new);
+    let old_index = old_facts.iter().copied().collect::<HashMap<_, _>>();
+    let new_index = new_facts.iter().copied().collect::<HashMap<_, _>>();
+    let mut diffs = vec![];
+
+    for (key, fact) in &old_facts {
+        match new_index.get(key) {
+            Some(new_fact) if is_unchanged(fact, new_fact) => (),
+            Some(new_fact) => diffs.push(FactDiff::new(Some(fact), Some(new_fact), key.2)),
+            None => diffs.push(FactDiff::new(Some(fact), None, key.2)),
+        }
+    }
+
+    for (key, fact) in &new_facts {
+        if !old_index.contains_key(key) {
+            diffs.push(FactDiff::new(None, Some(fact), key.2));
+        }
+    }
+
+    diffs
+}
+
+/// Key the facts by concept, context, and occurrence in the given order.
+fn key_facts(facts: &[Fact]) -> Vec<(FactKey<'_>, &Fact)> {
+    let mut occurrences = HashMap::<(&str, &str), usize>::new();
+
+    facts
+        .iter()
+        .map(|fact| {
+            let occurrence = occurrences
+                .entry((fact.concept.as_str(), fact.context.as_str()))
+                .or_default();
+            *occurrence += 1;
+
+            (
+                (fact.concept.as_str(), fact.context.as_str(), *occurrence),
+                fact,
+            )
+        })
+        .collect()
+}
+
+fn numeric_value(fact: &Fact) -> Option<Decimal> {
+    if fact.nil {
+        return None;
+    }
+
+    fact.value.as_deref()?.parse().ok()
+}
+
+/// Numeric values are compared by value, e.g. `100.0` equals `100.00`.
+fn is_unchanged(old: &Fact, new: &Fact) -> bool {
+    let same_value = match (numeric_value(old), numeric_value(new)) {
+        (Some(old_value), Some(new_value)) => old_value == new_value,
+        _ => old.value == new.value,
+    };
+
+    same_value && changed_attributes(old, new).is_empty()
+}
+
+fn changed_attributes(old: &Fact, new: &Fact) -> Vec<String> {
+    let mut attributes = vec![];
+
+    if old.nil != new.nil {
+        attributes.push(String::from("xsi:nil"));
+    }
+
+    if old.unit != new.unit {
+        attributes.push(String::from("unitRef"));
+    }
+
+    if old.decimals != new.decimals {
+        attributes.push(String::from("decimals"));
+    }
+
+    attributes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fact(concept: &str, value: Option<&str>) -> Fact {
+        Fact::new(
+            concept,
+            "I-AKTJAHR",
+            Some(String::from("EUR")),
+            value.map(|_| String::from("2")),
+            value.map(String::from),
+            value.is_none(),
+        )
+    }
+
+    #[test]
+    fn test_diff_facts() {
+        let old = vec![
+            fact("de-gaap-ci:bs.ass", Some("200.00")),
+            fact("de-gaap-ci:bs.ass.fixAss", Some("100.00")),
+            fact("de-gaap-ci:bs.ass.currAss", Some("100.00")),
+            fact("de-gaap-ci:bs.eqLiab", None),
+        ];
+        let new = vec![
+            fact("de-gaap-ci:bs.ass", Some("250.0")),
+            fact("de-gaap-ci:bs.ass.fixAss", Some("100.0")),
+            fact("de-gaap-ci:bs.eqLiab", Some("250.00")),
+            fact("de-gaap-ci:is.netIncome", Some("50.00")),
+        ];
+
+        let diffs = diff_facts(&old, &new);
+
+        let summary = diffs
+            .iter()
+            .map(|diff| {
+                (
+                    diff.change,
+                    diff.concept.as_str(),
+                    diff.delta.map(|delta| delta.to_string()),
+                    diff.percentage.map(|percentage| percentage.to_string()),
+                    diff.attributes.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Change::Changed,
+                    "de-gaap-ci:bs.ass",
+                    Some(String::from("50.00")),
+                    Some(String::from("25.00")),
+                    vec![]
+                ),
+                (
+                    Change::Removed,
+                    "de-gaap-ci:bs.ass.currAss",
+                    None,
+                    None,
+                    vec![]
+                ),
+                (
+                    Change::Changed,
+                    "de-gaap-ci:bs.eqLiab",
+                    None,
+                    None,
+                    vec![String::from("xsi:nil"), String::from("decimals")]
+                ),
+                (Change::Added, "de-gaap-ci:is.netIncome", None, None, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_facts_duplicates() {
+        let old = vec![
+            fact("de-gaap-ci:bs.ass", Some("100.00")),
+            fact("de-gaap-ci:bs.ass", Some("200.00")),
+        ];
+        let new = vec![
+            fact("de-gaap-ci:bs.ass", Some("100.00")),
+            fact("de-gaap-ci:bs.ass", Some("250.00")),
+            fact("de-gaap-ci:bs.ass", Some("300.00")),
+        ];
+
+        let diffs = diff_facts(&old, &new);
+
+        let summary = diffs
+            .iter()
+            .map(|diff| {
+                (
+                    diff.change,
+                    diff.occurrence,
+                    diff.delta.map(|delta| delta.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            summary,
+            vec![
+                (Change::Changed, 2, Some(String::from("50.00"))),
+                (Change::Added, 3, None),
+            ]
+        );
+        assert!(diff_facts(&new, &new).is_empty());
+    }
+}
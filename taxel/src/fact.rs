@@ -0,0 +1,35 @@
+use serde::Serialize;
+
+/// A fact of an XBRL instance, i.e. a taxonomy element with a `contextRef`.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Fact {
+    /// The concept name including the taxonomy prefix, e.g.
+    /// `de-gaap-ci:bs.ass`.
+    pub concept: String,
+    pub context: String,
+    pub unit: Option<String>,
+    pub decimals: Option<String>,
+    pub value: Option<String>,
+    /// Whether the fact is marked as `xsi:nil="true"`.
+    pub nil: bool,
+}
+
+impl Fact {
+    pub fn new(
+        concept: impl Into<String>,
+        context: impl Into<String>,
+        unit: Option<String>,
+        decimals: Option<String>,
+        value: Option<String>,
+        nil: bool,
+    ) -> Self {
+        Self {
+            concept: concept.into(),
+            context: context.into(),
+            unit,
+            decimals,
+            value,
+            nil,
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! Create a new eBilanz filing from the master data of the company.
+
+use crate::{
+    taxonomy_version::is_date,
+    xbrl::{XbrlAttribute, CONTEXT_REF, CURRENT_YEAR},
+    Rounding, Tags, TaxType, TaxonomySchema, TaxonomyVersion, XbrlElement,
+};
+use anyhow::anyhow;
+use std::collections::HashSet;
+
+/// The scheme of the entity identifier, i.e. the tax number of the company.
+const TAX_NUMBER_SCHEME: &str = "http://www.rzf-nrw.de/Steuernummer";
+
+/// The base url of the roles of the presentation hierarchy.
+const ROLE_URL: &str = "http://www.xbrl.de/taxonomies/de-gaap-ci/role/";
+
+/// An entry of an enumeration in `de-gcd`, e.g. the legal form `GMBH`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Choice {
+    /// The suffix of the concept, e.g. `GMBH` for
+    /// `de-gcd:genInfo.company.id.legalStatus.legalStatus.GMBH`.
+    pub code: &'static str,
+    /// The German label, which is used as value of the fact.
+    pub label: &'static str,
+}
+
+impl Choice {
+    const fn new(code: &'static str, label: &'static str) -> Self {
+        Self { code, label }
+    }
+}
+
+/// The common legal forms (`Rechtsform`).
+pub const LEGAL_FORMS: [Choice; 10] = [
+    Choice::new("GMBH", "GmbH"),
+    Choice::new("UG", "UG (haftungsbeschränkt)"),
+    Choice::new("AG", "AG"),
+    Choice::new("GKG", "GmbH & Co. KG"),
+    Choice::new("KG", "KG"),
+    Choice::new("OHG", "OHG"),
+    Choice::new("GBR", "GbR"),
+    Choice::new("PG", "Partnerschaftsgesellschaft"),
+    Choice::new("EU", "Einzelkaufmann"),
+    Choice::new("EG", "eG"),
+];
+
+/// The types of report (`Art des Berichts`).
+pub const REPORT_TYPES: [Choice; 4] = [
+    Choice::new("JA", "Jahresabschluss"),
+    Choice::new("EB", "Erstellungsbericht"),
+    Choice::new("GB", "Geschäftsbericht"),
+    Choice::new("S", "sonstiger Bericht"),
+];
+
+/// A statement of the financial report (`Berichtsbestandteil`).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Statement {
+    /// The balance sheet (_Bilanz_).
+    BalanceSheet,
+    /// The income statement (_Gewinn- und Verlustrechnung_).
+    IncomeStatement,
+    /// The appropriation of profits (_Ergebnisverwendung_).
+    AppropriationProfits,
+    /// The reconciliation to the tax balance sheet (_steuerliche
+    /// Überleitungsrechnung_).
+    TransfersCommercialCodeToTax,
+}
+
+impl Statement {
+    pub const ALL: [Statement; 4] = [
+        Statement::BalanceSheet,
+        Statement::IncomeStatement,
+        Statement::AppropriationProfits,
+        Statement::TransfersCommercialCodeToTax,
+    ];
+
+    /// The report element in `de-gcd`.
+    pub fn report_element(&self) -> Choice {
+        match self {
+            Self::BalanceSheet => Choice::new("B", "Bilanz"),
+            Self::IncomeStatement => Choice::new("GuV", "GuV"),
+            Self::AppropriationProfits => Choice::new("EV", "Ergebnisverwendung"),
+            Self::TransfersCommercialCodeToTax => {
+                Choice::new("STU", "steuerliche Überleitungsrechnung")
+            }
+        }
+    }
+
+    /// The role of the presentation hierarchy in `de-gaap-ci`.
+    pub fn role(&self) -> String {
+        let role = match self {
+            Self::BalanceSheet => "balanceSheet",
+            Self::IncomeStatement => "incomeStatement",
+            Self::AppropriationProfits => "appropriationProfits",
+            Self::TransfersCommercialCodeToTax => "transfersCommercialCodeToTax",
+        };
+
+        format!("{ROLE_URL}{role}")
+    }
+
+    /// The context of the facts; the balance sheet is reported at the end of
+    /// the fiscal year, the other statements for the fiscal year.
+    fn context(&self) -> String {
+        match self {
+            Self::BalanceSheet => format!("I-{CURRENT_YEAR}"),
+            _ => format!("D-{CURRENT_YEAR}"),
+        }
+    }
+}
+
+/// The master data of a new eBilanz filing.
+#[derive(Debug, Clone)]
+pub struct Filing {
+    pub company_name: String,
+    pub legal_form: Choice,
+    /// The tax number (`Steuernummer`) in the 13-digit ELSTER format.
+    pub tax_number: String,
+    /// The 4-digit number of the tax office (`Finanzamt`).
+    pub tax_office: String,
+    /// The first day of the fiscal year in the format `YYYY-MM-DD`.
+    pub fiscal_year_begin: String,
+    /// The last day of the fiscal year in the format `YYYY-MM-DD`.
+    pub fiscal_year_end: String,
+    pub version: &'static TaxonomyVersion,
+    pub report_type: Choice,
+    pub statements: Vec<Statement>,
+}
+
+impl Filing {
+    /// Check the master data.
+    pub fn check(&self) -> Result<(), anyhow::Error> {
+        if self.company_name.trim().is_empty() {
+            return Err(anyhow!("Missing company name"));
+        }
+
+        if self.tax_number.len() != 13 || !is_digits(&self.tax_number) {
+            return Err(anyhow!(
+                "Invalid tax number '{}'; expected 13 digits",
+                self.tax_number
+            ));
+        }
+
+        if self.tax_office.len() != 4 || !is_digits(&self.tax_office) {
+            return Err(anyhow!(
+                "Invalid tax office '{}'; expected 4 digits",
+                self.tax_office
+            ));
+        }
+
+        for date in [&self.fiscal_year_begin, &self.fiscal_year_end] {
+            if !is_date(date) {
+                return Err(anyhow!("Invalid date '{date}'; expected YYYY-MM-DD"));
+            }
+        }
+
+        if self.fiscal_year_begin >= self.fiscal_year_end {
+            return Err(anyhow!(
+                "The fiscal year begins after it ends: {} - {}",
+                self.fiscal_year_begin,
+                self.fiscal_year_end
+            ));
+        }
+
+        if self.statements.is_empty() {
+            return Err(anyhow!("Missing statements"));
+        }
+
+        Ok(())
+    }
+
+    /// Build the XBRL instance, i.e. `xbrli:xbrl`, with empty facts for the
+    /// items of the selected statements.
+    ///
+    /// The items are taken from the presentation hierarchy of the statements
+    /// in the given taxonomy schema; tuples are left out.
+    pub fn instance(&self, schema: &TaxonomySchema) -> XbrlElement {
+        let mut children = vec![
+            xbrl("link:schemaRef")
+                .with_attribute("xlink:type", "simple")
+                .with_attribute("xlink:href", self.version.shell("de-gcd", "shell")),
+            xbrl("link:schemaRef")
+                .with_attribute("xlink:type", "simple")
+                .with_attribute(
+                    "xlink:href",
+                    self.version.shell("de-gaap-ci", "shell-fiscal"),
+                ),
+            self.context(&format!("I-{CURRENT_YEAR}"), true),
+            self.context(&format!("D-{CURRENT_YEAR}"), false),
+        ];
+
+        children.extend(self.general_information());
+
+        let mut concepts = HashSet::new();
+
+        for statement in &self.statements {
+            let role = statement.role();
+            let context = statement.context();
+
+            for root in schema.presentation.roots(&role) {
+                collect_facts(schema, &role, root, &context, &mut concepts, &mut children);
+            }
+        }
+
+        let mut instance = xbrl("xbrli:xbrl");
+
+        for module in ["de-gcd", "de-gaap-ci"] {
+            instance =
+                instance.with_attribute(&format!("xmlns:{module}"), self.version.namespace(module));
+        }
+
+        for (prefix, namespace) in [
+            ("link", "http://www.xbrl.org/2003/linkbase"),
+            ("xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+            ("xbrli", "http://www.xbrl.org/2003/instance"),
+            ("xbrldi", "http://xbrl.org/2006/xbrldi"),
+            ("iso4217", "http://www.xbrl.org/2003/iso4217"),
+            ("xlink", "http://www.w3.org/1999/xlink"),
+        ] {
+            instance = instance.with_attribute(&format!("xmlns:{prefix}"), namespace);
+        }
+
+        instance.children = children;
+        instance
+    }
+
+    /// The master data as values for the facts of the instance.
+    pub fn tags(&self) -> Tags {
+        let mut tags = Tags::new();
+
+        for (key, value) in [
+            ("de-gcd:genInfo.company.id.name", self.company_name.trim()),
+            (
+                "de-gcd:genInfo.company.id.idNo.type.companyId.ST13",
+                &self.tax_number,
+            ),
+            (
+                "de-gcd:genInfo.company.id.idNo.type.companyId.BF4",
+                &self.tax_office,
+            ),
+            (
+                "de-gcd:genInfo.report.period.fiscalYearBegin",
+                &self.fiscal_year_begin,
+            ),
+            (
+                "de-gcd:genInfo.report.period.fiscalYearEnd",
+                &self.fiscal_year_end,
+            ),
+            (
+                "de-gcd:genInfo.report.period.balSheetClosingDate",
+                &self.fiscal_year_end,
+            ),
+        ] {
+            tags.insert(key, Some(value));
+        }
+
+        tags
+    }
+
+    /// Insert the instance into the `ebilanz:EBilanz` element of an ELSTER
+    /// template like `templates/elster_v11/taxonomy_v6.5/ebilanz.xml`, and
+    /// add the master data like `taxel generate`.
+    pub fn generate(
+        &self,
+        mut template: XbrlElement,
+        schema: &TaxonomySchema,
+    ) -> Result<XbrlElement, anyhow::Error> {
+        self.check()?;
+
+        let ebilanz = find_mut(&mut template, "ebilanz:EBilanz")
+            .ok_or(anyhow!("Missing element 'ebilanz:EBilanz' in template"))?;
+        ebilanz.set_attribute("version", self.version.ebilanz_version);
+        ebilanz.children.retain(|child| child.name != "xbrli:xbrl");
+
+        match ebilanz
+            .children
+            .iter_mut()
+            .find(|child| child.name == "ebilanz:stichtag")
+        {
+            Some(closing_date) => closing_date.value = Some(self.fiscal_year_end.replace('-', "")),
+            None => return Err(anyhow!("Missing element 'ebilanz:stichtag' in template")),
+        }
+
+        ebilanz.children.push(self.instance(schema));
+        template.generate_values(self.tags(), TaxType::Bilanz, schema, Rounding::default());
+
+        Ok(template)
+    }
+
+    fn context(&self, id: &str, is_instant: bool) -> XbrlElement {
+        let period = if is_instant {
+            xbrl("xbrli:period").with_child(xbrl("xbrli:instant").with_value(&self.fiscal_year_end))
+        } else {
+            xbrl("xbrli:period")
+                .with_child(xbrl("xbrli:startDate").with_value(&self.fiscal_year_begin))
+                .with_child(xbrl("xbrli:endDate").with_value(&self.fiscal_year_end))
+        };
+
+        xbrl("xbrli:context")
+            .with_attribute("id", id)
+            .with_child(
+                xbrl("xbrli:entity").with_child(
+                    xbrl("xbrli:identifier")
+                        .with_attribute("scheme", TAX_NUMBER_SCHEME)
+                        .with_value(&self.tax_number),
+                ),
+            )
+            .with_child(period)
+    }
+
+    /// The facts of `de-gcd` for the master data and the enumerations of
+    /// report type, statements, and legal form.
+    fn general_information(&self) -> Vec<XbrlElement> {
+        let context = format!("D-{CURRENT_YEAR}");
+        let fact = |concept: &str| {
+            xbrl(concept)
+                .with_attribute(CONTEXT_REF, &context)
+                .with_nil()
+        };
+        let choice = |concept: &str, choice: &Choice| {
+            xbrl(&format!("{concept}.{}", choice.code))
+                .with_attribute(CONTEXT_REF, &context)
+                .with_value(choice.label)
+        };
+
+        let mut report_elements = xbrl("de-gcd:genInfo.report.id.reportElement");
+
+        for statement in &self.statements {
+            report_elements = report_elements.with_child(choice(
+                "de-gcd:genInfo.report.id.reportElement.reportElements",
+                &statement.report_element(),
+            ));
+        }
+
+        vec![
+            xbrl("de-gcd:genInfo.report.id.reportType").with_child(choice(
+                "de-gcd:genInfo.report.id.reportType.reportType",
+                &self.report_type,
+            )),
+            report_elements,
+            fact("de-gcd:genInfo.report.period.fiscalYearBegin"),
+            fact("de-gcd:genInfo.report.period.fiscalYearEnd"),
+            fact("de-gcd:genInfo.report.period.balSheetClosingDate"),
+            xbrl("de-gcd:genInfo.company.id.legalStatus").with_child(choice(
+                "de-gcd:genInfo.company.id.legalStatus.legalStatus",
+                &self.legal_form,
+            )),
+            fact("de-gcd:genInfo.company.id.name"),
+            xbrl("de-gcd:genInfo.company.id.idNo")
+                .with_child(fact("de-gcd:genInfo.company.id.idNo.type.companyId.ST13"))
+                .with_child(fact("de-gcd:genInfo.company.id.idNo.type.companyId.BF4")),
+        ]
+    }
+}
+
+/// Collect empty facts for the items of the presentation hierarchy in
+/// presentation order; each concept is reported once.
+///
+/// Abstract concepts only group their children, and tuples are skipped
+/// including their children.
+fn collect_facts(
+    schema: &TaxonomySchema,
+    role: &str,
+    concept: &str,
+    context: &str,
+    concepts: &mut HashSet<String>,
+    facts: &mut Vec<XbrlElement>,
+) {
+    let Some(item_type) = schema.concept_types.get(concept) else {
+        return;
+    };
+
+    if !concepts.insert(concept.to_owned()) {
+        return;
+    }
+
+    if !schema.concept_types.is_abstract(concept) {
+        let mut fact = xbrl(concept)
+            .with_attribute(CONTEXT_REF, context)
+            .with_nil();
+
+        if let Some(unit) = item_type.unit() {
+            fact = fact.with_attribute("unitRef", unit);
+        }
+
+        facts.push(fact);
+    }
+
+    for child in schema.presentation.children(role, concept) {
+        collect_facts(schema, role, child, context, concepts, facts);
+    }
+}
+
+fn find_mut<'a>(element: &'a mut XbrlElement, name: &str) -> Option<&'a mut XbrlElement> {
+    if element.name == name {
+        return Some(element);
+    }
+
+    element
+        .children
+        .iter_mut()
+        .find_map(|child| find_mut(child, name))
+}
+
+fn is_digits(value: &str) -> bool {
+    value.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Create an element; the xml type is derived from the name like for parsed
+/// elements.
+fn xbrl(name: &str) -> XbrlElement {
+    XbrlElement::new(name, None, vec![], XbrlElement::get_xml_type(name), vec![])
+}
+
+impl XbrlElement {
+    fn with_attribute(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.attributes.push(XbrlAttribute::new(key, value));
+        self
+    }
+
+    fn with_value(mut self, value: &str) -> Self {
+        self.value = Some(value.to_owned());
+        self
+    }
+
+    fn with_child(mut self, child: XbrlElement) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn with_nil(self) -> Self {
+        self.with_attribute("xsi:nil", "true")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{write_declaration, Reader, Writer};
+    use std::{fs, path::Path};
+
+    fn filing() -> Filing {
+        Filing {
+            company_name: String::from("Muster GmbH"),
+            legal_form: LEGAL_FORMS[0],
+            tax_number: String::from("5192050001265"),
+            tax_office: String::from("5192"),
+            fiscal_year_begin: String::from("2023-07-01"),
+            fiscal_year_end: String::from("2024-06-30"),
+            version: TaxonomyVersion::get("6.6").unwrap(),
+            report_type: REPORT_TYPES[0],
+            statements: vec![Statement::BalanceSheet, Statement::IncomeStatement],
+        }
+    }
+
+    #[test]
+    fn test_check_filing() {
+        assert!(filing().check().is_ok());
+
+        let mut invalid = filing();
+        invalid.tax_number = String::from("519/205/00012");
+        assert_eq!(
+            invalid.check().unwrap_err().to_string(),
+            "Invalid tax number '519/205/00012'; expected 13 digits"
+        );
+
+        let mut invalid = filing();
+        invalid.fiscal_year_end = String::from("2023-06-30");
+        assert_eq!(
+            invalid.check().unwrap_err().to_string(),
+            "The fiscal year begins after it ends: 2023-07-01 - 2023-06-30"
+        );
+    }
+
+    #[test]
+    fn test_generate_filing() {
+        let filing = filing();
+        let schema = TaxonomySchema::discover(
+            Path::new("../test_data/schema/taxonomy/v6.6"),
+            &filing.version.shells(),
+        )
+        .unwrap();
+        let template =
+            fs::read_to_string("../templates/elster_v11/taxonomy_v6.5/ebilanz.xml").unwrap();
+        let mut reader = Reader::from_str(&template);
+        reader.trim_text(true);
+        let template = XbrlElement::parse(&mut reader).unwrap();
+
+        let element = filing.generate(template, &schema).unwrap();
+
+        let facts = element.facts();
+        let fact = |concept: &str| facts.iter().find(|fact| fact.concept == concept);
+
+        assert_eq!(
+            fact("de-gcd:genInfo.company.id.name")
+                .unwrap()
+                .value
+                .as_deref(),
+            Some("Muster GmbH")
+        );
+        assert_eq!(
+            fact("de-gcd:genInfo.company.id.legalStatus.legalStatus.GMBH")
+                .unwrap()
+                .value
+                .as_deref(),
+            Some("GmbH")
+        );
+        assert_eq!(
+            fact("de-gcd:genInfo.company.id.idNo.type.companyId.ST13")
+                .unwrap()
+                .value
+                .as_deref(),
+            Some("5192050001265")
+        );
+
+        let total = fact("de-gaap-ci:bs.ass").unwrap();
+        assert!(total.nil);
+        assert_eq!(total.context, "I-AKTJAHR");
+        assert_eq!(total.unit.as_deref(), Some("EUR"));
+        assert_eq!(
+            fact("de-gaap-ci:is.netIncome").unwrap().context,
+            "D-AKTJAHR"
+        );
+        assert!(fact("de-gaap-ci:bs").is_none());
+        assert_eq!(
+            facts
+                .iter()
+                .filter(|fact| fact.concept == "de-gaap-ci:bs.ass")
+                .count(),
+            1
+        );
+
+        let mut writer = Writer::new(Vec::new());
+        write_declaration(&mut writer).unwrap();
+        element.serialize(&mut writer).unwrap();
+        let xml = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert_eq!(
+            TaxonomyVersion::detect(&xml).unwrap(),
+            TaxonomyVersion::get("6.6")
+        );
+        assert!(xml.contains("<ebilanz:stichtag>20240630</ebilanz:stichtag>"));
+        assert!(xml.contains(
+            r#"<xbrli:unit id="EUR"><xbrli:measure>iso4217:EUR</xbrli:measure></xbrli:unit>"#
+        ));
+        assert!(xml.contains("<Verfahren>ElsterBilanz</Verfahren>"));
+    }
+}
@@ -1,16 +1,48 @@
+mod calculation;
 mod csv;
+mod decimal;
+mod diff;
+mod fact;
+mod filing;
+mod linkbase;
+mod merge;
+mod migrate;
 mod ods;
+mod presentation;
+mod provenance;
+mod query;
+mod report;
+mod rollover;
+mod tax_type;
+mod taxonomy;
+mod taxonomy_version;
 mod xbrl;
 mod xml;
 
 pub use crate::csv::{
-    read_tags, write_tags, Reader as CsvReader, ReaderBuilder as CsvReaderBuilder, Trim,
-    Writer as CsvWriter, WriterBuilder as CsvWriterBuilder,
+    read_tags, read_tags_from, write_tags, Reader as CsvReader, ReaderBuilder as CsvReaderBuilder,
+    Trim, Writer as CsvWriter, WriterBuilder as CsvWriterBuilder,
 };
+pub use calculation::{CalculationMismatch, Calculations};
+pub use decimal::{Decimal, Rounding};
+pub use diff::{diff_facts, Change, FactDiff};
+pub use fact::Fact;
+pub use filing::{Choice, Filing, Statement, LEGAL_FORMS, REPORT_TYPES};
+pub use linkbase::{read_arcs, Arc};
 use log::warn;
+pub use merge::{merge_instances, merge_tags, MergeConflict};
+pub use migrate::Migration;
+pub use ods::read_tags_ods;
+pub use presentation::Presentation;
+pub use provenance::{Provenance, SourceMap};
+pub use query::Query;
 pub use quick_xml::{Reader, Writer};
+pub use report::{Diagnostic, Origin, Severity, ValidationReport};
 use std::collections::HashMap;
-pub use xbrl::XbrlElement;
+pub use tax_type::TaxType;
+pub use taxonomy::{ConceptTypes, Decimals, ItemType, TaxonomySchema};
+pub use taxonomy_version::{namespace_dates, TaxonomyVersion, TAXONOMY_VERSIONS};
+pub use xbrl::{DecimalsPolicy, XbrlElement};
 pub use xml::{extract_tag_values, remove_formatting, write_declaration};
 
 #[derive(Debug, PartialEq)]
@@ -29,7 +61,11 @@ impl Tag {
 }
 
 #[derive(Debug, PartialEq)]
-pub struct Tags(HashMap<String, Option<String>>);
+pub struct Tags {
+    values: HashMap<String, Option<String>>,
+    /// The location of the values in the source files.
+    provenance: HashMap<String, Provenance>,
+}
 
 impl Default for Tags {
     fn default() -> Self {
@@ -39,11 +75,30 @@ impl Default for Tags {
 
 impl Tags {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self {
+            values: HashMap::new(),
+            provenance: HashMap::new(),
+        }
     }
 
     pub fn get(&self, target_key: &str) -> Option<&Option<String>> {
-        self.0.get(target_key)
+        self.values.get(target_key)
+    }
+
+    /// The location of the value in the source file, if known.
+    pub fn provenance(&self, target_key: &str) -> Option<&Provenance> {
+        self.provenance.get(target_key)
+    }
+
+    /// The locations of all values read from source files.
+    pub fn sources(&self) -> SourceMap {
+        let mut sources = SourceMap::new();
+
+        for (key, provenance) in &self.provenance {
+            sources.insert(key.clone(), provenance.clone());
+        }
+
+        sources
     }
 
     pub fn insert(
@@ -53,26 +108,52 @@ impl Tags {
     ) {
         let key = target_key.into();
         let value = target_value.map(|inner| inner.into());
-        let entry = self.0.insert(key.clone(), value);
+        let entry = self.values.insert(key.clone(), value);
+        self.provenance.remove(&key);
 
         if entry.is_some() {
             warn!("Duplicate key '{key}'");
         }
     }
 
+    /// Insert a value read from the given location of a source file.
+    pub fn insert_with_provenance(
+        &mut self,
+        target_key: impl Into<String>,
+        target_value: Option<impl Into<String>>,
+        provenance: Provenance,
+    ) {
+        let key = target_key.into();
+        self.insert(key.clone(), target_value);
+        self.provenance.insert(key, provenance);
+    }
+
     pub fn remove(&mut self, target_key: impl Into<String>) {
         let key = target_key.into();
-        let entry = self.0.remove(&key);
+        let entry = self.values.remove(&key);
+        self.provenance.remove(&key);
 
         if let Some(entry) = entry {
             warn!("Key not supported: '{key}', removing value: '{entry:#?}'",);
         }
     }
 
-    /// Add required target tags for processing eBilanz.
-    pub fn add_required_tags(&mut self) {
-        self.insert("Verfahren", Some("ElsterBilanz"));
-        self.insert("DatenArt", Some("Bilanz"));
+    /// Convert into tags sorted by name.
+    pub fn into_vec(self) -> Vec<Tag> {
+        let mut tags = self
+            .values
+            .into_iter()
+            .map(|(name, value)| Tag::new(name, value))
+            .collect::<Vec<_>>();
+        tags.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        tags
+    }
+
+    /// Add required target tags of the transfer header for processing the
+    /// given tax type.
+    pub fn add_required_tags(&mut self, tax_type: TaxType) {
+        self.insert("Verfahren", Some(tax_type.procedure()));
+        self.insert("DatenArt", Some(tax_type.as_str()));
         self.insert("Vorgang", Some("send-Auth"));
         self.insert("HerstellerID", Some("00000"));
         self.insert("Kompression", Some("GZIP"));
@@ -0,0 +1,411 @@
+//! Read arcs from the linkbases of a taxonomy.
+
+use anyhow::anyhow;
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+use std::{collections::HashMap, io::BufRead, str};
+
+/// The role of the standard label of a concept.
+const STANDARD_LABEL_ROLE: &str = "http://www.xbrl.org/2003/role/label";
+
+/// An arc between two concepts of an extended link.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Arc {
+    /// The role of the extended link, e.g.
+    /// `http://www.xbrl.de/taxonomies/de-gaap-ci/role/balanceSheet`.
+    pub role: String,
+    /// The local name of the arc element, e.g. `calculationArc`.
+    pub kind: String,
+    pub from: String,
+    pub to: String,
+    pub order: Option<String>,
+    pub weight: Option<String>,
+}
+
+/// Read all arcs from a linkbase.
+///
+/// The locators of each extended link are resolved to concept names such
+/// that `from` and `to` of the returned arcs are concepts, e.g.
+/// `de-gaap-ci:bs.ass`.
+pub fn read_arcs<R>(reader: &mut Reader<R>) -> Result<Vec<Arc>, anyhow::Error>
+where
+    R: std::io::Read + BufRead,
+{
+    let mut buf = Vec::new();
+    let mut arcs = vec![];
+    let mut role = String::new();
+    let mut locators = HashMap::new();
+    let mut link_arcs = vec![];
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => {
+                let local_name = tag.local_name();
+                let local_name = str::from_utf8(local_name.as_ref())?;
+
+                if local_name.ends_with("Link") {
+                    role = get_attribute(&tag, "xlink:role")?.unwrap_or_default();
+                } else if local_name == "loc" {
+                    let label = get_attribute(&tag, "xlink:label")?;
+                    let href = get_attribute(&tag, "xlink:href")?;
+
+                    if let (Some(label), Some(href)) = (label, href) {
+                        if let Some(concept) = concept_from_href(&href) {
+                            locators.insert(label, concept);
+                        }
+                    }
+                } else if local_name.ends_with("Arc") {
+                    let from = get_attribute(&tag, "xlink:from")?;
+                    let to = get_attribute(&tag, "xlink:to")?;
+
+                    if let (Some(from), Some(to)) = (from, to) {
+                        link_arcs.push(Arc {
+                            role: role.clone(),
+                            kind: local_name.to_owned(),
+                            from,
+                            to,
+                            order: get_attribute(&tag, "order")?,
+                            weight: get_attribute(&tag, "weight")?,
+                        });
+                    }
+                }
+            }
+            Ok(Event::End(tag)) => {
+                let local_name = tag.local_name();
+                let local_name = str::from_utf8(local_name.as_ref())?;
+
+                if local_name.ends_with("Link") {
+                    // Resolve locator labels when the extended link is complete.
+                    for mut arc in link_arcs.drain(..) {
+                        if let (Some(from), Some(to)) =
+                            (locators.get(&arc.from), locators.get(&arc.to))
+                        {
+                            arc.from = from.clone();
+                            arc.to = to.clone();
+                            arcs.push(arc);
+                        }
+                    }
+
+                    locators.clear();
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(anyhow!("Can't parse linkbase: {err}"));
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(arcs)
+}
+
+/// Read the fiscal requirements of the concepts from a reference linkbase,
+/// e.g. `Summenmussfeld` for `de-gaap-ci:bs.ass`.
+///
+/// The requirement is the value of `hgbref:fiscalRequirement` of the
+/// reference which is linked to the concept by a `referenceArc`.
+pub fn read_fiscal_requirements<R>(
+    reader: &mut Reader<R>,
+) -> Result<HashMap<String, String>, anyhow::Error>
+where
+    R: std::io::Read + BufRead,
+{
+    let mut buf = Vec::new();
+    let mut requirements = HashMap::new();
+    let mut locators = HashMap::new();
+    let mut resources = HashMap::new();
+    let mut link_arcs = vec![];
+    let mut resource = None;
+    let mut in_requirement = false;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => {
+                let local_name = tag.local_name();
+
+                match local_name.as_ref() {
+                    b"loc" => {
+                        let label = get_attribute(&tag, "xlink:label")?;
+                        let href = get_attribute(&tag, "xlink:href")?;
+
+                        if let (Some(label), Some(href)) = (label, href) {
+                            if let Some(concept) = concept_from_href(&href) {
+                                locators.insert(label, concept);
+                            }
+                        }
+                    }
+                    b"referenceArc" => {
+                        let from = get_attribute(&tag, "xlink:from")?;
+                        let to = get_attribute(&tag, "xlink:to")?;
+
+                        if let (Some(from), Some(to)) = (from, to) {
+                            link_arcs.push((from, to));
+                        }
+                    }
+                    b"reference" => resource = get_attribute(&tag, "xlink:label")?,
+                    b"fiscalRequirement" => in_requirement = true,
+                    _ => (),
+                }
+            }
+            Ok(Event::Text(text)) if in_requirement => {
+                if let Some(label) = &resource {
+                    resources.insert(label.clone(), text.unescape()?.into_owned());
+                }
+            }
+            Ok(Event::End(tag)) => match tag.local_name().as_ref() {
+                b"fiscalRequirement" => in_requirement = false,
+                b"reference" => resource = None,
+                b"referenceLink" => {
+                    // Resolve locator and resource labels when the extended
+                    // link is complete.
+                    for (from, to) in link_arcs.drain(..) {
+                        if let (Some(concept), Some(requirement)) =
+                            (locators.get(&from), resources.get(&to))
+                        {
+                            requirements.insert(concept.clone(), requirement.clone());
+                        }
+                    }
+
+                    locators.clear();
+                    resources.clear();
+                }
+                _ => (),
+            },
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(anyhow!("Can't parse linkbase: {err}"));
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(requirements)
+}
+
+/// Read the standard labels of the concepts in the given language from a
+/// label linkbase, e.g. `Bilanzsumme, Summe Aktiva` for `de-gaap-ci:bs.ass`.
+///
+/// Labels of other roles like `terseLabel` or `documentation` are skipped.
+pub fn read_labels<R>(
+    reader: &mut Reader<R>,
+    lang: &str,
+) -> Result<HashMap<String, String>, anyhow::Error>
+where
+    R: std::io::Read + BufRead,
+{
+    let mut buf = Vec::new();
+    let mut labels = HashMap::new();
+    let mut locators = HashMap::new();
+    let mut resources = HashMap::new();
+    let mut link_arcs = vec![];
+    let mut resource = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => {
+                let local_name = tag.local_name();
+
+                match local_name.as_ref() {
+                    b"loc" => {
+                        let label = get_attribute(&tag, "xlink:label")?;
+                        let href = get_attribute(&tag, "xlink:href")?;
+
+                        if let (Some(label), Some(href)) = (label, href) {
+                            if let Some(concept) = concept_from_href(&href) {
+                                locators.insert(label, concept);
+                            }
+                        }
+                    }
+                    b"labelArc" => {
+                        let from = get_attribute(&tag, "xlink:from")?;
+                        let to = get_attribute(&tag, "xlink:to")?;
+
+                        if let (Some(from), Some(to)) = (from, to) {
+                            link_arcs.push((from, to));
+                        }
+                    }
+                    b"label" => {
+                        let role = get_attribute(&tag, "xlink:role")?;
+                        let label_lang = get_attribute(&tag, "xml:lang")?;
+
+                        resource = if role.as_deref() == Some(STANDARD_LABEL_ROLE)
+                            && label_lang.as_deref() == Some(lang)
+                        {
+                            get_attribute(&tag, "xlink:label")?
+                        } else {
+                            None
+                        };
+                    }
+                    _ => (),
+                }
+            }
+            Ok(Event::Text(text)) => {
+                if let Some(label) = resource.take() {
+                    resources.insert(label, text.unescape()?.into_owned());
+                }
+            }
+            Ok(Event::End(tag)) => match tag.local_name().as_ref() {
+                b"label" => resource = None,
+                b"labelLink" => {
+                    // Resolve locator and resource labels when the extended
+                    // link is complete.
+                    for (from, to) in link_arcs.drain(..) {
+                        if let (Some(concept), Some(label)) =
+                            (locators.get(&from), resources.get(&to))
+                        {
+                            labels.insert(concept.clone(), label.clone());
+                        }
+                    }
+
+                    locators.clear();
+                    resources.clear();
+                }
+                _ => (),
+            },
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(anyhow!("Can't parse linkbase: {err}"));
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(labels)
+}
+
+/// Get the unescaped value of an attribute.
+pub(crate) fn get_attribute(tag: &BytesStart, key: &str) -> Result<Option<String>, anyhow::Error> {
+    for attribute in tag.attributes() {
+        let attribute = attribute?;
+
+        if attribute.key.as_ref() == key.as_bytes() {
+            return Ok(Some(attribute.unescape_value()?.into_owned()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Convert the href of a locator to a concept name, e.g.
+/// `de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass` to `de-gaap-ci:bs.ass`.
+pub(crate) fn concept_from_href(href: &str) -> Option<String> {
+    let (_, id) = href.rsplit_once('#')?;
+    let (prefix, name) = id.split_once('_')?;
+
+    Some(format!("{prefix}:{name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concept_from_href() {
+        assert_eq!(
+            concept_from_href("de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass.fixAss"),
+            Some(String::from("de-gaap-ci:bs.ass.fixAss"))
+        );
+        assert_eq!(concept_from_href("de-gaap-ci-2022-05-02.xsd"), None);
+    }
+
+    #[test]
+    fn test_read_arcs() {
+        let xml = r#"
+            <linkbase xmlns="http://www.xbrl.org/2003/linkbase">
+                <calculationLink xlink:role="http://www.xbrl.de/taxonomies/de-gaap-ci/role/balanceSheet" xlink:type="extended">
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass" xlink:label="de-gaap-ci_bs.ass"/>
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass.fixAss" xlink:label="de-gaap-ci_bs.ass.fixAss"/>
+                    <calculationArc xlink:from="de-gaap-ci_bs.ass" xlink:to="de-gaap-ci_bs.ass.fixAss" order="3" weight="1" xlink:type="arc"/>
+                </calculationLink>
+            </linkbase>
+        "#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let arcs = read_arcs(&mut reader).unwrap();
+
+        assert_eq!(
+            arcs,
+            vec![Arc {
+                role: String::from("http://www.xbrl.de/taxonomies/de-gaap-ci/role/balanceSheet"),
+                kind: String::from("calculationArc"),
+                from: String::from("de-gaap-ci:bs.ass"),
+                to: String::from("de-gaap-ci:bs.ass.fixAss"),
+                order: Some(String::from("3")),
+                weight: Some(String::from("1")),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_fiscal_requirements() {
+        let xml = r#"
+            <link:linkbase xmlns:hgbref="http://www.xbrl.de/taxonomies/de-ref-2010-02-19" xmlns:link="http://www.xbrl.org/2003/linkbase">
+                <referenceLink xmlns="http://www.xbrl.org/2003/linkbase" xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass" xlink:label="de-gaap-ci_bs.ass"/>
+                    <referenceArc xlink:type="arc" xlink:from="de-gaap-ci_bs.ass" xlink:to="reference_de-gaap-ci_bs.ass"/>
+                    <reference xlink:type="resource" xlink:label="reference_de-gaap-ci_bs.ass">
+                        <hgbref:legalFormKSt>true</hgbref:legalFormKSt>
+                        <hgbref:fiscalRequirement>Summenmussfeld</hgbref:fiscalRequirement>
+                    </reference>
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass.accountingConvenience" xlink:label="de-gaap-ci_bs.ass.accountingConvenience"/>
+                    <referenceArc xlink:type="arc" xlink:from="de-gaap-ci_bs.ass.accountingConvenience" xlink:to="reference_de-gaap-ci_bs.ass.accountingConvenience"/>
+                    <reference xlink:type="resource" xlink:label="reference_de-gaap-ci_bs.ass.accountingConvenience">
+                        <hgbref:legalFormKSt>true</hgbref:legalFormKSt>
+                    </reference>
+                </referenceLink>
+            </link:linkbase>
+        "#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let requirements = read_fiscal_requirements(&mut reader).unwrap();
+
+        assert_eq!(
+            requirements,
+            HashMap::from([(
+                String::from("de-gaap-ci:bs.ass"),
+                String::from("Summenmussfeld")
+            )])
+        );
+    }
+
+    #[test]
+    fn test_read_labels() {
+        let xml = r#"
+            <linkbase xmlns="http://www.xbrl.org/2003/linkbase">
+                <labelLink xlink:type="extended" xlink:role="http://www.xbrl.org/2003/role/link">
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass" xlink:label="de-gaap-ci_bs.ass"/>
+                    <labelArc xlink:from="de-gaap-ci_bs.ass" xlink:to="label_de-gaap-ci_bs.ass" xlink:type="arc"/>
+                    <label xlink:label="label_de-gaap-ci_bs.ass" xlink:role="http://www.xbrl.org/2003/role/label" xlink:type="resource" xml:lang="de">Bilanzsumme, Summe Aktiva</label>
+                    <labelArc xlink:from="de-gaap-ci_bs.ass" xlink:to="label_de-gaap-ci_bs.ass_1" xlink:type="arc"/>
+                    <label xlink:label="label_de-gaap-ci_bs.ass_1" xlink:role="http://www.xbrl.org/2003/role/terseLabel" xlink:type="resource" xml:lang="de">Aktiva</label>
+                    <loc xlink:type="locator" xlink:href="de-gaap-ci-2022-05-02.xsd#de-gaap-ci_bs.ass.fixAss" xlink:label="de-gaap-ci_bs.ass.fixAss"/>
+                    <labelArc xlink:from="de-gaap-ci_bs.ass.fixAss" xlink:to="label_de-gaap-ci_bs.ass.fixAss" xlink:type="arc"/>
+                    <label xlink:label="label_de-gaap-ci_bs.ass.fixAss" xlink:role="http://www.xbrl.org/2003/role/label" xlink:type="resource" xml:lang="en">Fixed assets</label>
+                </labelLink>
+            </linkbase>
+        "#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let labels = read_labels(&mut reader, "de").unwrap();
+
+        assert_eq!(
+            labels,
+            HashMap::from([(
+                String::from("de-gaap-ci:bs.ass"),
+                String::from("Bilanzsumme, Summe Aktiva")
+            )])
+        );
+    }
+}
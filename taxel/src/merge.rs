@@ -0,0 +1,437 @@
+//! Merge partial instances, e.g. the company data and the balance sheet
+//! prepared by different contributors.
+
+use crate::{
+    decimal::Decimal,
+    xbrl::{XbrlElement, CONTEXT_REF},
+    Tags,
+};
+use anyhow::anyhow;
+use std::{collections::HashMap, fmt};
+
+/// Conflicting values of the same fact in several sources.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MergeConflict {
+    pub concept: String,
+    /// The context of the fact; csv files don't have contexts.
+    pub context: Option<String>,
+    /// The values by source in order of priority; the first value is used.
+    pub values: Vec<(String, String)>,
+}
+
+impl fmt::Display for MergeConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Conflicting values for '{}'", self.concept)?;
+
+        if let Some(context) = &self.context {
+            write!(f, " in context '{context}'")?;
+        }
+
+        let values = self
+            .values
+            .iter()
+            .map(|(source, value)| format!("'{value}' ({source})"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, ": {values}")?;
+
+        if let Some((source, value)) = self.values.first() {
+            write!(f, "; using '{value}' from '{source}'")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A fact is identified by concept and context.
+type FactKey = (String, Option<String>);
+
+/// The values of each fact by source in order of priority.
+#[derive(Default)]
+struct Candidates {
+    keys: Vec<FactKey>,
+    values: HashMap<FactKey, Vec<(String, String)>>,
+}
+
+impl Candidates {
+    fn insert(&mut self, key: FactKey, source: &str, value: &str) {
+        let values = self.values.entry(key.clone()).or_insert_with(|| {
+            self.keys.push(key);
+            vec![]
+        });
+
+        if !values.iter().any(|(_, el)| is_same_value(el, value)) {
+            values.push((source.to_owned(), value.to_owned()));
+        }
+    }
+
+    fn insert_fact(&mut self, source: &str, fact: &XbrlElement) {
+        if let (Some(context), Some(value)) = (fact.attribute(CONTEXT_REF), &fact.value) {
+            if fact.has_value() {
+                let key = (fact.name.clone(), Some(context.to_owned()));
+                self.insert(key, source, value);
+            }
+        }
+    }
+
+    fn conflicts(self) -> Vec<MergeConflict> {
+        let mut values = self.values;
+
+        self.keys
+            .into_iter()
+            .filter_map(|key| {
+                let values = values.remove(&key)?;
+
+                (values.len() > 1).then_some(MergeConflict {
+                    concept: key.0,
+                    context: key.1,
+                    values,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Merge the tags of several csv files.
+///
+/// The sources are given in order of priority, i.e. for conflicting values
+/// the value of the first source is used. Empty values don't conflict.
+pub fn merge_tags(sources: &[(&str, Tags)]) -> (Tags, Vec<MergeConflict>) {
+    let mut merged = Tags::new();
+    let mut candidates = Candidates::default();
+
+    for (source, tags) in sources {
+        let mut keys = tags.values.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        for key in keys {
+            let value = tags.values[key].as_deref();
+
+            if let Some(value) = value {
+                candidates.insert((key.clone(), None), source, value);
+            }
+
+            match merged.values.get(key) {
+                Some(Some(_)) => (),
+                _ => {
+                    merged
+                        .values
+                        .insert(key.clone(), value.map(|value| value.to_owned()));
+
+                    match tags.provenance(key) {
+                        Some(provenance) => {
+                            merged.provenance.insert(key.clone(), provenance.clone());
+                        }
+                        None => {
+                            merged.provenance.remove(key);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (merged, candidates.conflicts())
+}
+
+/// Merge several XBRL instances into one instance.
+///
+/// The sources are given in order of priority:
+///
+/// - The first instance is used as base, i.e. its header and document
+///   structure are retained.
+/// - Contexts, units, and facts of the other instances are added if missing.
+///   An error is returned if a context or unit is declared differently with
+///   the same id, e.g. with another period, because its facts would be
+///   attributed to the wrong context.
+/// - Nil facts are filled with the value of the first instance which has a
+///   value; for conflicting values the value of the instance with the highest
+///   priority is used.
+/// - Tuples are only taken from the base, because their rows can't be matched
+///   across instances. An error is returned if another instance contains a
+///   tuple with values which is missing in the base.
+pub fn merge_instances(
+    sources: &[(&str, XbrlElement)],
+) -> Result<(XbrlElement, Vec<MergeConflict>), anyhow::Error> {
+    let ((base_source, base), others) = sources
+        .split_first()
+        .ok_or(anyhow!("Missing instances to merge"))?;
+    let mut merged = base.clone();
+    let mut candidates = Candidates::default();
+
+    let xbrl = merged
+        .find_mut("xbrli:xbrl")
+        .ok_or(anyhow!("Missing element 'xbrli:xbrl' in '{base_source}'"))?;
+
+    for fact in &xbrl.children {
+        candidates.insert_fact(base_source, fact);
+    }
+
+    for (source, element) in others {
+        let other = element
+            .find("xbrli:xbrl")
+            .ok_or(anyhow!("Missing element 'xbrli:xbrl' in '{source}'"))?;
+
+        for child in &other.children {
+            if child.name == "xbrli:context" || child.name == "xbrli:unit" {
+                merge_declaration(xbrl, child, source)?;
+            } else if child.is_tuple() {
+                if child.has_values() && !xbrl.children.contains(child) {
+                    return Err(anyhow!(
+                        "Can't merge tuple '{}' of '{source}': tuples are only taken from the first file '{base_source}'",
+                        child.name
+                    ));
+                }
+            } else if let Some(context) = child.attribute(CONTEXT_REF) {
+                candidates.insert_fact(source, child);
+
+                match xbrl.children.iter_mut().find(|fact| {
+                    fact.name == child.name && fact.attribute(CONTEXT_REF) == Some(context)
+                }) {
+                    Some(fact) if fact.has_value() => (),
+                    Some(fact) => {
+                        if child.has_value() {
+                            *fact = child.clone();
+                        }
+                    }
+                    None => xbrl.children.push(child.clone()),
+                }
+            }
+        }
+    }
+
+    Ok((merged, candidates.conflicts()))
+}
+
+/// Add a context or unit if it is not declared yet.
+fn merge_declaration(
+    xbrl: &mut XbrlElement,
+    declaration: &XbrlElement,
+    source: &str,
+) -> Result<(), anyhow::Error> {
+    let id = declaration.attribute("id");
+    let existing = xbrl
+        .children
+        .iter()
+        .find(|child| child.name == declaration.name && child.attribute("id") == id);
+
+    match existing {
+        Some(existing) if existing == declaration => (),
+        Some(_) => {
+            return Err(anyhow!(
+                "Can't merge '{}' with id '{}' of '{source}': it differs from the declaration with the same id of a file with higher priority",
+                declaration.name,
+                id.unwrap_or_default()
+            ))
+        }
+        None => xbrl.insert_declaration(declaration.clone()),
+    }
+
+    Ok(())
+}
+
+/// Numeric values are compared by value, e.g. `100.0` equals `100.00`.
+fn is_same_value(lhs: &str, rhs: &str) -> bool {
+    match (lhs.parse::<Decimal>(), rhs.parse::<Decimal>()) {
+        (Ok(lhs), Ok(rhs)) => lhs == rhs,
+        _ => lhs == rhs,
+    }
+}
+
+impl XbrlElement {
+    /// Check if the element is a tuple, i.e. it groups facts but is not a fact
+    /// itself.
+    fn is_tuple(&self) -> bool {
+        self.attribute(CONTEXT_REF).is_none()
+            && self
+                .children
+                .iter()
+                .any(|child| child.attribute(CONTEXT_REF).is_some() || child.is_tuple())
+    }
+
+    /// Check if any fact of the element has a value.
+    fn has_values(&self) -> bool {
+        self.has_value() || self.children.iter().any(|child| child.has_values())
+    }
+
+    fn find(&self, name: &str) -> Option<&XbrlElement> {
+        if self.name == name {
+            return Some(self);
+        }
+
+        self.children.iter().find_map(|child| child.find(name))
+    }
+
+    fn find_mut(&mut self, name: &str) -> Option<&mut XbrlElement> {
+        if self.name == name {
+            return Some(self);
+        }
+
+        self.children
+            .iter_mut()
+            .find_map(|child| child.find_mut(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{remove_formatting, Reader, Writer};
+    use std::{io::Cursor, str};
+
+    fn parse(xml: &str) -> XbrlElement {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        XbrlElement::parse(&mut reader).unwrap()
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let mut company = Tags::new();
+        company.insert("de-gcd:genInfo.company.id.name", Some("Landwirt GmbH"));
+        company.insert("de-gaap-ci:bs.ass", None::<String>);
+        let mut balance_sheet = Tags::new();
+        balance_sheet.insert("de-gcd:genInfo.company.id.name", Some("Landwirt"));
+        balance_sheet.insert("de-gaap-ci:bs.ass", Some("300.00"));
+
+        let (merged, conflicts) =
+            merge_tags(&[("company.csv", company), ("balance.csv", balance_sheet)]);
+
+        assert_eq!(
+            merged.get("de-gcd:genInfo.company.id.name"),
+            Some(&Some(String::from("Landwirt GmbH")))
+        );
+        assert_eq!(
+            merged.get("de-gaap-ci:bs.ass"),
+            Some(&Some(String::from("300.00")))
+        );
+        assert_eq!(
+            conflicts,
+            vec![MergeConflict {
+                concept: String::from("de-gcd:genInfo.company.id.name"),
+                context: None,
+                values: vec![
+                    (String::from("company.csv"), String::from("Landwirt GmbH")),
+                    (String::from("balance.csv"), String::from("Landwirt")),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_merge_instances() {
+        let company = parse(
+            r#"
+            <xbrli:xbrl>
+                <xbrli:context id="D-AKTJAHR"></xbrli:context>
+                <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Landwirt GmbH</de-gcd:genInfo.company.id.name>
+                <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" xsi:nil="true"/>
+            </xbrli:xbrl>
+        "#,
+        );
+        let balance_sheet = parse(
+            r#"
+            <xbrli:xbrl>
+                <xbrli:context id="D-AKTJAHR"></xbrli:context>
+                <xbrli:context id="I-AKTJAHR"></xbrli:context>
+                <xbrli:unit id="EUR"><xbrli:measure>iso4217:EUR</xbrli:measure></xbrli:unit>
+                <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Landwirt</de-gcd:genInfo.company.id.name>
+                <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                <de-gaap-ci:bs.ass.fixAss contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass.fixAss>
+            </xbrli:xbrl>
+        "#,
+        );
+        let expected_xml = r#"
+            <xbrli:xbrl>
+                <xbrli:context id="D-AKTJAHR"/>
+                <xbrli:context id="I-AKTJAHR"/>
+                <xbrli:unit id="EUR"><xbrli:measure>iso4217:EUR</xbrli:measure></xbrli:unit>
+                <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Landwirt GmbH</de-gcd:genInfo.company.id.name>
+                <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                <de-gaap-ci:bs.ass.fixAss contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass.fixAss>
+            </xbrli:xbrl>
+        "#;
+
+        let (merged, conflicts) =
+            merge_instances(&[("company.xml", company), ("balance.xml", balance_sheet)]).unwrap();
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        merged.serialize(&mut writer).unwrap();
+        let actual = writer.into_inner().into_inner();
+
+        assert_eq!(
+            str::from_utf8(&actual).unwrap(),
+            remove_formatting(expected_xml).unwrap()
+        );
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Conflicting values for 'de-gcd:genInfo.company.id.name' in context 'D-AKTJAHR': 'Landwirt GmbH' (company.xml), 'Landwirt' (balance.xml); using 'Landwirt GmbH' from 'company.xml'"
+        );
+    }
+
+    #[test]
+    fn test_merge_instances_declarations() {
+        let context = |date: &str| {
+            parse(&format!(
+                r#"
+                <xbrli:xbrl>
+                    <xbrli:context id="I-AKTJAHR">
+                        <xbrli:period><xbrli:instant>{date}</xbrli:instant></xbrli:period>
+                    </xbrli:context>
+                    <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                </xbrli:xbrl>
+            "#
+            ))
+        };
+        let current = context("2023-12-31");
+
+        // Identical declarations are merged.
+        let (merged, _) =
+            merge_instances(&[("a.xml", current.clone()), ("b.xml", current.clone())]).unwrap();
+        assert_eq!(merged, current);
+
+        let err =
+            merge_instances(&[("a.xml", current), ("b.xml", context("2022-12-31"))]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can't merge 'xbrli:context' with id 'I-AKTJAHR' of 'b.xml': it differs from the declaration with the same id of a file with higher priority"
+        );
+    }
+
+    #[test]
+    fn test_merge_instances_tuples() {
+        let tuple = |value: &str| {
+            format!(
+                r#"
+                <xbrli:xbrl>
+                    <xbrli:context id="D-AKTJAHR"></xbrli:context>
+                    <de-gaap-ci:nt.particip.listRow>
+                        <de-gaap-ci:nt.particip.listRow.name contextRef="D-AKTJAHR"{value}
+                    </de-gaap-ci:nt.particip.listRow>
+                </xbrli:xbrl>
+            "#
+            )
+        };
+        let empty = parse(&tuple(r#" xsi:nil="true"/>"#));
+        let filled = parse(&tuple(
+            ">Tochter GmbH</de-gaap-ci:nt.particip.listRow.name>",
+        ));
+
+        // Empty tuples of the other instances are ignored.
+        let (merged, conflicts) =
+            merge_instances(&[("filled.xml", filled.clone()), ("empty.xml", empty.clone())])
+                .unwrap();
+        assert_eq!(merged, filled);
+        assert!(conflicts.is_empty());
+
+        // Identical tuples are merged.
+        assert!(merge_instances(&[("a.xml", filled.clone()), ("b.xml", filled.clone())]).is_ok());
+
+        let err = merge_instances(&[("empty.xml", empty), ("filled.xml", filled)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Can't merge tuple 'de-gaap-ci:nt.particip.listRow' of 'filled.xml': tuples are only taken from the first file 'empty.xml'"
+        );
+    }
+}
@@ -0,0 +1,515 @@
+//! Migrate a filing from one taxonomy version to another.
+
+use crate::{
+    taxonomy_version::TAXONOMY_URL, xbrl::XbrlElement, ItemType, TaxonomySchema, TaxonomyVersion,
+};
+use anyhow::anyhow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The differences between two taxonomy versions.
+///
+/// The differences are derived by comparing the concepts of both taxonomies:
+///
+/// - Concepts which are only available in the old taxonomy are removed, and
+///   concepts which are only available in the new taxonomy are added.
+/// - A removed and an added concept with the same item type and the same last
+///   name segment (the concept may have been moved, e.g. `bs.ass.misc.other`
+///   to `bs.ass.other.other`) are suggested as renamed if no other removed or
+///   added concept matches.
+/// - A remaining removed and added concept with the same item type and the
+///   same parent (the concept may have been renamed in place) are suggested
+///   as renamed likewise.
+/// - Suggestions are not applied; only the explicit renames of
+///   [`Migration::with_renames`], e.g. confirmed suggestions, are.
+/// - Concepts which are a `Mussfeld` or `Summenmussfeld` in the new taxonomy
+///   but not in the old one are newly mandatory.
+#[derive(Debug, PartialEq)]
+pub struct Migration {
+    pub from: &'static TaxonomyVersion,
+    pub to: &'static TaxonomyVersion,
+    /// The renamed concepts by their old name.
+    pub renamed: BTreeMap<String, String>,
+    /// The suggested renames by their old name, which are not applied; the
+    /// old concept is removed and the new concept is added.
+    pub suggested: BTreeMap<String, String>,
+    pub removed: BTreeSet<String>,
+    pub added: BTreeSet<String>,
+    pub newly_mandatory: BTreeSet<String>,
+}
+
+impl Migration {
+    /// Compare the concepts of the old and the new taxonomy.
+    pub fn new(
+        from: &'static TaxonomyVersion,
+        to: &'static TaxonomyVersion,
+        from_schema: &TaxonomySchema,
+        to_schema: &TaxonomySchema,
+    ) -> Self {
+        Self::compare(from, to, from_schema, to_schema, &BTreeMap::new())
+    }
+
+    /// Compare the concepts of the old and the new taxonomy like
+    /// [`Migration::new`], and apply the given renames, e.g. confirmed
+    /// suggestions.
+    ///
+    /// Each rename must pair a concept which is only available in the old
+    /// taxonomy with a concept which is only available in the new taxonomy.
+    pub fn with_renames(
+        from: &'static TaxonomyVersion,
+        to: &'static TaxonomyVersion,
+        from_schema: &TaxonomySchema,
+        to_schema: &TaxonomySchema,
+        renames: &BTreeMap<String, String>,
+    ) -> Result<Self, anyhow::Error> {
+        for (old_concept, new_concept) in renames {
+            if from_schema.concept_types.get(old_concept).is_none()
+                || to_schema.concept_types.get(old_concept).is_some()
+            {
+                return Err(anyhow!(
+                    "Can't rename '{old_concept}': not a removed concept of taxonomy version {}",
+                    from.version
+                ));
+            }
+
+            if to_schema.concept_types.get(new_concept).is_none()
+                || from_schema.concept_types.get(new_concept).is_some()
+            {
+                return Err(anyhow!(
+                    "Can't rename '{old_concept}' to '{new_concept}': not an added concept of taxonomy version {}",
+                    to.version
+                ));
+            }
+        }
+
+        Ok(Self::compare(from, to, from_schema, to_schema, renames))
+    }
+
+    fn compare(
+        from: &'static TaxonomyVersion,
+        to: &'static TaxonomyVersion,
+        from_schema: &TaxonomySchema,
+        to_schema: &TaxonomySchema,
+        renames: &BTreeMap<String, String>,
+    ) -> Self {
+        let old_concepts: HashMap<&str, ItemType> = from_schema.concept_types.iter().collect();
+        let new_concepts: HashMap<&str, ItemType> = to_schema.concept_types.iter().collect();
+
+        let mut removed: BTreeSet<&str> = old_concepts
+            .keys()
+            .filter(|concept| !new_concepts.contains_key(*concept))
+            .copied()
+            .collect();
+        let mut added: BTreeSet<&str> = new_concepts
+            .keys()
+            .filter(|concept| !old_concepts.contains_key(*concept))
+            .copied()
+            .collect();
+        let mut renamed = BTreeMap::new();
+
+        for (old_concept, new_concept) in renames {
+            removed.remove(old_concept.as_str());
+            added.remove(new_concept.as_str());
+            renamed.insert(old_concept.clone(), new_concept.clone());
+        }
+
+        let moved = unique_matches(&removed, &old_concepts, &added, &new_concepts, leaf_key);
+        let remaining_removed = removed
+            .iter()
+            .filter(|concept| !moved.iter().any(|(old_concept, _)| old_concept == *concept))
+            .copied()
+            .collect();
+        let remaining_added = added
+            .iter()
+            .filter(|concept| !moved.iter().any(|(_, new_concept)| new_concept == *concept))
+            .copied()
+            .collect();
+        let renamed_in_place = unique_matches(
+            &remaining_removed,
+            &old_concepts,
+            &remaining_added,
+            &new_concepts,
+            parent_key,
+        );
+        let suggested = moved
+            .into_iter()
+            .chain(renamed_in_place)
+            .map(|(old_concept, new_concept)| (old_concept.to_owned(), new_concept.to_owned()))
+            .collect();
+
+        let old_names: HashMap<&str, &str> = renamed
+            .iter()
+            .map(|(old_concept, new_concept)| (new_concept.as_str(), old_concept.as_str()))
+            .collect();
+        let newly_mandatory = new_concepts
+            .keys()
+            .filter(|concept| to_schema.is_mandatory(concept))
+            .filter(|concept| {
+                let old_concept = old_names.get(*concept).unwrap_or(concept);
+                !from_schema.is_mandatory(old_concept)
+            })
+            .map(|concept| concept.to_string())
+            .collect();
+
+        Self {
+            from,
+            to,
+            renamed,
+            suggested,
+            removed: removed.into_iter().map(String::from).collect(),
+            added: added.into_iter().map(String::from).collect(),
+            newly_mandatory,
+        }
+    }
+
+    /// The name of the concept in the new taxonomy; `None` if the concept was
+    /// removed.
+    ///
+    /// Names which aren't concepts of the old taxonomy, e.g. `Empfaenger`, are
+    /// returned unchanged.
+    pub fn concept<'a>(&'a self, concept: &'a str) -> Option<&'a str> {
+        if self.removed.contains(concept) {
+            return None;
+        }
+
+        Some(self.renamed.get(concept).map_or(concept, String::as_str))
+    }
+
+    /// Replace the release date of the old taxonomy in the namespaces and
+    /// schema references of an attribute value.
+    fn migrate_url(&self, value: &str) -> Option<String> {
+        if !value.contains(TAXONOMY_URL) {
+            return None;
+        }
+
+        Some(value.replace(
+            &format!("-{}", self.from.date),
+            &format!("-{}", self.to.date),
+        ))
+    }
+}
+
+impl XbrlElement {
+    /// Migrate the filing to the new taxonomy version of the migration.
+    ///
+    /// - The namespaces, schema references, and the version of
+    ///   `ebilanz:EBilanz` are updated.
+    /// - Facts and explicit members of renamed concepts are renamed.
+    /// - Facts of removed concepts are discarded.
+    ///
+    /// Returns the removed concepts of the filing.
+    pub fn migrate(&mut self, migration: &Migration) -> BTreeSet<String> {
+        let mut removed = BTreeSet::new();
+        self.migrate_element(migration, &mut removed);
+        removed
+    }
+
+    fn migrate_element(&mut self, migration: &Migration, removed: &mut BTreeSet<String>) {
+        for attribute in &mut self.attributes {
+            if let Some(value) = migration.migrate_url(&attribute.value) {
+                attribute.value = value;
+            }
+        }
+
+        if self.name == "ebilanz:EBilanz" {
+            self.set_attribute("version", migration.to.ebilanz_version);
+        }
+
+        if self.name == "xbrldi:explicitMember" {
+            if let Some(dimension) = self.attribute("dimension") {
+                if let Some(new_dimension) = migration.renamed.get(dimension) {
+                    let new_dimension = new_dimension.clone();
+                    self.set_attribute("dimension", &new_dimension);
+                }
+            }
+
+            if let Some(member) = &self.value {
+                if let Some(new_member) = migration.renamed.get(member.trim()) {
+                    self.value = Some(new_member.clone());
+                }
+            }
+        }
+
+        if let Some(new_name) = migration.renamed.get(&self.name) {
+            self.name = new_name.clone();
+        }
+
+        let children = std::mem::take(&mut self.children);
+
+        for mut child in children {
+            if migration.removed.contains(&child.name) {
+                removed.insert(child.name);
+                continue;
+            }
+
+            child.migrate_element(migration, removed);
+            self.children.push(child);
+        }
+    }
+}
+
+/// The prefix, item type, and last name segment of a concept, e.g.
+/// `de-gaap-ci`, `Monetary`, and `other` for `de-gaap-ci:bs.ass.misc.other`.
+fn leaf_key(concept: &str, item_type: ItemType) -> (String, ItemType, String) {
+    let (prefix, name) = concept.split_once(':').unwrap_or(("", concept));
+    let leaf = name.rsplit_once('.').map_or(name, |(_, leaf)| leaf);
+
+    (prefix.to_owned(), item_type, leaf.to_owned())
+}
+
+/// The prefix, item type, and parent name of a concept, e.g. `de-gaap-ci`,
+/// `Monetary`, and `bs.ass.misc` for `de-gaap-ci:bs.ass.misc.other`.
+fn parent_key(concept: &str, item_type: ItemType) -> (String, ItemType, String) {
+    let (prefix, name) = concept.split_once(':').unwrap_or(("", concept));
+    let parent = name.rsplit_once('.').map_or("", |(parent, _)| parent);
+
+    (prefix.to_owned(), item_type, parent.to_owned())
+}
+
+type ConceptKey = fn(&str, ItemType) -> (String, ItemType, String);
+
+/// Pair the removed and added concepts whose key is unique on both sides.
+fn unique_matches<'a>(
+    removed: &BTreeSet<&'a str>,
+    old_concepts: &HashMap<&str, ItemType>,
+    added: &BTreeSet<&'a str>,
+    new_concepts: &HashMap<&str, ItemType>,
+    key: ConceptKey,
+) -> Vec<(&'a str, &'a str)> {
+    let group = |concepts: &BTreeSet<&'a str>, item_types: &HashMap<&str, ItemType>| {
+        let mut groups: HashMap<_, Vec<&'a str>> = HashMap::new();
+
+        for concept in concepts {
+            groups
+                .entry(key(concept, item_types[concept]))
+                .or_default()
+                .push(*concept);
+        }
+
+        groups
+    };
+    let removed = group(removed, old_concepts);
+    let added = group(added, new_concepts);
+
+    removed
+        .iter()
+        .filter_map(
+            |(key, old)| match (old.as_slice(), added.get(key)?.as_slice()) {
+                ([old_concept], [new_concept]) => Some((*old_concept, *new_concept)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    fn schema(concepts: &[(&str, ItemType)], mandatory: &[&str]) -> TaxonomySchema {
+        let mut schema = TaxonomySchema::default();
+
+        for (concept, item_type) in concepts {
+            schema.concept_types.insert(*concept, *item_type);
+        }
+
+        for concept in mandatory {
+            schema
+                .fiscal_requirements
+                .insert(concept.to_string(), String::from("Mussfeld"));
+        }
+
+        schema
+    }
+
+    fn schemas() -> (TaxonomySchema, TaxonomySchema) {
+        let from_schema = schema(
+            &[
+                ("de-gaap-ci:bs.ass", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.misc.other", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.prepaid", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.obsolete", ItemType::NonNumeric),
+                ("de-gcd:genInfo.report.id", ItemType::NonNumeric),
+            ],
+            &["de-gaap-ci:bs.ass"],
+        );
+        let to_schema = schema(
+            &[
+                ("de-gaap-ci:bs.ass", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.other.other", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.prepaidExp", ItemType::Monetary),
+                ("de-gaap-ci:bs.ass.deferred", ItemType::Percent),
+                ("de-gcd:genInfo.report.id", ItemType::NonNumeric),
+            ],
+            &[
+                "de-gaap-ci:bs.ass",
+                "de-gaap-ci:bs.ass.deferred",
+                "de-gcd:genInfo.report.id",
+            ],
+        );
+
+        (from_schema, to_schema)
+    }
+
+    fn migration() -> Migration {
+        let (from_schema, to_schema) = schemas();
+
+        Migration::new(
+            TaxonomyVersion::get("6.5").unwrap(),
+            TaxonomyVersion::get("6.6").unwrap(),
+            &from_schema,
+            &to_schema,
+        )
+    }
+
+    /// The migration with the suggested rename of `bs.ass.prepaid`.
+    fn migration_with_renames() -> Migration {
+        let (from_schema, to_schema) = schemas();
+
+        Migration::with_renames(
+            TaxonomyVersion::get("6.5").unwrap(),
+            TaxonomyVersion::get("6.6").unwrap(),
+            &from_schema,
+            &to_schema,
+            &BTreeMap::from([(
+                String::from("de-gaap-ci:bs.ass.prepaid"),
+                String::from("de-gaap-ci:bs.ass.prepaidExp"),
+            )]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migration() {
+        let migration = migration();
+
+        // Moved concepts and concepts with the same parent are only suggested
+        assert!(migration.renamed.is_empty());
+        assert_eq!(
+            migration.suggested,
+            BTreeMap::from([
+                (
+                    String::from("de-gaap-ci:bs.ass.misc.other"),
+                    String::from("de-gaap-ci:bs.ass.other.other")
+                ),
+                (
+                    String::from("de-gaap-ci:bs.ass.prepaid"),
+                    String::from("de-gaap-ci:bs.ass.prepaidExp")
+                )
+            ])
+        );
+        assert_eq!(
+            migration.removed,
+            BTreeSet::from([
+                String::from("de-gaap-ci:bs.ass.misc.other"),
+                String::from("de-gaap-ci:bs.ass.obsolete"),
+                String::from("de-gaap-ci:bs.ass.prepaid")
+            ])
+        );
+        assert_eq!(
+            migration.added,
+            BTreeSet::from([
+                String::from("de-gaap-ci:bs.ass.deferred"),
+                String::from("de-gaap-ci:bs.ass.other.other"),
+                String::from("de-gaap-ci:bs.ass.prepaidExp")
+            ])
+        );
+        assert_eq!(
+            migration.newly_mandatory,
+            BTreeSet::from([
+                String::from("de-gaap-ci:bs.ass.deferred"),
+                String::from("de-gcd:genInfo.report.id")
+            ])
+        );
+        assert_eq!(migration.concept("de-gaap-ci:bs.ass.misc.other"), None);
+        assert_eq!(migration.concept("de-gaap-ci:bs.ass.prepaid"), None);
+        assert_eq!(migration.concept("de-gaap-ci:bs.ass.obsolete"), None);
+        assert_eq!(migration.concept("Empfaenger"), Some("Empfaenger"));
+    }
+
+    #[test]
+    fn test_migration_with_renames() {
+        let migration = migration_with_renames();
+
+        assert_eq!(
+            migration.concept("de-gaap-ci:bs.ass.prepaid"),
+            Some("de-gaap-ci:bs.ass.prepaidExp")
+        );
+        assert_eq!(
+            migration.suggested,
+            BTreeMap::from([(
+                String::from("de-gaap-ci:bs.ass.misc.other"),
+                String::from("de-gaap-ci:bs.ass.other.other")
+            )])
+        );
+        assert_eq!(
+            migration.removed,
+            BTreeSet::from([
+                String::from("de-gaap-ci:bs.ass.misc.other"),
+                String::from("de-gaap-ci:bs.ass.obsolete")
+            ])
+        );
+        assert_eq!(
+            migration.added,
+            BTreeSet::from([
+                String::from("de-gaap-ci:bs.ass.deferred"),
+                String::from("de-gaap-ci:bs.ass.other.other")
+            ])
+        );
+
+        let (from_schema, to_schema) = schemas();
+        let res = Migration::with_renames(
+            TaxonomyVersion::get("6.5").unwrap(),
+            TaxonomyVersion::get("6.6").unwrap(),
+            &from_schema,
+            &to_schema,
+            &BTreeMap::from([(
+                String::from("de-gaap-ci:bs.ass"),
+                String::from("de-gaap-ci:bs.ass.prepaidExp"),
+            )]),
+        );
+        assert_eq!(
+            res.unwrap_err().to_string(),
+            "Can't rename 'de-gaap-ci:bs.ass': not a removed concept of taxonomy version 6.5"
+        );
+    }
+
+    #[test]
+    fn test_migrate_filing() {
+        let xml = r#"<ebilanz:EBilanz version="000001" xmlns:ebilanz="http://rzf.fin-nrw.de/RMS/EBilanz/2016/XMLSchema">
+            <xbrli:xbrl xmlns:de-gaap-ci="http://www.xbrl.de/taxonomies/de-gaap-ci-2021-04-14" xmlns:xbrli="http://www.xbrl.org/2003/instance">
+                <link:schemaRef xlink:type="simple" xlink:href="http://www.xbrl.de/taxonomies/de-gaap-ci-2021-04-14/de-gaap-ci-2021-04-14-shell-fiscal.xsd"/>
+                <de-gaap-ci:bs.ass contextRef="D-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                <de-gaap-ci:bs.ass.prepaid contextRef="D-AKTJAHR" unitRef="EUR" decimals="2">100.00</de-gaap-ci:bs.ass.prepaid>
+                <de-gaap-ci:bs.ass.obsolete contextRef="D-AKTJAHR">Text</de-gaap-ci:bs.ass.obsolete>
+            </xbrli:xbrl>
+        </ebilanz:EBilanz>"#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut element = XbrlElement::parse(&mut reader).unwrap();
+
+        let removed = element.migrate(&migration_with_renames());
+
+        assert_eq!(
+            removed,
+            BTreeSet::from([String::from("de-gaap-ci:bs.ass.obsolete")])
+        );
+        assert_eq!(element.attribute("version"), Some("000002"));
+        assert_eq!(
+            element.children[0].attribute("xmlns:de-gaap-ci"),
+            Some("http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02")
+        );
+        assert_eq!(
+            element.schema_refs(),
+            vec![String::from("http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02/de-gaap-ci-2022-05-02-shell-fiscal.xsd")]
+        );
+        assert_eq!(
+            element
+                .facts()
+                .iter()
+                .map(|fact| fact.concept.as_str())
+                .collect::<Vec<_>>(),
+            vec!["de-gaap-ci:bs.ass", "de-gaap-ci:bs.ass.prepaidExp"]
+        );
+    }
+}
@@ -1,6 +1,7 @@
+use crate::{Provenance, Tags};
 use anyhow::anyhow;
 use spreadsheet_ods::{Sheet, Value};
-use std::{collections::HashMap, path::Path};
+use std::path::Path;
 
 #[derive(Debug)]
 struct Cell {
@@ -29,14 +30,17 @@ impl SourceCell {
 const NUM_ROWS: usize = 500;
 
 /// Read target tags from ods file.
-#[allow(dead_code)]
-pub fn read_target_tags_ods(path: &Path) -> Result<HashMap<String, Value>, anyhow::Error> {
-    let mut target_tags = HashMap::new();
+///
+/// Every sheet with the columns `ebilanz_key` and `ebilanz_value` is read, and
+/// the provenance of each value is recorded.
+pub fn read_tags_ods(path: &Path) -> Result<Tags, anyhow::Error> {
+    let mut target_tags = Tags::new();
     let workbook = if path.exists() {
         spreadsheet_ods::read_ods(path)?
     } else {
         return Err(anyhow!("Invalid path: {}", path.display()));
     };
+    let file = path.display().to_string();
     let num_sheets = workbook.num_sheets();
     let mut sheets = vec![];
 
@@ -50,17 +54,14 @@ pub fn read_target_tags_ods(path: &Path) -> Result<HashMap<String, Value>, anyho
         let key_column = source_cell.key.col;
         let value_column = source_cell.value.col;
 
-        for i in 1..NUM_ROWS {
-            let key = sheet.value(i as u32, key_column);
-            let value = sheet.value(i as u32, value_column);
-
-            match (key, value) {
-                (Value::Empty, Value::Empty) => continue,
-                (key, value) => {
-                    if let Some(key) = key.as_str_opt() {
-                        target_tags.insert(key.to_owned(), value.to_owned());
-                    }
-                }
+        for i in (source_cell.key.row + 1)..NUM_ROWS as u32 {
+            let key = sheet.value(i, key_column);
+            let value = sheet.value(i, value_column);
+
+            if let Some(key) = key.as_str_opt() {
+                let provenance = Provenance::new(&file, i as u64 + 1, value_column as u64 + 1)
+                    .with_sheet(sheet.name().as_str());
+                target_tags.insert_with_provenance(key, to_text(value), provenance);
             }
         }
     }
@@ -68,6 +69,20 @@ pub fn read_target_tags_ods(path: &Path) -> Result<HashMap<String, Value>, anyho
     Ok(target_tags)
 }
 
+/// Convert the value of a cell to text; empty cells have no value.
+fn to_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Empty => None,
+        Value::Boolean(value) => Some(value.to_string()),
+        Value::Number(value) | Value::Percentage(value) | Value::Currency(value, _) => {
+            Some(value.to_string())
+        }
+        Value::DateTime(value) => Some(value.format("%Y-%m-%d").to_string()),
+        value => value.as_str_opt().map(|value| value.to_owned()),
+    }
+    .filter(|value| !value.is_empty())
+}
+
 fn find_source_cell(sheet: &Sheet) -> Result<SourceCell, anyhow::Error> {
     let mut key_cell = None;
     let mut value_cell = None;
@@ -101,3 +116,44 @@ fn find_source_cell(sheet: &Sheet) -> Result<SourceCell, anyhow::Error> {
         (None, None) => Err(anyhow!("Missing columns `ebilanz_key` and `ebilanz_value`")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spreadsheet_ods::WorkBook;
+    use std::env;
+
+    #[test]
+    fn test_read_tags_ods() {
+        let path = env::temp_dir().join("taxel_test_read_tags_ods.ods");
+        let mut sheet = Sheet::new("Bilanz");
+        sheet.set_value(0, 0, "ebilanz_key");
+        sheet.set_value(0, 1, "ebilanz_value");
+        sheet.set_value(1, 0, "de-gcd:genInfo.company.id.name");
+        sheet.set_value(1, 1, "Landwirt GmbH");
+        sheet.set_value(2, 0, "de-gaap-ci:bs.ass");
+        sheet.set_value(2, 1, 300.5);
+        sheet.set_value(3, 0, "de-gaap-ci:bs.eqLiab");
+        let mut workbook = WorkBook::new_empty();
+        workbook.push_sheet(sheet);
+        spreadsheet_ods::write_ods(&mut workbook, &path).unwrap();
+
+        let target_tags = read_tags_ods(&path).unwrap();
+
+        assert_eq!(
+            target_tags.get("de-gcd:genInfo.company.id.name"),
+            Some(&Some(String::from("Landwirt GmbH")))
+        );
+        assert_eq!(
+            target_tags.get("de-gaap-ci:bs.ass"),
+            Some(&Some(String::from("300.5")))
+        );
+        assert_eq!(target_tags.get("de-gaap-ci:bs.eqLiab"), Some(&None));
+        assert_eq!(
+            target_tags
+                .provenance("de-gaap-ci:bs.ass")
+                .map(|provenance| provenance.to_string()),
+            Some(format!("{}[Bilanz]:3:2", path.display()))
+        );
+    }
+}
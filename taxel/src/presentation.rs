@@ -0,0 +1,171 @@
+//! The parent-child relationships of the presentation linkbases.
+
+use crate::{decimal::Decimal, linkbase::Arc};
+use std::collections::{BTreeMap, HashSet};
+
+#[derive(Debug, PartialEq, Clone)]
+struct Child {
+    concept: String,
+    order: Decimal,
+}
+
+/// The parent-child relationships of the presentation linkbases.
+///
+/// The relationships are grouped by the role of the extended link, e.g.
+/// `http://www.xbrl.de/taxonomies/de-gaap-ci/role/balanceSheet`, and the
+/// concept of the parent.
+#[derive(Debug, Default)]
+pub struct Presentation(BTreeMap<String, BTreeMap<String, Vec<Child>>>);
+
+impl Presentation {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Insert a `presentationArc`; duplicate arcs are ignored.
+    pub fn insert(&mut self, arc: Arc) -> Result<(), anyhow::Error> {
+        let order = arc.order.as_deref().unwrap_or("1").parse()?;
+        let children = self
+            .0
+            .entry(arc.role)
+            .or_default()
+            .entry(arc.from)
+            .or_default();
+
+        if !children.iter().any(|child| child.concept == arc.to) {
+            let index = children
+                .iter()
+                .position(|child| child.order > order)
+                .unwrap_or(children.len());
+            children.insert(
+                index,
+                Child {
+                    concept: arc.to,
+                    order,
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// The number of parent-child relationships.
+    pub fn len(&self) -> usize {
+        self.0
+            .values()
+            .flat_map(|parents| parents.values())
+            .map(|children| children.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The roles of the extended links, i.e. the networks of the presentation
+    /// hierarchy.
+    pub fn roles(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(|role| role.as_str())
+    }
+
+    /// The concepts without parent in the given role.
+    pub fn roots(&self, role: &str) -> Vec<&str> {
+        let Some(parents) = self.0.get(role) else {
+            return vec![];
+        };
+        let children = parents
+            .values()
+            .flatten()
+            .map(|child| child.concept.as_str())
+            .collect::<HashSet<_>>();
+
+        parents
+            .keys()
+            .map(|parent| parent.as_str())
+            .filter(|parent| !children.contains(parent))
+            .collect()
+    }
+
+    /// The children of a concept in the given role in presentation order.
+    pub fn children(&self, role: &str, concept: &str) -> Vec<&str> {
+        self.0
+            .get(role)
+            .and_then(|parents| parents.get(concept))
+            .map(|children| {
+                children
+                    .iter()
+                    .map(|child| child.concept.as_str())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The given concept and all its descendants in any role.
+    pub fn subtree(&self, concept: &str) -> HashSet<String> {
+        let mut subtree = HashSet::from([concept.to_owned()]);
+        let mut stack = vec![concept.to_owned()];
+
+        while let Some(parent) = stack.pop() {
+            for parents in self.0.values() {
+                for child in parents.get(&parent).into_iter().flatten() {
+                    if subtree.insert(child.concept.clone()) {
+                        stack.push(child.concept.clone());
+                    }
+                }
+            }
+        }
+
+        subtree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arc(from: &str, to: &str, order: &str) -> Arc {
+        Arc {
+            role: String::from("balanceSheet"),
+            kind: String::from("presentationArc"),
+            from: String::from(from),
+            to: String::from(to),
+            order: Some(String::from(order)),
+            weight: None,
+        }
+    }
+
+    #[test]
+    fn test_presentation() {
+        let mut presentation = Presentation::new();
+
+        for arc in [
+            arc("de-gaap-ci:bs.ass", "de-gaap-ci:bs.ass.currAss", "2"),
+            arc("de-gaap-ci:bs.ass", "de-gaap-ci:bs.ass.fixAss", "1"),
+            arc("de-gaap-ci:bs.ass", "de-gaap-ci:bs.ass.fixAss", "1"),
+            arc(
+                "de-gaap-ci:bs.ass.fixAss",
+                "de-gaap-ci:bs.ass.fixAss.tan",
+                "1",
+            ),
+        ] {
+            presentation.insert(arc).unwrap();
+        }
+
+        assert_eq!(presentation.len(), 3);
+        assert_eq!(
+            presentation.roots("balanceSheet"),
+            vec!["de-gaap-ci:bs.ass"]
+        );
+        assert_eq!(
+            presentation.children("balanceSheet", "de-gaap-ci:bs.ass"),
+            vec!["de-gaap-ci:bs.ass.fixAss", "de-gaap-ci:bs.ass.currAss"]
+        );
+        assert_eq!(
+            presentation.subtree("de-gaap-ci:bs.ass.fixAss"),
+            HashSet::from([
+                String::from("de-gaap-ci:bs.ass.fixAss"),
+                String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+            ])
+        );
+    }
+}
@@ -0,0 +1,87 @@
+//! The origin of values in the source files, e.g. the row of a csv file.
+
+use serde::Serialize;
+use std::{collections::BTreeMap, fmt};
+
+/// The location of a value in a source file.
+///
+/// Rows and columns are 1-based, i.e. the first row of a csv file is the
+/// header.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub struct Provenance {
+    pub file: String,
+    /// The sheet of a spreadsheet; csv files don't have sheets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sheet: Option<String>,
+    pub row: u64,
+    pub column: u64,
+}
+
+impl Provenance {
+    pub fn new(file: impl Into<String>, row: u64, column: u64) -> Self {
+        Self {
+            file: file.into(),
+            sheet: None,
+            row,
+            column,
+        }
+    }
+
+    pub fn with_sheet(mut self, sheet: impl Into<String>) -> Self {
+        self.sheet = Some(sheet.into());
+        self
+    }
+}
+
+impl fmt::Display for Provenance {
+    /// Format as `file:row:column`, or `file[sheet]:row:column` for
+    /// spreadsheets.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file)?;
+
+        if let Some(sheet) = &self.sheet {
+            write!(f, "[{sheet}]")?;
+        }
+
+        write!(f, ":{}:{}", self.row, self.column)
+    }
+}
+
+/// The provenance of values by concept.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SourceMap(BTreeMap<String, Provenance>);
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    pub fn get(&self, concept: &str) -> Option<&Provenance> {
+        self.0.get(concept)
+    }
+
+    pub fn insert(&mut self, concept: impl Into<String>, provenance: Provenance) {
+        self.0.insert(concept.into(), provenance);
+    }
+
+    /// Add the provenances of another source map; existing entries are kept.
+    pub fn extend(&mut self, other: SourceMap) {
+        for (concept, provenance) in other.0 {
+            self.0.entry(concept).or_insert(provenance);
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Provenance)> {
+        self.0
+            .iter()
+            .map(|(concept, provenance)| (concept.as_str(), provenance))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
@@ -0,0 +1,196 @@
+//! Select facts by concept, context, and unit.
+
+use crate::{presentation::Presentation, Fact, XbrlElement};
+use std::collections::HashSet;
+
+/// A selector for facts.
+///
+/// The patterns for concept, context, and unit support the wildcards `*` for
+/// any sequence of characters and `?` for a single character, e.g.
+/// `de-gaap-ci:bs.ass.*`. All given criteria must match.
+#[derive(Debug, Default, Clone)]
+pub struct Query {
+    concept: Option<String>,
+    context: Option<String>,
+    unit: Option<String>,
+    subtree: Option<HashSet<String>>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select facts whose concept matches the given pattern.
+    pub fn concept(mut self, pattern: impl Into<String>) -> Self {
+        self.concept = Some(pattern.into());
+        self
+    }
+
+    /// Select facts whose context matches the given pattern.
+    pub fn context(mut self, pattern: impl Into<String>) -> Self {
+        self.context = Some(pattern.into());
+        self
+    }
+
+    /// Select facts whose unit matches the given pattern.
+    pub fn unit(mut self, pattern: impl Into<String>) -> Self {
+        self.unit = Some(pattern.into());
+        self
+    }
+
+    /// Select facts of the given concept and its descendants in the
+    /// presentation hierarchy.
+    pub fn subtree(mut self, presentation: &Presentation, concept: &str) -> Self {
+        self.subtree = Some(presentation.subtree(concept));
+        self
+    }
+
+    /// Check if a fact matches the query.
+    pub fn matches(&self, fact: &Fact) -> bool {
+        let matches_pattern = |pattern: &Option<String>, value: Option<&str>| match pattern {
+            Some(pattern) => value.is_some_and(|value| matches_wildcard(pattern, value)),
+            None => true,
+        };
+
+        matches_pattern(&self.concept, Some(&fact.concept))
+            && matches_pattern(&self.context, Some(&fact.context))
+            && matches_pattern(&self.unit, fact.unit.as_deref())
+            && self
+                .subtree
+                .as_ref()
+                .is_none_or(|subtree| subtree.contains(&fact.concept))
+    }
+
+    /// Select the matching facts.
+    pub fn select(&self, facts: &[Fact]) -> Vec<Fact> {
+        facts
+            .iter()
+            .filter(|fact| self.matches(fact))
+            .cloned()
+            .collect()
+    }
+}
+
+impl XbrlElement {
+    /// Collect all facts matching the query recursively.
+    pub fn query(&self, query: &Query) -> Vec<Fact> {
+        query.select(&self.facts())
+    }
+}
+
+/// Match a value against a pattern with the wildcards `*` and `?`.
+fn matches_wildcard(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let value = value.chars().collect::<Vec<_>>();
+    let (mut p, mut v) = (0, 0);
+    // The position of the last `*` in the pattern and the matched position in
+    // the value for backtracking.
+    let mut star = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::linkbase::Arc;
+
+    fn fact(concept: &str, context: &str) -> Fact {
+        Fact::new(
+            concept,
+            context,
+            Some(String::from("EUR")),
+            Some(String::from("2")),
+            Some(String::from("100.00")),
+            false,
+        )
+    }
+
+    #[test]
+    fn test_matches_wildcard() {
+        assert!(matches_wildcard(
+            "de-gaap-ci:bs.ass.*",
+            "de-gaap-ci:bs.ass.fixAss"
+        ));
+        assert!(!matches_wildcard(
+            "de-gaap-ci:bs.ass.*",
+            "de-gaap-ci:bs.ass"
+        ));
+        assert!(matches_wildcard("*-AKTJAHR", "I-AKTJAHR"));
+        assert!(matches_wildcard("?-AKTJAHR", "D-AKTJAHR"));
+        assert!(matches_wildcard("*ass*Ass", "de-gaap-ci:bs.ass.currAss"));
+        assert!(!matches_wildcard("EUR", "PURE"));
+    }
+
+    #[test]
+    fn test_query() {
+        let facts = vec![
+            fact("de-gaap-ci:bs.ass", "I-AKTJAHR"),
+            fact("de-gaap-ci:bs.ass.fixAss", "I-AKTJAHR"),
+            fact("de-gaap-ci:bs.ass.fixAss.tan", "I-AKTJAHR"),
+            fact("de-gaap-ci:bs.ass.fixAss", "I-VORJAHR"),
+            fact("de-gaap-ci:bs.ass.currAss", "I-AKTJAHR"),
+        ];
+        let mut presentation = Presentation::new();
+        presentation
+            .insert(Arc {
+                role: String::from("balanceSheet"),
+                kind: String::from("presentationArc"),
+                from: String::from("de-gaap-ci:bs.ass.fixAss"),
+                to: String::from("de-gaap-ci:bs.ass.fixAss.tan"),
+                order: None,
+                weight: None,
+            })
+            .unwrap();
+
+        let concepts = |query: Query| {
+            query
+                .select(&facts)
+                .into_iter()
+                .map(|fact| format!("{} {}", fact.concept, fact.context))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            concepts(
+                Query::new()
+                    .concept("de-gaap-ci:bs.ass.*")
+                    .context("I-AKTJAHR")
+            ),
+            vec![
+                "de-gaap-ci:bs.ass.fixAss I-AKTJAHR",
+                "de-gaap-ci:bs.ass.fixAss.tan I-AKTJAHR",
+                "de-gaap-ci:bs.ass.currAss I-AKTJAHR",
+            ]
+        );
+        assert_eq!(
+            concepts(
+                Query::new()
+                    .subtree(&presentation, "de-gaap-ci:bs.ass.fixAss")
+                    .context("*AKTJAHR")
+            ),
+            vec![
+                "de-gaap-ci:bs.ass.fixAss I-AKTJAHR",
+                "de-gaap-ci:bs.ass.fixAss.tan I-AKTJAHR",
+            ]
+        );
+        assert!(concepts(Query::new().unit("PURE")).is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+//! Parse the validation and server responses of ERiC into diagnostics.
+
+use crate::provenance::{Provenance, SourceMap};
+use anyhow::anyhow;
+use quick_xml::{events::Event, Reader};
+use serde::Serialize;
+use std::{collections::HashMap, fmt, str};
+
+/// The severity of a diagnostic.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The filing is rejected (_Fehler_).
+    Error,
+    /// The filing is accepted, but should be checked (_Hinweis_).
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The response a diagnostic originates from.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Origin {
+    /// The plausibility check of ERiC.
+    Validation,
+    /// The server of the tax authorities.
+    Server,
+}
+
+/// A single finding of the validation or transmission of a filing.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub origin: Origin,
+    /// The id of the violated rule, e.g. the `FachlicheFehlerId`, or the
+    /// return code of the server.
+    pub rule_id: Option<String>,
+    pub message: String,
+    /// The XBRL concept involved, e.g. `de-gaap-ci:bs.ass`.
+    pub concept: Option<String>,
+    /// The XPath of the element involved.
+    pub xpath: Option<String>,
+    /// The location of the value of the concept in the source file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<Provenance>,
+}
+
+/// The diagnostics of a validated or sent filing.
+#[derive(Debug, Default, PartialEq, Clone, Serialize)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// Parse the validation response and the server response of ERiC; empty
+    /// responses are skipped.
+    pub fn parse(validation_response: &str, server_response: &str) -> Result<Self, anyhow::Error> {
+        let mut diagnostics = vec![];
+
+        for response in [validation_response, server_response] {
+            if !response.trim().is_empty() {
+                let mut reader = Reader::from_str(response);
+                reader.trim_text(true);
+                diagnostics.extend(read_diagnostics(&mut reader)?);
+            }
+        }
+
+        Ok(Self { diagnostics })
+    }
+
+    /// Point the diagnostics of a concept to the location of its value in
+    /// the source files.
+    pub fn locate(&mut self, sources: &SourceMap) {
+        for diagnostic in &mut self.diagnostics {
+            if let Some(concept) = &diagnostic.concept {
+                diagnostic.source = sources.get(concept).cloned();
+            }
+        }
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    /// List the diagnostics grouped by severity and concept.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (title, severity) in [("Errors", Severity::Error), ("Warnings", Severity::Warning)] {
+            let mut diagnostics = self
+                .diagnostics
+                .iter()
+                .filter(|diagnostic| diagnostic.severity == severity)
+                .collect::<Vec<_>>();
+
+            if diagnostics.is_empty() {
+                continue;
+            }
+
+            // Sort by concept; the sort is stable, i.e. the order of the
+            // response is retained per concept.
+            diagnostics.sort_by(|lhs, rhs| lhs.concept.cmp(&rhs.concept));
+            writeln!(f, "{title} ({}):", diagnostics.len())?;
+
+            let mut current_concept = None;
+
+            for diagnostic in diagnostics {
+                if current_concept != Some(&diagnostic.concept) {
+                    current_concept = Some(&diagnostic.concept);
+                    writeln!(
+                        f,
+                        "  {}",
+                        diagnostic.concept.as_deref().unwrap_or("(no concept)")
+                    )?;
+                }
+
+                match &diagnostic.rule_id {
+                    Some(rule_id) => writeln!(f, "    [{rule_id}] {}", diagnostic.message)?,
+                    None => writeln!(f, "    {}", diagnostic.message)?,
+                }
+
+                if let Some(xpath) = &diagnostic.xpath {
+                    writeln!(f, "      at {xpath}")?;
+                }
+
+                if let Some(source) = &diagnostic.source {
+                    writeln!(f, "      from {source}")?;
+                }
+            }
+        }
+
+        if self.diagnostics.is_empty() {
+            writeln!(f, "No errors or warnings")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_diagnostics<R>(reader: &mut Reader<R>) -> Result<Vec<Diagnostic>, anyhow::Error>
+where
+    R: std::io::Read + std::io::BufRead,
+{
+    let mut buf = Vec::new();
+    let mut diagnostics = vec![];
+    // The local names of the open elements.
+    let mut path: Vec<String> = vec![];
+    // The fields of the current `Fehlerregelpruefung`, `Hinweis`, or
+    // `Rueckgabe` element.
+    let mut fields: Option<(String, HashMap<String, String>)> = None;
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(tag)) => {
+                let local_name = str::from_utf8(tag.local_name().as_ref())?.to_owned();
+
+                if fields.is_none()
+                    && matches!(
+                        local_name.as_str(),
+                        "Fehlerregelpruefung" | "Hinweis" | "Rueckgabe"
+                    )
+                {
+                    fields = Some((local_name.clone(), HashMap::new()));
+                }
+
+                path.push(local_name);
+            }
+            Ok(Event::Text(text)) => {
+                if let (Some((_, fields)), Some(name)) = (&mut fields, path.last()) {
+                    fields.insert(name.clone(), text.unescape()?.into_owned());
+                }
+            }
+            Ok(Event::End(_)) => {
+                let local_name = path.pop();
+
+                if let Some((kind, _)) = &fields {
+                    if Some(kind) == local_name.as_ref() {
+                        let (kind, fields) = fields.take().unwrap_or_default();
+
+                        if let Some(diagnostic) = to_diagnostic(&kind, fields) {
+                            diagnostics.push(diagnostic);
+                        }
+                    }
+                }
+            }
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(anyhow!("Can't parse response: {err}"));
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(diagnostics)
+}
+
+fn to_diagnostic(kind: &str, mut fields: HashMap<String, String>) -> Option<Diagnostic> {
+    let message = fields.remove("Text").unwrap_or_default();
+
+    if kind == "Rueckgabe" {
+        let code = fields.remove("Code")?;
+
+        // Return code `0` means success
+        if code == "0" {
+            return None;
+        }
+
+        return Some(Diagnostic {
+            severity: Severity::Error,
+            origin: Origin::Server,
+            rule_id: Some(code),
+            message,
+            concept: None,
+            xpath: None,
+            source: None,
+        });
+    }
+
+    let severity = match kind {
+        "Fehlerregelpruefung" => Severity::Error,
+        _ => Severity::Warning,
+    };
+    let rule_id = fields
+        .remove("FachlicheFehlerId")
+        .or_else(|| fields.remove("FachlicheHinweisId"))
+        .or_else(|| fields.remove("RegelName"));
+    let mut concept = None;
+    let mut xpath = None;
+
+    for field in ["Feldidentifikator", "PrivateKennnummer"] {
+        match fields.remove(field) {
+            Some(value) if value.starts_with('/') => xpath = xpath.or(Some(value)),
+            Some(value) if value.contains(':') => concept = concept.or(Some(value)),
+            _ => (),
+        }
+    }
+
+    let concept = concept.or_else(|| xpath.as_deref().and_then(concept_from_xpath));
+
+    Some(Diagnostic {
+        severity,
+        origin: Origin::Validation,
+        rule_id,
+        message,
+        concept,
+        xpath,
+        source: None,
+    })
+}
+
+/// Get the concept of the last step of an XPath, e.g.
+/// `/ebilanz:EBilanz/xbrli:xbrl/de-gaap-ci:bs.ass[1]` to `de-gaap-ci:bs.ass`.
+fn concept_from_xpath(xpath: &str) -> Option<String> {
+    let step = xpath.rsplit('/').next()?;
+    let step = step.split('[').next()?;
+
+    (step.contains(':')).then(|| step.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALIDATION_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <EricBearbeiteVorgang xmlns="http://www.elster.de/EricXML/1.1/EricBearbeiteVorgang">
+            <Fehlerregelpruefung>
+                <Nutzdatenticket>1</Nutzdatenticket>
+                <Feldidentifikator>de-gaap-ci:bs.ass</Feldidentifikator>
+                <RegelName>Summenpruefung</RegelName>
+                <FachlicheFehlerId>E_BS_001</FachlicheFehlerId>
+                <Text>Die Summe der Aktiva stimmt nicht.</Text>
+            </Fehlerregelpruefung>
+            <Hinweis>
+                <Nutzdatenticket>1</Nutzdatenticket>
+                <PrivateKennnummer>/ebilanz:EBilanz/xbrli:xbrl/de-gcd:genInfo.company.id.location.street[1]</PrivateKennnummer>
+                <RegelName>Adresse</RegelName>
+                <Text>Die Stra&#223;e fehlt.</Text>
+            </Hinweis>
+        </EricBearbeiteVorgang>"#;
+
+    const SERVER_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Elster xmlns="http://www.elster.de/elsterxml/schema/v11">
+            <TransferHeader version="11">
+                <RC><Rueckgabe><Code>610101292</Code><Text>Fehler im Nutzdaten-Header.</Text></Rueckgabe></RC>
+            </TransferHeader>
+            <DatenTeil><Nutzdatenblock><NutzdatenHeader><RC><Rueckgabe><Code>0</Code><Text>OK</Text></Rueckgabe></RC></NutzdatenHeader></Nutzdatenblock></DatenTeil>
+        </Elster>"#;
+
+    #[test]
+    fn test_parse_validation_report() {
+        let report = ValidationReport::parse(VALIDATION_RESPONSE, SERVER_RESPONSE).unwrap();
+
+        assert_eq!(
+            report.diagnostics,
+            vec![
+                Diagnostic {
+                    severity: Severity::Error,
+                    origin: Origin::Validation,
+                    rule_id: Some(String::from("E_BS_001")),
+                    message: String::from("Die Summe der Aktiva stimmt nicht."),
+                    concept: Some(String::from("de-gaap-ci:bs.ass")),
+                    xpath: None,
+                    source: None,
+                },
+                Diagnostic {
+                    severity: Severity::Warning,
+                    origin: Origin::Validation,
+                    rule_id: Some(String::from("Adresse")),
+                    message: String::from("Die Straße fehlt."),
+                    concept: Some(String::from("de-gcd:genInfo.company.id.location.street")),
+                    xpath: Some(String::from(
+                        "/ebilanz:EBilanz/xbrli:xbrl/de-gcd:genInfo.company.id.location.street[1]"
+                    )),
+                    source: None,
+                },
+                Diagnostic {
+                    severity: Severity::Error,
+                    origin: Origin::Server,
+                    rule_id: Some(String::from("610101292")),
+                    message: String::from("Fehler im Nutzdaten-Header."),
+                    concept: None,
+                    xpath: None,
+                    source: None,
+                },
+            ]
+        );
+        assert!(report.has_errors());
+        assert_eq!(report.warnings().count(), 1);
+    }
+
+    #[test]
+    fn test_display_validation_report() {
+        let report = ValidationReport::parse(VALIDATION_RESPONSE, "").unwrap();
+
+        assert_eq!(
+            report.to_string(),
+            "Errors (1):\n  \
+               de-gaap-ci:bs.ass\n    \
+                 [E_BS_001] Die Summe der Aktiva stimmt nicht.\n\
+             Warnings (1):\n  \
+               de-gcd:genInfo.company.id.location.street\n    \
+                 [Adresse] Die Straße fehlt.\n      \
+                   at /ebilanz:EBilanz/xbrli:xbrl/de-gcd:genInfo.company.id.location.street[1]\n"
+        );
+    }
+
+    #[test]
+    fn test_locate_validation_report() {
+        let mut report = ValidationReport::parse(VALIDATION_RESPONSE, "").unwrap();
+        let mut sources = SourceMap::new();
+        sources.insert("de-gaap-ci:bs.ass", Provenance::new("balance.csv", 12, 2));
+
+        report.locate(&sources);
+
+        assert_eq!(
+            report.diagnostics[0].source,
+            Some(Provenance::new("balance.csv", 12, 2))
+        );
+        assert_eq!(report.diagnostics[1].source, None);
+        assert!(report.to_string().contains(
+            "[E_BS_001] Die Summe der Aktiva stimmt nicht.\n      from balance.csv:12:2\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_empty_responses() {
+        let report = ValidationReport::parse("", "").unwrap();
+
+        assert!(!report.has_errors());
+        assert_eq!(report.to_string(), "No errors or warnings\n");
+    }
+}
@@ -0,0 +1,251 @@
+//! Roll over the filing of the previous year to a new filing.
+
+use crate::xbrl::{is_period, Taxonomy, XbrlElement, XmlType, CURRENT_YEAR, PREVIOUS_YEAR};
+use anyhow::anyhow;
+
+const PERIOD_DATES: [&str; 3] = ["xbrli:instant", "xbrli:startDate", "xbrli:endDate"];
+
+impl XbrlElement {
+    /// Roll over the filing of the previous year to the filing of the current
+    /// year.
+    ///
+    /// - The current-year facts of the financial statements are moved to
+    ///   prior-year (`VORJAHR`) contexts, and the current-year facts are
+    ///   cleared.
+    /// - The periods of the current-year contexts, the fiscal period dates in
+    ///   GCD, and the `ebilanz:stichtag` are shifted by one year.
+    /// - Static company master data like name, address, legal form, and
+    ///   Steuernummer is retained.
+    ///
+    /// Prior-year facts and contexts of the given filing are replaced; facts
+    /// and contexts of other periods are retained unchanged.
+    pub fn rollover(&mut self) -> Result<(), anyhow::Error> {
+        if self.name == "ebilanz:stichtag" {
+            if let Some(value) = &self.value {
+                self.value = Some(shift_year(value)?);
+            }
+        }
+
+        if self.is_period_date() {
+            if let Some(value) = &self.value {
+                self.value = Some(shift_year(value)?);
+            }
+        }
+
+        let children = std::mem::take(&mut self.children);
+
+        for mut child in children {
+            if child.is_previous_year_context() || child.is_previous_year_fact() {
+                // Discard values of the year before the previous year.
+                continue;
+            }
+
+            if child.is_current_year_context() {
+                let previous_year = child.to_previous_year_context();
+                child.shift_period()?;
+                self.children.push(child);
+                self.children.push(previous_year);
+            } else if child.is_current_year_fact() && child.is_financial_statement() {
+                let mut previous_year = child.clone();
+                previous_year.set_context_suffix(PREVIOUS_YEAR);
+                child.remove_values();
+                self.children.push(child);
+                self.children.push(previous_year);
+            } else {
+                child.rollover()?;
+                self.children.push(child);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_financial_statement(&self) -> bool {
+        self.xml_type == XmlType::Taxonomy(Taxonomy::GaapCi)
+    }
+
+    fn is_period_date(&self) -> bool {
+        self.name.starts_with("de-gcd:genInfo.report.period.")
+    }
+
+    fn is_current_year_context(&self) -> bool {
+        self.name == "xbrli:context"
+            && self
+                .attribute("id")
+                .is_some_and(|id| is_period(id, CURRENT_YEAR))
+    }
+
+    fn is_previous_year_context(&self) -> bool {
+        self.name == "xbrli:context"
+            && self
+                .attribute("id")
+                .is_some_and(|id| is_period(id, PREVIOUS_YEAR))
+    }
+
+    fn is_current_year_fact(&self) -> bool {
+        self.attribute("contextRef")
+            .is_some_and(|context| is_period(context, CURRENT_YEAR))
+    }
+
+    fn is_previous_year_fact(&self) -> bool {
+        self.attribute("contextRef")
+            .is_some_and(|context| is_period(context, PREVIOUS_YEAR))
+    }
+
+    /// Copy a current-year context with unchanged period to a prior-year
+    /// context.
+    fn to_previous_year_context(&self) -> XbrlElement {
+        let mut context = self.clone();
+
+        if let Some(id) = self.attribute("id") {
+            let id = replace_suffix(id, PREVIOUS_YEAR);
+            context.set_attribute("id", &id);
+        }
+
+        context
+    }
+
+    fn set_context_suffix(&mut self, suffix: &str) {
+        if let Some(context) = self.attribute("contextRef") {
+            let context = replace_suffix(context, suffix);
+            self.set_attribute("contextRef", &context);
+        }
+    }
+
+    /// Shift the period dates of a context by one year.
+    fn shift_period(&mut self) -> Result<(), anyhow::Error> {
+        if PERIOD_DATES.contains(&self.name.as_str()) {
+            if let Some(value) = &self.value {
+                self.value = Some(shift_year(value)?);
+            }
+        }
+
+        for child in &mut self.children {
+            child.shift_period()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn replace_suffix(id: &str, suffix: &str) -> String {
+    match id.strip_suffix(CURRENT_YEAR) {
+        Some(prefix) if is_period(id, CURRENT_YEAR) => format!("{prefix}{suffix}"),
+        _ => id.to_owned(),
+    }
+}
+
+/// Shift a date in the format `YYYY-MM-DD` or `YYYYMMDD` by one year.
+///
+/// The 29th of February is shifted to the 28th of February.
+fn shift_year(date: &str) -> Result<String, anyhow::Error> {
+    let (year, month, day, separator) = match date.len() {
+        10 => (&date[0..4], &date[5..7], &date[8..10], "-"),
+        8 => (&date[0..4], &date[4..6], &date[6..8], ""),
+        _ => return Err(anyhow!("Invalid date: '{date}'")),
+    };
+    let year = year
+        .parse::<u32>()
+        .map_err(|err| anyhow!("Invalid date '{date}': {err}"))?
+        + 1;
+    let day = if month == "02" && day == "29" && !is_leap_year(year) {
+        "28"
+    } else {
+        day
+    };
+
+    Ok(format!("{year:04}{separator}{month}{separator}{day}"))
+}
+
+fn is_leap_year(year: u32) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{remove_formatting, Reader, Writer};
+    use std::{io::Cursor, str};
+
+    #[test]
+    fn test_shift_year() {
+        assert_eq!(shift_year("2022-06-30").unwrap(), "2023-06-30");
+        assert_eq!(shift_year("20221231").unwrap(), "20231231");
+        assert_eq!(shift_year("2024-02-29").unwrap(), "2025-02-28");
+        assert!(shift_year("30.06.2022").is_err());
+    }
+
+    #[test]
+    fn test_rollover() {
+        let xml = r#"
+            <ebilanz:EBilanz>
+                <ebilanz:stichtag>20220630</ebilanz:stichtag>
+                <xbrli:xbrl>
+                    <xbrli:context id="I-AKTJAHR">
+                        <xbrli:entity>
+                            <xbrli:identifier scheme="http://www.rzf-nrw.de/Steuernummer">5192050001265</xbrli:identifier>
+                        </xbrli:entity>
+                        <xbrli:period>
+                            <xbrli:instant>2022-06-30</xbrli:instant>
+                        </xbrli:period>
+                    </xbrli:context>
+                    <xbrli:context id="I-VORJAHR">
+                        <xbrli:entity>
+                            <xbrli:identifier scheme="http://www.rzf-nrw.de/Steuernummer">5192050001265</xbrli:identifier>
+                        </xbrli:entity>
+                        <xbrli:period>
+                            <xbrli:instant>2021-06-30</xbrli:instant>
+                        </xbrli:period>
+                    </xbrli:context>
+                    <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Landwirt GmbH</de-gcd:genInfo.company.id.name>
+                    <de-gcd:genInfo.report.period.fiscalYearEnd contextRef="D-AKTJAHR">2022-06-30</de-gcd:genInfo.report.period.fiscalYearEnd>
+                    <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                    <de-gaap-ci:bs.ass contextRef="I-VORJAHR" unitRef="EUR" decimals="2">200.00</de-gaap-ci:bs.ass>
+                    <de-gaap-ci:bs.ass contextRef="I-VORVORJAHR" unitRef="EUR" decimals="2">100.00</de-gaap-ci:bs.ass>
+                </xbrli:xbrl>
+            </ebilanz:EBilanz>
+        "#;
+        let expected_xml = r#"
+            <ebilanz:EBilanz>
+                <ebilanz:stichtag>20230630</ebilanz:stichtag>
+                <xbrli:xbrl>
+                    <xbrli:context id="I-AKTJAHR">
+                        <xbrli:entity>
+                            <xbrli:identifier scheme="http://www.rzf-nrw.de/Steuernummer">5192050001265</xbrli:identifier>
+                        </xbrli:entity>
+                        <xbrli:period>
+                            <xbrli:instant>2023-06-30</xbrli:instant>
+                        </xbrli:period>
+                    </xbrli:context>
+                    <xbrli:context id="I-VORJAHR">
+                        <xbrli:entity>
+                            <xbrli:identifier scheme="http://www.rzf-nrw.de/Steuernummer">5192050001265</xbrli:identifier>
+                        </xbrli:entity>
+                        <xbrli:period>
+                            <xbrli:instant>2022-06-30</xbrli:instant>
+                        </xbrli:period>
+                    </xbrli:context>
+                    <de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Landwirt GmbH</de-gcd:genInfo.company.id.name>
+                    <de-gcd:genInfo.report.period.fiscalYearEnd contextRef="D-AKTJAHR">2023-06-30</de-gcd:genInfo.report.period.fiscalYearEnd>
+                    <de-gaap-ci:bs.ass contextRef="I-AKTJAHR" unitRef="EUR" xsi:nil="true"/>
+                    <de-gaap-ci:bs.ass contextRef="I-VORJAHR" unitRef="EUR" decimals="2">300.00</de-gaap-ci:bs.ass>
+                    <de-gaap-ci:bs.ass contextRef="I-VORVORJAHR" unitRef="EUR" decimals="2">100.00</de-gaap-ci:bs.ass>
+                </xbrli:xbrl>
+            </ebilanz:EBilanz>
+        "#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut element = XbrlElement::parse(&mut reader).unwrap();
+
+        element.rollover().unwrap();
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        element.serialize(&mut writer).unwrap();
+        let actual = writer.into_inner().into_inner();
+
+        assert_eq!(
+            str::from_utf8(&actual).unwrap(),
+            remove_formatting(expected_xml).unwrap()
+        );
+    }
+}
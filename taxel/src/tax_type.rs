@@ -0,0 +1,117 @@
+use crate::TAXONOMY_VERSIONS;
+use anyhow::anyhow;
+use std::{fmt, str::FromStr};
+
+/// The ELSTER procedures supported by taxel.
+///
+/// Each tax type has its own template, procedure (`Verfahren`) and data type
+/// (`DatenArt`) in the transfer header, and versions. ERiC processes a xml
+/// file as `<data type>_<version>`, e.g. `Bilanz_6.5` or `UStVA_2024`.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum TaxType {
+    /// The electronic balance sheet (_E-Bilanz_).
+    #[default]
+    Bilanz,
+    /// The advance return for VAT (_Umsatzsteuer-Voranmeldung_).
+    UStVA,
+}
+
+impl TaxType {
+    pub const ALL: [TaxType; 2] = [TaxType::Bilanz, TaxType::UStVA];
+
+    /// The data type (`DatenArt`) of the transfer header.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bilanz => "Bilanz",
+            Self::UStVA => "UStVA",
+        }
+    }
+
+    /// The procedure (`Verfahren`) of the transfer header.
+    pub fn procedure(&self) -> &'static str {
+        match self {
+            Self::Bilanz => "ElsterBilanz",
+            Self::UStVA => "ElsterAnmeldung",
+        }
+    }
+
+    /// The supported versions, i.e. the taxonomy versions for `Bilanz` and the
+    /// years for `UStVA`.
+    pub fn versions(&self) -> Vec<&'static str> {
+        match self {
+            Self::Bilanz => TAXONOMY_VERSIONS
+                .iter()
+                .map(|taxonomy| taxonomy.version)
+                .collect(),
+            Self::UStVA => vec!["2024"],
+        }
+    }
+
+    /// The latest supported version.
+    pub fn latest_version(&self) -> &'static str {
+        self.versions()
+            .last()
+            .copied()
+            .expect("Missing versions for tax type")
+    }
+
+    /// The template in the `templates` directory.
+    pub fn template(&self) -> &'static str {
+        match self {
+            Self::Bilanz => "elster_v11/taxonomy_v6.5/ebilanz.xml",
+            Self::UStVA => "elster_v11/ustva_v2024/ustva.xml",
+        }
+    }
+
+    /// Check that the version is supported for the tax type.
+    pub fn check_version(&self, version: &str) -> Result<(), anyhow::Error> {
+        if self.versions().contains(&version) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Unsupported version '{version}' for tax type '{self}'; expected one of: {}",
+                self.versions().join(", ")
+            ))
+        }
+    }
+}
+
+impl FromStr for TaxType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|tax_type| tax_type.as_str() == s)
+            .ok_or(anyhow!("Invalid tax type: {s}"))
+    }
+}
+
+impl fmt::Display for TaxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tax_type() {
+        assert_eq!("Bilanz".parse::<TaxType>().unwrap(), TaxType::Bilanz);
+        assert_eq!("UStVA".parse::<TaxType>().unwrap(), TaxType::UStVA);
+        assert!("KSt".parse::<TaxType>().is_err());
+    }
+
+    #[test]
+    fn test_check_version() {
+        assert!(TaxType::Bilanz.check_version("6.5").is_ok());
+        assert!(TaxType::UStVA.check_version("2024").is_ok());
+        assert_eq!(
+            TaxType::UStVA.check_version("6.5").unwrap_err().to_string(),
+            "Unsupported version '6.5' for tax type 'UStVA'; expected one of: 2024"
+        );
+        assert_eq!(TaxType::Bilanz.latest_version(), "6.6");
+    }
+}
@@ -0,0 +1,550 @@
+//! Read the schema files and linkbases of the eBilanz taxonomies.
+
+use crate::{
+    calculation::Calculations,
+    linkbase::{self, get_attribute},
+    presentation::Presentation,
+};
+use anyhow::anyhow;
+use log::{debug, warn};
+use quick_xml::{events::Event, Reader};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    str,
+};
+
+/// The item type of a concept as defined in the taxonomy schema.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ItemType {
+    /// A monetary amount, e.g. `xbrli:monetaryItemType`.
+    Monetary,
+    /// A percentage, e.g. `num:percentItemType`.
+    Percent,
+    /// A dimensionless number, e.g. `xbrli:pureItemType`.
+    Pure,
+    /// An integer number, e.g. `xbrli:positiveIntegerItemType`.
+    Integer,
+    /// Any other decimal number, e.g. `xbrli:decimalItemType`.
+    Decimal,
+    /// Strings, dates, booleans, etc.
+    NonNumeric,
+}
+
+impl ItemType {
+    /// Convert the `type` attribute of a schema element to an item type.
+    pub fn from_schema_type(schema_type: &str) -> Self {
+        let local_name = schema_type
+            .rsplit_once(':')
+            .map(|(_, local_name)| local_name)
+            .unwrap_or(schema_type);
+
+        match local_name {
+            "monetaryItemType" => Self::Monetary,
+            "percentItemType" => Self::Percent,
+            "pureItemType" => Self::Pure,
+            "integerItemType"
+            | "nonNegativeIntegerItemType"
+            | "positiveIntegerItemType"
+            | "nonPositiveIntegerItemType"
+            | "negativeIntegerItemType"
+            | "longItemType"
+            | "intItemType"
+            | "shortItemType" => Self::Integer,
+            "decimalItemType" | "floatItemType" | "doubleItemType" => Self::Decimal,
+            _ => Self::NonNumeric,
+        }
+    }
+
+    /// Derive the item type from the `unitRef` of a fact if the concept is not
+    /// available in the taxonomy schema.
+    pub fn from_unit(unit: Option<&str>) -> Self {
+        match unit {
+            Some("EUR") => Self::Monetary,
+            Some("PURE") => Self::Pure,
+            Some(_) => Self::Decimal,
+            None => Self::NonNumeric,
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        *self != Self::NonNumeric
+    }
+
+    /// The id of the unit used for facts of this item type.
+    pub fn unit(&self) -> Option<&str> {
+        match self {
+            Self::Monetary => Some("EUR"),
+            Self::Percent | Self::Pure | Self::Integer | Self::Decimal => Some("PURE"),
+            Self::NonNumeric => None,
+        }
+    }
+
+    /// The `decimals` attribute used for facts of this item type.
+    pub fn decimals(&self) -> Option<Decimals> {
+        match self {
+            Self::Monetary => Some(Decimals::Places(2)),
+            Self::Integer => Some(Decimals::Places(0)),
+            Self::Percent | Self::Pure | Self::Decimal => Some(Decimals::Infinite),
+            Self::NonNumeric => None,
+        }
+    }
+}
+
+/// The value of the `decimals` attribute of a numeric fact.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Decimals {
+    /// The value is rounded to the given number of decimal places.
+    Places(u32),
+    /// The value is exact (`INF`).
+    Infinite,
+}
+
+impl fmt::Display for Decimals {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Places(places) => write!(f, "{places}"),
+            Self::Infinite => write!(f, "INF"),
+        }
+    }
+}
+
+/// The item types of all concepts of the taxonomy schema.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ConceptTypes {
+    item_types: HashMap<String, ItemType>,
+    /// The abstract concepts, e.g. `de-gaap-ci:bs`, which group the concepts
+    /// of the presentation hierarchy but can't be reported.
+    abstract_concepts: HashSet<String>,
+}
+
+impl ConceptTypes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the item type for a concept, e.g. `de-gaap-ci:bs.ass`.
+    pub fn get(&self, concept: &str) -> Option<ItemType> {
+        self.item_types.get(concept).copied()
+    }
+
+    pub fn insert(&mut self, concept: impl Into<String>, item_type: ItemType) {
+        self.item_types.insert(concept.into(), item_type);
+    }
+
+    /// Check if the concept is abstract.
+    pub fn is_abstract(&self, concept: &str) -> bool {
+        self.abstract_concepts.contains(concept)
+    }
+
+    /// Iterate over all concepts and their item types in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, ItemType)> {
+        self.item_types
+            .iter()
+            .map(|(concept, item_type)| (concept.as_str(), *item_type))
+    }
+
+    pub fn len(&self) -> usize {
+        self.item_types.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.item_types.is_empty()
+    }
+
+    /// Read the item types from a taxonomy schema file.
+    ///
+    /// The prefix of the concepts is taken from the namespace declaration
+    /// matching the `targetNamespace` of the schema.
+    pub fn parse<R>(&mut self, reader: &mut Reader<R>) -> Result<(), anyhow::Error>
+    where
+        R: std::io::Read + BufRead,
+    {
+        let mut buf = Vec::new();
+        let mut prefix = None;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => match tag.name().as_ref() {
+                    b"xs:schema" => {
+                        prefix = target_prefix(&tag)?;
+                    }
+                    b"xs:element" => {
+                        let name = get_attribute(&tag, "name")?;
+                        let schema_type = get_attribute(&tag, "type")?;
+                        let is_abstract = get_attribute(&tag, "abstract")?;
+
+                        if let (Some(prefix), Some(name), Some(schema_type)) =
+                            (&prefix, name, schema_type)
+                        {
+                            let concept = format!("{prefix}:{name}");
+
+                            if is_abstract.as_deref() == Some("true") {
+                                self.abstract_concepts.insert(concept.clone());
+                            }
+
+                            self.insert(concept, ItemType::from_schema_type(&schema_type));
+                        }
+                    }
+                    _ => (),
+                },
+                Ok(Event::Eof) => break,
+                Err(err) => {
+                    return Err(anyhow!("Can't parse schema file: {err}"));
+                }
+                _ => (),
+            }
+
+            buf.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Find the namespace prefix of the `targetNamespace` of a schema.
+fn target_prefix(tag: &quick_xml::events::BytesStart) -> Result<Option<String>, anyhow::Error> {
+    let target_namespace = match get_attribute(tag, "targetNamespace")? {
+        Some(target_namespace) => target_namespace,
+        None => return Ok(None),
+    };
+
+    for attribute in tag.attributes() {
+        let attribute = attribute?;
+        let key = str::from_utf8(attribute.key.as_ref())?;
+
+        if let Some(prefix) = key.strip_prefix("xmlns:") {
+            if attribute.unescape_value()? == target_namespace {
+                return Ok(Some(prefix.to_owned()));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// The parts of the discoverable taxonomy set (DTS) used by taxel.
+#[derive(Debug, Default)]
+pub struct TaxonomySchema {
+    pub concept_types: ConceptTypes,
+    pub calculations: Calculations,
+    pub presentation: Presentation,
+    /// The fiscal requirements of the concepts, e.g. `Mussfeld`.
+    pub fiscal_requirements: HashMap<String, String>,
+    /// The German standard labels of the concepts, e.g. `Anlagevermögen`.
+    pub labels: HashMap<String, String>,
+}
+
+impl TaxonomySchema {
+    /// Check if a value for the concept is mandatory for the tax authorities,
+    /// i.e. the concept is a `Mussfeld` or `Summenmussfeld`.
+    pub fn is_mandatory(&self, concept: &str) -> bool {
+        self.fiscal_requirements
+            .get(concept)
+            .is_some_and(|requirement| {
+                requirement.starts_with("Mussfeld") || requirement == "Summenmussfeld"
+            })
+    }
+
+    /// The German label of the concept, if the label linkbase was found.
+    pub fn label(&self, concept: &str) -> Option<&str> {
+        self.labels.get(concept).map(|label| label.as_str())
+    }
+
+    /// Discover the taxonomy schema from the `link:schemaRef` entry points of
+    /// an XBRL instance.
+    ///
+    /// The entry points, e.g.
+    /// `http://www.xbrl.de/taxonomies/de-gcd-2022-05-02/de-gcd-2022-05-02-shell.xsd`,
+    /// are resolved against the local `taxonomy_dir` by their directory and
+    /// file name. Referenced schemas and linkbases are loaded recursively;
+    /// missing files are skipped.
+    pub fn discover(taxonomy_dir: &Path, schema_refs: &[String]) -> Result<Self, anyhow::Error> {
+        let mut schema = Self::default();
+        let mut visited = HashSet::new();
+
+        for schema_ref in schema_refs {
+            match resolve_entry_point(taxonomy_dir, schema_ref) {
+                Some(path) => schema.load_schema(&path, &mut visited)?,
+                None => warn!("Can't resolve schema reference: {schema_ref}"),
+            }
+        }
+
+        debug!(
+            "Discovered {} concepts, {} calculation arcs, {} presentation arcs, {} fiscal requirements, and {} labels",
+            schema.concept_types.len(),
+            schema.calculations.len(),
+            schema.presentation.len(),
+            schema.fiscal_requirements.len(),
+            schema.labels.len()
+        );
+
+        Ok(schema)
+    }
+
+    fn load_schema(
+        &mut self,
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<(), anyhow::Error> {
+        if !visited.insert(path.to_path_buf()) {
+            return Ok(());
+        }
+
+        if !path.exists() {
+            warn!("Missing schema file: {}", path.display());
+            return Ok(());
+        }
+
+        debug!("Load schema file: {}", path.display());
+
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+        reader.trim_text(true);
+        self.concept_types.parse(&mut reader)?;
+
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+        reader.trim_text(true);
+        let references = read_references(&mut reader)?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+
+        for schema_location in references.schemas {
+            if let Some(path) = resolve_reference(base_dir, &schema_location) {
+                self.load_schema(&path, visited)?;
+            }
+        }
+
+        for href in references.linkbases {
+            if let Some(path) = resolve_reference(base_dir, &href) {
+                self.load_linkbase(&path, visited)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_linkbase(
+        &mut self,
+        path: &Path,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<(), anyhow::Error> {
+        if !visited.insert(path.to_path_buf()) {
+            return Ok(());
+        }
+
+        if !path.exists() {
+            warn!("Missing linkbase file: {}", path.display());
+            return Ok(());
+        }
+
+        debug!("Load linkbase file: {}", path.display());
+
+        let mut reader = Reader::from_reader(BufReader::new(File::open(path)?));
+        reader.trim_text(true);
+
+        if is_reference_linkbase(path) {
+            let requirements = linkbase::read_fiscal_requirements(&mut reader)?;
+            self.fiscal_requirements.extend(requirements);
+            return Ok(());
+        }
+
+        if is_label_linkbase(path) {
+            let labels = linkbase::read_labels(&mut reader, "de")?;
+            self.labels.extend(labels);
+            return Ok(());
+        }
+
+        for arc in linkbase::read_arcs(&mut reader)? {
+            match arc.kind.as_str() {
+                "calculationArc" => self.calculations.insert(arc)?,
+                "presentationArc" => self.presentation.insert(arc)?,
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The schemas and linkbases referenced by a schema file.
+#[derive(Debug, Default)]
+struct References {
+    schemas: Vec<String>,
+    linkbases: Vec<String>,
+}
+
+fn read_references<R>(reader: &mut Reader<R>) -> Result<References, anyhow::Error>
+where
+    R: std::io::Read + BufRead,
+{
+    let mut buf = Vec::new();
+    let mut references = References::default();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(tag)) | Ok(Event::Empty(tag)) => match tag.name().as_ref() {
+                b"xs:import" | b"xs:include" => {
+                    if let Some(schema_location) = get_attribute(&tag, "schemaLocation")? {
+                        references.schemas.push(schema_location);
+                    }
+                }
+                b"link:linkbaseRef" => {
+                    if let Some(href) = get_attribute(&tag, "xlink:href")? {
+                        references.linkbases.push(href);
+                    }
+                }
+                _ => (),
+            },
+            Ok(Event::Eof) => break,
+            Err(err) => {
+                return Err(anyhow!("Can't parse schema file: {err}"));
+            }
+            _ => (),
+        }
+
+        buf.clear();
+    }
+
+    Ok(references)
+}
+
+/// Check if the linkbase contains references, e.g.
+/// `de-gaap-ci-2022-05-02-reference-fiscal.xml`.
+fn is_reference_linkbase(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .is_some_and(|file_name| file_name.contains("-reference"))
+}
+
+/// Check if the linkbase contains labels, e.g.
+/// `de-gaap-ci-2022-05-02-label-fiscal-de.xml`.
+fn is_label_linkbase(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|file_name| file_name.to_str())
+        .is_some_and(|file_name| file_name.contains("-label"))
+}
+
+/// Resolve an entry point like
+/// `http://www.xbrl.de/taxonomies/de-gcd-2022-05-02/de-gcd-2022-05-02-shell.xsd`
+/// to `<taxonomy_dir>/de-gcd-2022-05-02/de-gcd-2022-05-02-shell.xsd`.
+fn resolve_entry_point(taxonomy_dir: &Path, schema_ref: &str) -> Option<PathBuf> {
+    let mut segments = schema_ref.rsplit('/');
+    let file_name = segments.next()?;
+    let dir_name = segments.next()?;
+
+    Some(taxonomy_dir.join(dir_name).join(file_name))
+}
+
+/// Resolve a reference relative to the referencing file; references to
+/// remote schemas like the XBRL specification are skipped.
+fn resolve_reference(base_dir: &Path, reference: &str) -> Option<PathBuf> {
+    if reference.contains("://") {
+        return None;
+    }
+
+    Some(base_dir.join(reference))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_type_from_schema_type() {
+        assert_eq!(
+            ItemType::from_schema_type("xbrli:monetaryItemType"),
+            ItemType::Monetary
+        );
+        assert_eq!(
+            ItemType::from_schema_type("num:percentItemType"),
+            ItemType::Percent
+        );
+        assert_eq!(
+            ItemType::from_schema_type("xbrli:pureItemType"),
+            ItemType::Pure
+        );
+        assert_eq!(
+            ItemType::from_schema_type("xbrli:positiveIntegerItemType"),
+            ItemType::Integer
+        );
+        assert_eq!(
+            ItemType::from_schema_type("xbrli:stringItemType"),
+            ItemType::NonNumeric
+        );
+    }
+
+    #[test]
+    fn test_parse_concept_types() {
+        let xml = r#"
+            <xs:schema xmlns:de-gaap-ci="http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02"
+                xmlns:xbrli="http://www.xbrl.org/2003/instance"
+                xmlns:xs="http://www.w3.org/2001/XMLSchema"
+                targetNamespace="http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02">
+                <xs:element name="bs.ass" id="de-gaap-ci_bs.ass" substitutionGroup="xbrli:item" type="xbrli:monetaryItemType"/>
+                <xs:element name="nt.particip.listRow.votes" id="de-gaap-ci_nt.particip.listRow.votes" substitutionGroup="xbrli:item" type="xbrli:pureItemType"/>
+                <xs:element name="genInfo.comment" id="de-gaap-ci_genInfo.comment" substitutionGroup="xbrli:item" type="xbrli:stringItemType"/>
+                <xs:element name="bs" abstract="true" id="de-gaap-ci_bs" substitutionGroup="xbrli:item" type="xbrli:stringItemType"/>
+            </xs:schema>
+        "#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let mut concept_types = ConceptTypes::new();
+
+        concept_types.parse(&mut reader).unwrap();
+
+        assert_eq!(concept_types.len(), 4);
+        assert!(concept_types.is_abstract("de-gaap-ci:bs"));
+        assert!(!concept_types.is_abstract("de-gaap-ci:bs.ass"));
+        assert_eq!(
+            concept_types.get("de-gaap-ci:bs.ass"),
+            Some(ItemType::Monetary)
+        );
+        assert_eq!(
+            concept_types.get("de-gaap-ci:nt.particip.listRow.votes"),
+            Some(ItemType::Pure)
+        );
+        assert_eq!(
+            concept_types.get("de-gaap-ci:genInfo.comment"),
+            Some(ItemType::NonNumeric)
+        );
+    }
+
+    #[test]
+    fn test_discover_taxonomy_schema() {
+        let taxonomy_dir = Path::new("../test_data/schema/taxonomy/v6.6");
+        let schema_refs = vec![
+            String::from("http://www.xbrl.de/taxonomies/de-gcd-2022-05-02/de-gcd-2022-05-02-shell.xsd"),
+            String::from("http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02/de-gaap-ci-2022-05-02-shell-fiscal-microbilg.xsd"),
+        ];
+
+        let schema = TaxonomySchema::discover(taxonomy_dir, &schema_refs).unwrap();
+
+        assert_eq!(
+            schema.concept_types.get("de-gaap-ci:bs.ass"),
+            Some(ItemType::Monetary)
+        );
+        assert_eq!(
+            schema
+                .concept_types
+                .get("de-gcd:genInfo.company.id.shareholder.currentnumber"),
+            Some(ItemType::Integer)
+        );
+        assert!(!schema.calculations.is_empty());
+        assert!(schema
+            .presentation
+            .subtree("de-gaap-ci:bs.ass")
+            .contains("de-gaap-ci:bs.ass.fixAss"));
+        assert!(schema.is_mandatory("de-gaap-ci:bs.ass"));
+        assert!(!schema.is_mandatory("de-gaap-ci:bs.ass.unpaidCap.dueCapOfCoop"));
+        assert_eq!(
+            schema.label("de-gcd:genInfo.company.id.name.dateOfLastChange"),
+            Some("letztes Änderungsdatum, Name des Unternehmens")
+        );
+        assert_eq!(
+            schema.label("de-gaap-ci:bs.ass.currAss.receiv.affil.upTo1year"),
+            Some("Forderungen gegen verbundene Unternehmen, mit einer Restlaufzeit bis zu einem Jahr")
+        );
+    }
+}
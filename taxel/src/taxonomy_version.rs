@@ -0,0 +1,202 @@
+use anyhow::anyhow;
+use std::collections::BTreeSet;
+
+/// The base url of the namespaces and schemas of the HGB taxonomies.
+pub(crate) const TAXONOMY_URL: &str = "http://www.xbrl.de/taxonomies/";
+
+/// A release of the HGB taxonomy for the eBilanz.
+///
+/// The ELSTER version (`tax_version`) is tied to the release date of the
+/// taxonomy, which is part of its namespaces and schema files, e.g.
+/// `http://www.xbrl.de/taxonomies/de-gcd-2021-04-14` for version 6.5.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TaxonomyVersion {
+    /// The ELSTER version, e.g. `6.5`.
+    pub version: &'static str,
+    /// The release date in the namespaces, e.g. `2021-04-14`.
+    pub date: &'static str,
+    /// The `version` attribute of `ebilanz:EBilanz`.
+    pub ebilanz_version: &'static str,
+}
+
+/// The supported taxonomy versions, oldest first.
+pub const TAXONOMY_VERSIONS: [TaxonomyVersion; 12] = [
+    TaxonomyVersion::new("5.0", "2011-09-14", "000001"),
+    TaxonomyVersion::new("5.1", "2012-06-01", "000001"),
+    TaxonomyVersion::new("5.2", "2013-05-02", "000001"),
+    TaxonomyVersion::new("5.3", "2014-04-02", "000001"),
+    TaxonomyVersion::new("5.4", "2015-04-01", "000001"),
+    TaxonomyVersion::new("6.0", "2016-04-01", "000002"),
+    TaxonomyVersion::new("6.1", "2017-04-05", "000002"),
+    TaxonomyVersion::new("6.2", "2018-03-31", "000002"),
+    TaxonomyVersion::new("6.3", "2019-04-01", "000002"),
+    TaxonomyVersion::new("6.4", "2020-04-01", "000002"),
+    TaxonomyVersion::new("6.5", "2021-04-14", "000002"),
+    TaxonomyVersion::new("6.6", "2022-05-02", "000002"),
+];
+
+impl TaxonomyVersion {
+    const fn new(version: &'static str, date: &'static str, ebilanz_version: &'static str) -> Self {
+        Self {
+            version,
+            date,
+            ebilanz_version,
+        }
+    }
+
+    /// Get the taxonomy of the given ELSTER version.
+    pub fn get(version: &str) -> Option<&'static Self> {
+        TAXONOMY_VERSIONS
+            .iter()
+            .find(|taxonomy| taxonomy.version == version)
+    }
+
+    /// Get the taxonomy of the given release date.
+    pub fn from_date(date: &str) -> Option<&'static Self> {
+        TAXONOMY_VERSIONS
+            .iter()
+            .find(|taxonomy| taxonomy.date == date)
+    }
+
+    /// The namespace of a module, e.g. `de-gcd` or `de-gaap-ci`.
+    pub fn namespace(&self, module: &str) -> String {
+        format!("{TAXONOMY_URL}{module}-{}", self.date)
+    }
+
+    /// The url of a shell schema of a module, e.g. `shell-fiscal` for
+    /// `de-gaap-ci-2021-04-14-shell-fiscal.xsd`.
+    pub fn shell(&self, module: &str, shell: &str) -> String {
+        format!(
+            "{TAXONOMY_URL}{module}-{date}/{module}-{date}-{shell}.xsd",
+            date = self.date
+        )
+    }
+
+    /// The entry points of the eBilanz, i.e. the general information
+    /// (`de-gcd`) and the financial statements (`de-gaap-ci`).
+    pub fn shells(&self) -> Vec<String> {
+        vec![
+            self.shell("de-gcd", "shell"),
+            self.shell("de-gaap-ci", "shell-fiscal"),
+            self.shell("de-gaap-ci", "shell-fiscal-microbilg"),
+        ]
+    }
+
+    /// Detect the taxonomy version by the namespaces and schema references of
+    /// the xml file.
+    ///
+    /// Returns `None` if the xml file doesn't reference a taxonomy, and an
+    /// error if the release date is unknown or the xml file mixes taxonomy
+    /// versions.
+    pub fn detect(xml: &str) -> Result<Option<&'static Self>, anyhow::Error> {
+        let dates = namespace_dates(xml);
+        let mut versions = vec![];
+
+        for date in &dates {
+            match Self::from_date(date) {
+                Some(taxonomy) => versions.push(taxonomy),
+                None => return Err(anyhow!("Unknown taxonomy release '{date}'")),
+            }
+        }
+
+        match versions.as_slice() {
+            [] => Ok(None),
+            [taxonomy] => Ok(Some(taxonomy)),
+            _ => Err(anyhow!(
+                "Mixed taxonomy versions: {}",
+                versions
+                    .iter()
+                    .map(|taxonomy| format!("{} ({})", taxonomy.version, taxonomy.date))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+        }
+    }
+}
+
+/// The release dates of the taxonomies referenced in the xml file, e.g.
+/// `2021-04-14` for `http://www.xbrl.de/taxonomies/de-gcd-2021-04-14`.
+pub fn namespace_dates(xml: &str) -> BTreeSet<String> {
+    xml.match_indices(TAXONOMY_URL)
+        .filter_map(|(index, _)| {
+            let module = &xml[index + TAXONOMY_URL.len()..];
+            let end = module
+                .find(|c: char| c == '/' || c == '"' || c == '\'' || c.is_whitespace())
+                .unwrap_or(module.len());
+            let module = &module[..end];
+            let date = module.get(module.len().checked_sub(10)?..)?;
+
+            is_date(date).then(|| date.to_owned())
+        })
+        .collect()
+}
+
+/// Check the format `YYYY-MM-DD`.
+pub(crate) fn is_date(value: &str) -> bool {
+    value.len() == 10
+        && value.char_indices().all(|(index, c)| match index {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_detect_taxonomy_version() {
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.4/HandelsbilanzLandwirt_GmbH.xml")
+            .unwrap();
+        assert_eq!(
+            TaxonomyVersion::detect(&xml).unwrap().unwrap().version,
+            "6.4"
+        );
+
+        let xml =
+            fs::read_to_string("../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml")
+                .unwrap();
+        assert_eq!(
+            TaxonomyVersion::detect(&xml).unwrap().unwrap().version,
+            "6.5"
+        );
+
+        let xml = fs::read_to_string("../test_data/taxonomy/v6.6/de-gcd/output.xml").unwrap();
+        assert_eq!(
+            TaxonomyVersion::detect(&xml).unwrap().unwrap().version,
+            "6.6"
+        );
+
+        assert_eq!(TaxonomyVersion::detect("<Elster/>").unwrap(), None);
+    }
+
+    #[test]
+    fn test_detect_mixed_taxonomy_versions() {
+        let xml = r#"<xbrli:xbrl xmlns:de-gcd="http://www.xbrl.de/taxonomies/de-gcd-2021-04-14" xmlns:de-gaap-ci="http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02"/>"#;
+
+        assert_eq!(
+            TaxonomyVersion::detect(xml).unwrap_err().to_string(),
+            "Mixed taxonomy versions: 6.5 (2021-04-14), 6.6 (2022-05-02)"
+        );
+        assert!(TaxonomyVersion::detect(
+            r#"<xbrli:xbrl xmlns:de-gcd="http://www.xbrl.de/taxonomies/de-gcd-2099-01-01"/>"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_taxonomy_version_shells() {
+        let taxonomy = TaxonomyVersion::get("6.6").unwrap();
+
+        assert_eq!(
+            taxonomy.namespace("de-gcd"),
+            "http://www.xbrl.de/taxonomies/de-gcd-2022-05-02"
+        );
+        assert_eq!(
+            taxonomy.shells()[1],
+            "http://www.xbrl.de/taxonomies/de-gaap-ci-2022-05-02/de-gaap-ci-2022-05-02-shell-fiscal.xsd"
+        );
+        assert_eq!(TaxonomyVersion::get("7.0"), None);
+    }
+}
@@ -1,5 +1,11 @@
-use crate::Tags;
+use crate::{
+    calculation::CalculationMismatch,
+    decimal::{Decimal, Rounding},
+    taxonomy::{ConceptTypes, Decimals, ItemType, TaxonomySchema},
+    Fact, SourceMap, Tags, TaxType,
+};
 use anyhow::anyhow;
+use log::warn;
 use quick_xml::{
     events::{
         attributes::{Attribute, Attributes},
@@ -7,19 +13,24 @@ use quick_xml::{
     },
     Reader, Writer,
 };
-use std::{fmt, io::BufRead, str};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    io::BufRead,
+    str,
+};
 
 /// A simple tree structure to store the xml file.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct XbrlElement {
-    name: String,
-    value: Option<String>,
-    attributes: Vec<XbrlAttribute>,
-    xml_type: XmlType,
-    children: Vec<XbrlElement>,
+    pub(crate) name: String,
+    pub(crate) value: Option<String>,
+    pub(crate) attributes: Vec<XbrlAttribute>,
+    pub(crate) xml_type: XmlType,
+    pub(crate) children: Vec<XbrlElement>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum XmlType {
     Plain,
     Xbrl,
@@ -69,8 +80,8 @@ impl fmt::Display for Taxonomy {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct XbrlAttribute {
-    key: String,
-    value: String,
+    pub(crate) key: String,
+    pub(crate) value: String,
 }
 
 impl XbrlAttribute {
@@ -87,16 +98,90 @@ const NIL_ATTRIBUTE: XbrlAttributeBorrowed = XbrlAttributeBorrowed {
     value: "true",
 };
 
-const DECIMALS_2: XbrlAttributeBorrowed = XbrlAttributeBorrowed {
-    key: "decimals",
-    value: "2",
-};
+const DECIMALS: &str = "decimals";
+const UNIT_REF: &str = "unitRef";
+pub(crate) const CONTEXT_REF: &str = "contextRef";
+
+/// The suffix of context ids for the current fiscal year.
+pub(crate) const CURRENT_YEAR: &str = "AKTJAHR";
+/// The suffix of context ids for the previous fiscal year.
+pub(crate) const PREVIOUS_YEAR: &str = "VORJAHR";
+
+/// Check if the context id is of the given period, i.e. its last segment is
+/// the period, e.g. `D-AKTJAHR` for `AKTJAHR` but not `I-VORVORJAHR` for
+/// `VORJAHR`.
+pub(crate) fn is_period(id: &str, period: &str) -> bool {
+    id.rsplit('-').next() == Some(period)
+}
+
+/// Check if the context id is of a prior year, e.g. `I-VORJAHR` or
+/// `I-VORVORJAHR`.
+pub(crate) fn is_prior_year(id: &str) -> bool {
+    id.rsplit('-')
+        .next()
+        .is_some_and(|period| period.ends_with(PREVIOUS_YEAR))
+}
 
 struct XbrlAttributeBorrowed<'a> {
     key: &'a str,
     value: &'a str,
 }
 
+/// The policy to set `decimals` and `unitRef` of numeric facts and to round
+/// their values.
+///
+/// The item type of a fact is looked up in the given concept types. If the
+/// concept is unknown, the item type is derived from the `unitRef` of the
+/// template.
+#[derive(Debug, Default)]
+pub struct DecimalsPolicy {
+    concept_types: ConceptTypes,
+    rounding: Rounding,
+}
+
+impl DecimalsPolicy {
+    pub fn new(concept_types: ConceptTypes, rounding: Rounding) -> Self {
+        Self {
+            concept_types,
+            rounding,
+        }
+    }
+
+    fn item_type(&self, element: &XbrlElement) -> ItemType {
+        self.concept_types
+            .get(&element.name)
+            .unwrap_or_else(|| ItemType::from_unit(element.attribute(UNIT_REF)))
+    }
+
+    /// The unit of a numeric fact.
+    ///
+    /// If the concept is unknown, the `unitRef` of the template is kept, e.g.
+    /// for a custom currency or share unit.
+    fn unit(&self, element: &XbrlElement, item_type: ItemType) -> Option<String> {
+        match self.concept_types.get(&element.name) {
+            Some(_) => item_type.unit().map(str::to_owned),
+            None => element.attribute(UNIT_REF).map(str::to_owned),
+        }
+    }
+
+    /// Round a value according to the given decimals.
+    ///
+    /// Values which are not a decimal number are returned unchanged.
+    fn round(&self, concept: &str, value: &str, decimals: Decimals) -> String {
+        let Decimals::Places(places) = decimals else {
+            return value.to_owned();
+        };
+
+        match value.parse::<Decimal>() {
+            Ok(decimal) => decimal.round(places, self.rounding).to_string(),
+            Err(err) => {
+                warn!("Can't round value for '{concept}': {err}");
+                value.to_owned()
+            }
+        }
+    }
+}
+
 impl XbrlElement {
     /// Create a new XBRL element.
     pub fn new(
@@ -186,9 +271,8 @@ impl XbrlElement {
                     element.children.push(current_element);
                 }
                 Ok(Event::Text(tag)) => {
-                    let inner_value = tag.into_inner();
-                    let tag_value = str::from_utf8(inner_value.as_ref())?;
-                    element.value = Some(tag_value.to_owned());
+                    // The value is escaped again when serialized.
+                    element.value = Some(tag.unescape()?.into_owned());
                 }
                 Ok(Event::Decl(_)) => {
                     return Err(anyhow!("Unexpected xml declaration"));
@@ -248,102 +332,384 @@ impl XbrlElement {
     }
 
     /// Remove all values from `XbrlElement` recursively.
+    ///
+    /// Facts of prior years are retained, e.g. the comparatives of a filing
+    /// rolled over with [`XbrlElement::rollover`].
     pub fn remove_values(&mut self) {
         // Don't remove content for the following keys
         // TODO: refactor retained keys
         if self.name == "xbrli:measure"
             || self.name == "xbrldi:explicitMember"
             || self.name == "de-gaap-ci:dim_keyType"
+            || self.is_prior_year_fact()
         {
             return;
         }
 
         if self.value.is_some() {
-            // Remove value
-            self.value = None;
-
-            if self.xml_type == XmlType::Taxonomy(Taxonomy::Gcd) {
-                // Add `xsi:nil` attribute if not availabe.
-                if !self
-                    .attributes
-                    .iter()
-                    .any(|attribute| attribute.key == NIL_ATTRIBUTE.key)
-                {
-                    self.attributes
-                        .push(XbrlAttribute::new(NIL_ATTRIBUTE.key, NIL_ATTRIBUTE.value));
-                }
+            self.remove_value();
+        }
+
+        for child in &mut self.children {
+            child.remove_values();
+        }
+    }
+
+    fn is_prior_year_fact(&self) -> bool {
+        self.attribute(CONTEXT_REF).is_some_and(is_prior_year)
+    }
+
+    /// Remove the value of the element; facts are marked as nil.
+    fn remove_value(&mut self) {
+        self.value = None;
+
+        if let XmlType::Taxonomy(_) = self.xml_type {
+            // Remove `decimals` attribute; nil facts must not have decimals.
+            self.remove_attribute(DECIMALS);
+
+            // Add `xsi:nil` attribute if not availabe.
+            if self.attribute(NIL_ATTRIBUTE.key).is_none() {
+                self.attributes
+                    .push(XbrlAttribute::new(NIL_ATTRIBUTE.key, NIL_ATTRIBUTE.value));
             }
+        }
+    }
 
-            if self.xml_type == XmlType::Taxonomy(Taxonomy::GaapCi) {
-                // Remove `decimals` attribute
-                if let Some(index) = self
-                    .attributes
-                    .iter()
-                    .position(|attribute| attribute.key == DECIMALS_2.key)
-                {
-                    self.attributes.remove(index);
-                }
+    /// Add given values to `XbrlElement` recursively.
+    ///
+    /// Returns the provenance of the applied values by concept.
+    pub fn add_values(&mut self, target_tags: &Tags) -> SourceMap {
+        self.add_values_with(target_tags, &DecimalsPolicy::default())
+    }
 
-                // Add `xsi:nil` attribute if not availabe.
-                if !self
-                    .attributes
-                    .iter()
-                    .any(|attribute| attribute.key == NIL_ATTRIBUTE.key)
-                {
-                    self.attributes
-                        .push(XbrlAttribute::new(NIL_ATTRIBUTE.key, NIL_ATTRIBUTE.value));
-                }
+    /// Add given values to `XbrlElement` recursively.
+    ///
+    /// The values are the values of the current year; facts of prior years
+    /// are left unchanged. The `decimals` and `unitRef` attributes of numeric
+    /// facts are set according to the item type of the concept, and values
+    /// are rounded accordingly. Returns the provenance of the applied values
+    /// by concept.
+    pub fn add_values_with(&mut self, target_tags: &Tags, policy: &DecimalsPolicy) -> SourceMap {
+        let mut sources = SourceMap::new();
+        self.apply_values(target_tags, policy, &mut sources);
+        sources
+    }
+
+    fn apply_values(
+        &mut self,
+        target_tags: &Tags,
+        policy: &DecimalsPolicy,
+        sources: &mut SourceMap,
+    ) {
+        if self.is_prior_year_fact() {
+            return;
+        }
+
+        if let Some(Some(value)) = target_tags.get(&self.name) {
+            self.apply_value(value, policy);
+
+            if let Some(provenance) = target_tags.provenance(&self.name) {
+                sources.insert(self.name.clone(), provenance.clone());
             }
         }
 
         for child in &mut self.children {
-            child.remove_values();
+            child.apply_values(target_tags, policy, sources);
         }
     }
 
-    /// Add given values to `XbrlElement` recursively.
-    pub fn add_values(&mut self, target_tags: &Tags) {
-        if let Some(Some(value)) = target_tags.get(&self.name) {
-            self.value = Some(value.to_owned());
-
-            if self.xml_type == XmlType::Taxonomy(Taxonomy::Gcd) {
-                // Remove `xsi:nil` attribute
-                if let Some(index) = self
-                    .attributes
-                    .iter()
-                    .position(|attribute| attribute.key == NIL_ATTRIBUTE.key)
-                {
-                    self.attributes.remove(index);
+    /// Add the given values like `taxel generate`.
+    ///
+    /// The procedure and data type of the transfer header are set for the
+    /// given tax type. The taxonomy schema determines `decimals` and `unitRef`
+    /// of numeric facts, and the calculations are checked after rounding.
+    /// Returns the totals which don't add up, and the provenance of the
+    /// applied values by concept.
+    pub fn generate_values(
+        &mut self,
+        mut target_tags: Tags,
+        tax_type: TaxType,
+        schema: &TaxonomySchema,
+        rounding: Rounding,
+    ) -> (Vec<CalculationMismatch>, SourceMap) {
+        target_tags.add_required_tags(tax_type);
+        target_tags.remove_unsupported_tags();
+        let policy = DecimalsPolicy::new(schema.concept_types.clone(), rounding);
+        let sources = self.add_values_with(&target_tags, &policy);
+        self.add_missing_units();
+
+        (schema.calculations.check(&self.facts(), rounding), sources)
+    }
+
+    /// Set the value of the element and the `decimals` and `unitRef`
+    /// attributes of facts.
+    fn apply_value(&mut self, value: &str, policy: &DecimalsPolicy) {
+        self.value = Some(value.to_owned());
+
+        if let XmlType::Taxonomy(_) = self.xml_type {
+            // Remove `xsi:nil` attribute
+            self.remove_attribute(NIL_ATTRIBUTE.key);
+
+            let item_type = policy.item_type(self);
+
+            match (policy.unit(self, item_type), item_type.decimals()) {
+                (Some(unit), Some(decimals)) => {
+                    self.set_attribute(UNIT_REF, &unit);
+                    self.set_attribute(DECIMALS, &decimals.to_string());
+                    self.value = Some(policy.round(&self.name, value, decimals));
+                }
+                _ => {
+                    // Non-numeric facts must not have a unit or decimals.
+                    self.remove_attribute(UNIT_REF);
+                    self.remove_attribute(DECIMALS);
                 }
             }
+        }
+    }
+
+    /// Set the value of the fact at the given index of
+    /// [`XbrlElement::facts`] like [`XbrlElement::add_values_with`]; `None`
+    /// marks the fact as nil.
+    ///
+    /// Unlike `add_values_with`, only a single fact is updated, which is
+    /// necessary for facts of tuples sharing concept and context, and facts of
+    /// the previous year can be updated as well. Returns `false` if the fact
+    /// is not available.
+    pub fn set_fact_value(
+        &mut self,
+        index: usize,
+        value: Option<&str>,
+        policy: &DecimalsPolicy,
+    ) -> bool {
+        let mut remaining = index;
+        self.set_nth_fact_value(&mut remaining, value, policy)
+    }
 
-            if self.xml_type == XmlType::Taxonomy(Taxonomy::GaapCi) {
-                // Remove `xsi:nil` attribute
-                if let Some(index) = self
-                    .attributes
-                    .iter()
-                    .position(|attribute| attribute.key == NIL_ATTRIBUTE.key)
-                {
-                    self.attributes.remove(index);
+    fn set_nth_fact_value(
+        &mut self,
+        remaining: &mut usize,
+        value: Option<&str>,
+        policy: &DecimalsPolicy,
+    ) -> bool {
+        if self.attribute(CONTEXT_REF).is_some() {
+            if *remaining == 0 {
+                match value {
+                    Some(value) => self.apply_value(value, policy),
+                    None => self.remove_value(),
                 }
 
-                // Add `decimals` attribute if not availabe.
-                if !self
-                    .attributes
-                    .iter()
-                    .any(|attribute| attribute.key == DECIMALS_2.key)
-                {
-                    self.attributes
-                        .push(XbrlAttribute::new(DECIMALS_2.key, DECIMALS_2.value))
+                return true;
+            }
+
+            *remaining -= 1;
+        }
+
+        self.children
+            .iter_mut()
+            .any(|child| child.set_nth_fact_value(remaining, value, policy))
+    }
+
+    /// Declare missing units which are referenced by facts.
+    ///
+    /// Only the units `EUR` and `PURE` are supported.
+    pub fn add_missing_units(&mut self) {
+        if self.xml_type != XmlType::Xbrl || self.name != "xbrli:xbrl" {
+            for child in &mut self.children {
+                child.add_missing_units();
+            }
+
+            return;
+        }
+
+        let mut referenced_units = vec![];
+        self.collect_units(&mut referenced_units);
+
+        for unit in referenced_units {
+            let is_declared = self.children.iter().any(|child| {
+                child.name == "xbrli:unit" && child.attribute("id") == Some(unit.as_str())
+            });
+
+            if is_declared {
+                continue;
+            }
+
+            let measure = match unit.as_str() {
+                "EUR" => "iso4217:EUR",
+                "PURE" => "xbrli:pure",
+                unit => {
+                    warn!("Can't declare unknown unit '{unit}'");
+                    continue;
                 }
+            };
+            let unit_element = XbrlElement::new(
+                "xbrli:unit",
+                None,
+                vec![XbrlAttribute::new("id", unit)],
+                XmlType::Xbrl,
+                vec![XbrlElement::new(
+                    "xbrli:measure",
+                    Some(measure.to_owned()),
+                    vec![],
+                    XmlType::Xbrl,
+                    vec![],
+                )],
+            );
+
+            self.insert_declaration(unit_element);
+        }
+    }
+
+    /// Insert a context or unit after the last context or unit.
+    pub(crate) fn insert_declaration(&mut self, declaration: XbrlElement) {
+        let index = self
+            .children
+            .iter()
+            .rposition(|child| child.name == "xbrli:context" || child.name == "xbrli:unit")
+            .map(|index| index + 1)
+            .unwrap_or(self.children.len());
+        self.children.insert(index, declaration);
+    }
+
+    fn collect_units(&self, units: &mut Vec<String>) {
+        if let Some(unit) = self.attribute(UNIT_REF) {
+            if !units.iter().any(|el| el == unit) {
+                units.push(unit.to_owned());
             }
         }
 
-        for child in &mut self.children {
-            child.add_values(target_tags);
+        for child in &self.children {
+            child.collect_units(units);
         }
     }
 
+    /// Collect all facts, i.e. elements with a `contextRef`, recursively.
+    pub fn facts(&self) -> Vec<Fact> {
+        let mut facts = vec![];
+        self.collect_facts(&mut facts);
+        facts
+    }
+
+    fn collect_facts(&self, facts: &mut Vec<Fact>) {
+        if let Some(context) = self.attribute(CONTEXT_REF) {
+            facts.push(Fact::new(
+                &self.name,
+                context,
+                self.attribute(UNIT_REF).map(|unit| unit.to_owned()),
+                self.attribute(DECIMALS).map(|decimals| decimals.to_owned()),
+                self.value.clone(),
+                self.attribute(NIL_ATTRIBUTE.key) == Some(NIL_ATTRIBUTE.value),
+            ));
+        }
+
+        for child in &self.children {
+            child.collect_facts(facts);
+        }
+    }
+
+    /// Collect the names of the elements whose value can be set with
+    /// [`XbrlElement::add_values`].
+    pub fn element_names(&self) -> HashSet<&str> {
+        let mut names = HashSet::new();
+        self.collect_element_names(&mut names);
+        names
+    }
+
+    fn collect_element_names<'a>(&'a self, names: &mut HashSet<&'a str>) {
+        names.insert(self.name.as_str());
+
+        for child in &self.children {
+            child.collect_element_names(names);
+        }
+    }
+
+    /// Collect the explicit members of the contexts by context id, i.e. the
+    /// pairs of dimension and member, e.g. `de-gaap-ci:dim_taxTrans` and
+    /// `de-gaap-ci:dim_taxBal`.
+    ///
+    /// Contexts without dimensions are omitted.
+    pub fn context_dimensions(&self) -> HashMap<String, Vec<(String, String)>> {
+        let mut dimensions = HashMap::new();
+        self.collect_context_dimensions(&mut dimensions);
+        dimensions
+    }
+
+    fn collect_context_dimensions(&self, dimensions: &mut HashMap<String, Vec<(String, String)>>) {
+        if self.name == "xbrli:context" {
+            if let Some(id) = self.attribute("id") {
+                let mut members = vec![];
+                self.collect_explicit_members(&mut members);
+
+                if !members.is_empty() {
+                    dimensions.insert(id.to_owned(), members);
+                }
+            }
+
+            return;
+        }
+
+        for child in &self.children {
+            child.collect_context_dimensions(dimensions);
+        }
+    }
+
+    fn collect_explicit_members(&self, members: &mut Vec<(String, String)>) {
+        if self.name == "xbrldi:explicitMember" {
+            if let (Some(dimension), Some(member)) = (self.attribute("dimension"), &self.value) {
+                members.push((dimension.to_owned(), member.trim().to_owned()));
+            }
+        }
+
+        for child in &self.children {
+            child.collect_explicit_members(members);
+        }
+    }
+
+    /// Collect the `xlink:href` of all `link:schemaRef` elements recursively.
+    pub fn schema_refs(&self) -> Vec<String> {
+        let mut schema_refs = vec![];
+
+        if self.name == "link:schemaRef" {
+            if let Some(href) = self.attribute("xlink:href") {
+                schema_refs.push(href.to_owned());
+            }
+        }
+
+        for child in &self.children {
+            schema_refs.extend(child.schema_refs());
+        }
+
+        schema_refs
+    }
+
+    /// Check if the element is a fact with a value, i.e. not marked as nil.
+    pub(crate) fn has_value(&self) -> bool {
+        self.value.is_some() && self.attribute(NIL_ATTRIBUTE.key) != Some(NIL_ATTRIBUTE.value)
+    }
+
+    /// Get the value of an attribute.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.key == key)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Set the value of an attribute; the attribute is added if not available.
+    pub(crate) fn set_attribute(&mut self, key: &str, value: &str) {
+        match self
+            .attributes
+            .iter_mut()
+            .find(|attribute| attribute.key == key)
+        {
+            Some(attribute) => attribute.value = value.to_owned(),
+            None => self.attributes.push(XbrlAttribute::new(key, value)),
+        }
+    }
+
+    fn remove_attribute(&mut self, key: &str) {
+        self.attributes.retain(|attribute| attribute.key != key);
+    }
+
     fn convert_tag(tag: BytesStart) -> Result<XbrlElement, anyhow::Error> {
         let tag_name = tag.name();
         let name = str::from_utf8(tag_name.as_ref())?;
@@ -357,7 +723,7 @@ impl XbrlElement {
         ))
     }
 
-    fn get_xml_type(name: &str) -> XmlType {
+    pub(crate) fn get_xml_type(name: &str) -> XmlType {
         if name.contains(XmlType::Xbrl.as_str()) {
             XmlType::Xbrl
         } else if name.contains(Taxonomy::Gcd.as_str()) {
@@ -387,7 +753,8 @@ impl XbrlElement {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Cursor;
+    use crate::Provenance;
+    use std::{fs, io::Cursor};
 
     #[test]
     fn test_add_values() {
@@ -552,6 +919,374 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_add_values_provenance() {
+        let mut element = XbrlElement::new(
+            "xbrli:xbrl",
+            None,
+            vec![],
+            XmlType::Xbrl,
+            vec![
+                XbrlElement::new(
+                    "de-gaap-ci:bs.ass",
+                    None,
+                    vec![XbrlAttribute::new("contextRef", "I-AKTJAHR")],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+                XbrlElement::new(
+                    "de-gaap-ci:bs.eqLiab",
+                    None,
+                    vec![XbrlAttribute::new("contextRef", "I-AKTJAHR")],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+            ],
+        );
+        let mut target_tags = Tags::new();
+        target_tags.insert_with_provenance(
+            "de-gaap-ci:bs.ass",
+            Some("300.00"),
+            Provenance::new("balance.csv", 2, 2),
+        );
+        target_tags.insert("de-gaap-ci:bs.eqLiab", Some("300.00"));
+        target_tags.insert_with_provenance(
+            "de-gaap-ci:bs.ass.fixAss",
+            Some("300.00"),
+            Provenance::new("balance.csv", 3, 2),
+        );
+
+        let sources = element.add_values(&target_tags);
+
+        assert_eq!(sources.len(), 1);
+        assert_eq!(
+            sources.get("de-gaap-ci:bs.ass"),
+            Some(&Provenance::new("balance.csv", 2, 2))
+        );
+    }
+
+    #[test]
+    fn test_add_values_with_policy() {
+        let mut element = XbrlElement::new(
+            "xbrli:xbrl",
+            None,
+            vec![],
+            XmlType::Xbrl,
+            vec![
+                XbrlElement::new(
+                    "de-gaap-ci:bs.ass",
+                    None,
+                    vec![
+                        XbrlAttribute::new("contextRef", "I-AKTJAHR"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+                XbrlElement::new(
+                    "de-gaap-ci:nt.particip.listRow.votes",
+                    None,
+                    vec![
+                        XbrlAttribute::new("contextRef", "D-AKTJAHR"),
+                        XbrlAttribute::new("unitRef", "EUR"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+                XbrlElement::new(
+                    "de-gaap-ci:genInfo.comment",
+                    None,
+                    vec![
+                        XbrlAttribute::new("contextRef", "D-AKTJAHR"),
+                        XbrlAttribute::new("decimals", "2"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+                // The concept is unknown, so the unit of the template is kept.
+                XbrlElement::new(
+                    "de-gaap-ci:nt.particip.listRow.capital",
+                    None,
+                    vec![
+                        XbrlAttribute::new("contextRef", "D-AKTJAHR"),
+                        XbrlAttribute::new("unitRef", "USD"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+            ],
+        );
+        let mut concept_types = ConceptTypes::new();
+        concept_types.insert("de-gaap-ci:bs.ass", ItemType::Monetary);
+        concept_types.insert("de-gaap-ci:nt.particip.listRow.votes", ItemType::Pure);
+        concept_types.insert("de-gaap-ci:genInfo.comment", ItemType::NonNumeric);
+        let policy = DecimalsPolicy::new(concept_types, Rounding::HalfUp);
+        let mut target_tags = Tags::new();
+        target_tags.insert("de-gaap-ci:bs.ass", Some("1000.125"));
+        target_tags.insert("de-gaap-ci:nt.particip.listRow.votes", Some("0.125"));
+        target_tags.insert("de-gaap-ci:genInfo.comment", Some("comment"));
+        target_tags.insert("de-gaap-ci:nt.particip.listRow.capital", Some("12.5"));
+
+        element.add_values_with(&target_tags, &policy);
+
+        assert_eq!(
+            element,
+            XbrlElement::new(
+                "xbrli:xbrl",
+                None,
+                vec![],
+                XmlType::Xbrl,
+                vec![
+                    XbrlElement::new(
+                        "de-gaap-ci:bs.ass",
+                        Some(String::from("1000.13")),
+                        vec![
+                            XbrlAttribute::new("contextRef", "I-AKTJAHR"),
+                            XbrlAttribute::new("unitRef", "EUR"),
+                            XbrlAttribute::new("decimals", "2"),
+                        ],
+                        XmlType::Taxonomy(Taxonomy::GaapCi),
+                        vec![],
+                    ),
+                    XbrlElement::new(
+                        "de-gaap-ci:nt.particip.listRow.votes",
+                        Some(String::from("0.125")),
+                        vec![
+                            XbrlAttribute::new("contextRef", "D-AKTJAHR"),
+                            XbrlAttribute::new("unitRef", "PURE"),
+                            XbrlAttribute::new("decimals", "INF"),
+                        ],
+                        XmlType::Taxonomy(Taxonomy::GaapCi),
+                        vec![],
+                    ),
+                    XbrlElement::new(
+                        "de-gaap-ci:genInfo.comment",
+                        Some(String::from("comment")),
+                        vec![XbrlAttribute::new("contextRef", "D-AKTJAHR")],
+                        XmlType::Taxonomy(Taxonomy::GaapCi),
+                        vec![],
+                    ),
+                    XbrlElement::new(
+                        "de-gaap-ci:nt.particip.listRow.capital",
+                        Some(String::from("12.5")),
+                        vec![
+                            XbrlAttribute::new("contextRef", "D-AKTJAHR"),
+                            XbrlAttribute::new("unitRef", "USD"),
+                            XbrlAttribute::new("decimals", "INF"),
+                        ],
+                        XmlType::Taxonomy(Taxonomy::GaapCi),
+                        vec![],
+                    ),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_set_fact_value() {
+        let fact = |context: &str, value: Option<&str>, attributes: Vec<XbrlAttribute>| {
+            let mut attributes = attributes;
+            attributes.insert(0, XbrlAttribute::new("contextRef", context));
+            XbrlElement::new(
+                "de-gaap-ci:bs.ass",
+                value.map(String::from),
+                attributes,
+                XmlType::Taxonomy(Taxonomy::GaapCi),
+                vec![],
+            )
+        };
+        let mut element = XbrlElement::new(
+            "xbrli:xbrl",
+            None,
+            vec![],
+            XmlType::Xbrl,
+            vec![
+                fact(
+                    "I-AKTJAHR",
+                    None,
+                    vec![XbrlAttribute::new("xsi:nil", "true")],
+                ),
+                fact(
+                    "I-VORJAHR",
+                    Some("200.00"),
+                    vec![
+                        XbrlAttribute::new("unitRef", "EUR"),
+                        XbrlAttribute::new("decimals", "2"),
+                    ],
+                ),
+            ],
+        );
+        let mut concept_types = ConceptTypes::new();
+        concept_types.insert("de-gaap-ci:bs.ass", ItemType::Monetary);
+        let policy = DecimalsPolicy::new(concept_types, Rounding::HalfUp);
+
+        assert!(element.set_fact_value(0, Some("1000.125"), &policy));
+        assert!(element.set_fact_value(1, None, &policy));
+        assert!(!element.set_fact_value(2, Some("1"), &policy));
+
+        assert_eq!(
+            element.children,
+            vec![
+                fact(
+                    "I-AKTJAHR",
+                    Some("1000.13"),
+                    vec![
+                        XbrlAttribute::new("unitRef", "EUR"),
+                        XbrlAttribute::new("decimals", "2"),
+                    ],
+                ),
+                fact(
+                    "I-VORJAHR",
+                    None,
+                    vec![
+                        XbrlAttribute::new("unitRef", "EUR"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context_dimensions() {
+        let xml =
+            fs::read_to_string("../test_data/taxonomy/v6.5/SteuerbilanzAutoverkaeufer_PersG.xml")
+                .unwrap();
+        let mut reader = Reader::from_str(&xml);
+        reader.trim_text(true);
+        let element = XbrlElement::parse(&mut reader).unwrap();
+
+        let dimensions = element.context_dimensions();
+
+        assert!(!dimensions.contains_key("I-AKTJAHR"));
+        assert!(dimensions.values().any(|members| members.contains(&(
+            String::from("de-gaap-ci:dim_taxTrans"),
+            String::from("de-gaap-ci:dim_taxBal")
+        ))));
+    }
+
+    #[test]
+    fn test_element_names() {
+        let xml = r#"<ebilanz:EBilanz><TransferHeader><Empfaenger id="F">1111</Empfaenger></TransferHeader><xbrli:xbrl><de-gaap-ci:bs.ass contextRef="I-AKTJAHR">1.00</de-gaap-ci:bs.ass><de-gaap-ci:bs.eqLiab contextRef="I-VORJAHR">1.00</de-gaap-ci:bs.eqLiab></xbrli:xbrl></ebilanz:EBilanz>"#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+        let element = XbrlElement::parse(&mut reader).unwrap();
+
+        let names = element.element_names();
+
+        assert!(names.contains("Empfaenger"));
+        assert!(names.contains("de-gaap-ci:bs.ass"));
+        assert!(names.contains("de-gaap-ci:bs.eqLiab"));
+    }
+
+    #[test]
+    fn test_parse_and_serialize_escaped_value() {
+        let xml = r#"<de-gcd:genInfo.company.id.name contextRef="D-AKTJAHR">Müller &amp; Söhne</de-gcd:genInfo.company.id.name>"#;
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let element = XbrlElement::parse(&mut reader).unwrap();
+        assert_eq!(element.value.as_deref(), Some("Müller & Söhne"));
+
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        element.serialize(&mut writer).unwrap();
+        assert_eq!(
+            String::from_utf8(writer.into_inner().into_inner()).unwrap(),
+            xml
+        );
+    }
+
+    #[test]
+    fn test_add_missing_units() {
+        let mut element = XbrlElement::new(
+            "xbrli:xbrl",
+            None,
+            vec![],
+            XmlType::Xbrl,
+            vec![
+                XbrlElement::new("xbrli:context", None, vec![], XmlType::Xbrl, vec![]),
+                XbrlElement::new(
+                    "de-gaap-ci:bs.ass",
+                    Some(String::from("100.00")),
+                    vec![
+                        XbrlAttribute::new("contextRef", "I-AKTJAHR"),
+                        XbrlAttribute::new("unitRef", "EUR"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+            ],
+        );
+
+        element.add_missing_units();
+
+        assert_eq!(element.children[1].name, "xbrli:unit");
+        assert_eq!(element.children[1].attribute("id"), Some("EUR"));
+        assert_eq!(
+            element.children[1].children[0].value.as_deref(),
+            Some("iso4217:EUR")
+        );
+
+        // Units are declared only once.
+        element.add_missing_units();
+        assert_eq!(element.children.len(), 3);
+    }
+
+    #[test]
+    fn test_facts() {
+        let element = XbrlElement::new(
+            "xbrli:xbrl",
+            None,
+            vec![],
+            XmlType::Xbrl,
+            vec![
+                XbrlElement::new(
+                    "de-gcd:genInfo.report.audit.city",
+                    Some(String::from("Berlin")),
+                    vec![XbrlAttribute::new("contextRef", "D-AKTJAHR")],
+                    XmlType::Taxonomy(Taxonomy::Gcd),
+                    vec![],
+                ),
+                XbrlElement::new(
+                    "de-gaap-ci:bs.ass",
+                    None,
+                    vec![
+                        XbrlAttribute::new("contextRef", "I-AKTJAHR"),
+                        XbrlAttribute::new("unitRef", "EUR"),
+                        XbrlAttribute::new("xsi:nil", "true"),
+                    ],
+                    XmlType::Taxonomy(Taxonomy::GaapCi),
+                    vec![],
+                ),
+            ],
+        );
+
+        assert_eq!(
+            element.facts(),
+            vec![
+                Fact::new(
+                    "de-gcd:genInfo.report.audit.city",
+                    "D-AKTJAHR",
+                    None,
+                    None,
+                    Some(String::from("Berlin")),
+                    false
+                ),
+                Fact::new(
+                    "de-gaap-ci:bs.ass",
+                    "I-AKTJAHR",
+                    Some(String::from("EUR")),
+                    None,
+                    None,
+                    true
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_remove_values() {
         let mut element = XbrlElement::new(
@@ -671,6 +1406,62 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_values_of_previous_year() {
+        let fact = |context: &str, value: Option<&str>| {
+            let mut attributes = vec![
+                XbrlAttribute::new("contextRef", context),
+                XbrlAttribute::new("unitRef", "EUR"),
+            ];
+
+            match value {
+                Some(_) => attributes.push(XbrlAttribute::new("decimals", "2")),
+                None => attributes.push(XbrlAttribute::new("xsi:nil", "true")),
+            }
+
+            XbrlElement::new(
+                "de-gaap-ci:bs.ass",
+                value.map(String::from),
+                attributes,
+                XmlType::Taxonomy(Taxonomy::GaapCi),
+                vec![],
+            )
+        };
+        let xbrl = |children| XbrlElement::new("xbrli:xbrl", None, vec![], XmlType::Xbrl, children);
+        let mut element = xbrl(vec![
+            fact("I-AKTJAHR", Some("300.00")),
+            fact("I-VORJAHR", Some("200.00")),
+            fact("I-VORVORJAHR", None),
+        ]);
+
+        // Facts of prior years are retained, e.g. after a rollover.
+        element.remove_values();
+
+        assert_eq!(
+            element,
+            xbrl(vec![
+                fact("I-AKTJAHR", None),
+                fact("I-VORJAHR", Some("200.00")),
+                fact("I-VORVORJAHR", None),
+            ])
+        );
+
+        // The values are only applied to the current year.
+        let mut target_tags = Tags::new();
+        target_tags.insert("de-gaap-ci:bs.ass", Some("100.00"));
+
+        element.add_values(&target_tags);
+
+        assert_eq!(
+            element,
+            xbrl(vec![
+                fact("I-AKTJAHR", Some("100.00")),
+                fact("I-VORJAHR", Some("200.00")),
+                fact("I-VORVORJAHR", None),
+            ])
+        );
+    }
+
     #[test]
     fn test_remove_values_xbrl_gaap_empty() {
         let mut element = XbrlElement::new(
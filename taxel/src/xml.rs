@@ -31,7 +31,7 @@ where
             }
             Ok(Event::Empty(_)) => continue,
             Ok(Event::Text(tag)) => {
-                tag_value = Some(tag.clone().into_owned());
+                tag_value = Some(tag.unescape()?.into_owned());
             }
             Ok(Event::Decl(_)) => continue,
             Ok(Event::Eof) => {
@@ -52,8 +52,7 @@ where
 
             if start_tag_name == end_tag_name {
                 let tag_name = str::from_utf8(start_tag_name.as_ref())?;
-                let value = str::from_utf8(value.as_ref())?;
-                let extracted_tag = Tag::new(tag_name, Some(value));
+                let extracted_tag = Tag::new(tag_name, Some(value.as_str()));
                 extracted_tags.push(extracted_tag);
 
                 // Reset state
@@ -152,6 +151,18 @@ mod tests {
         test_extract_tag_values(xml, expected_tags);
     }
 
+    #[test]
+    fn test_extract_tag_values_escaped() {
+        let xml = r#"
+            <root>
+                <tag>Müller &amp; Söhne</tag>
+            </root>
+        "#;
+        let expected_tags = vec![Tag::new("tag", Some("Müller & Söhne"))];
+
+        test_extract_tag_values(xml, expected_tags);
+    }
+
     #[test]
     fn test_extract_tag_values_multiple() {
         let xml = r#"